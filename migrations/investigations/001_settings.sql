@@ -0,0 +1,5 @@
+-- Investigation settings stored as key/value pairs
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Numeric samples extracted from monitor output, one row per series per reading
+CREATE TABLE samples (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    widget_id INTEGER NOT NULL,
+    widget_version INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    series TEXT NOT NULL,
+    value REAL NOT NULL,
+    FOREIGN KEY(widget_id) REFERENCES widgets(id)
+);
+
+CREATE INDEX idx_samples_widget_timestamp ON samples(widget_id, timestamp);
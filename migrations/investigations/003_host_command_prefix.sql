@@ -0,0 +1,2 @@
+-- Words put in front of every command run on a host, e.g. "docker exec web" or "sudo -u svc"
+ALTER TABLE hosts ADD COLUMN command_prefix TEXT NOT NULL DEFAULT '';
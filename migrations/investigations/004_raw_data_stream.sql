@@ -0,0 +1,2 @@
+-- Which output stream a recorded line came from: 'stdout' or 'stderr'
+ALTER TABLE raw_data ADD COLUMN stream TEXT NOT NULL DEFAULT 'stdout';
@@ -0,0 +1,4 @@
+-- Connection details passed to ssh as -p, -l and -i; NULL leaves them to ~/.ssh/config
+ALTER TABLE hosts ADD COLUMN port INTEGER;
+ALTER TABLE hosts ADD COLUMN username TEXT;
+ALTER TABLE hosts ADD COLUMN identity_file TEXT;
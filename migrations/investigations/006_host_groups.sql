@@ -0,0 +1,5 @@
+-- Hosts sharing a group name can be targeted together; empty for no group
+ALTER TABLE hosts ADD COLUMN host_group TEXT NOT NULL DEFAULT '';
+
+-- Which host a line came from when a command fans out to a group; NULL for single-host runs
+ALTER TABLE raw_data ADD COLUMN host TEXT;
@@ -0,0 +1,3 @@
+-- Replay looks up a widget's lines recorded before a moment; the single-column indexes
+-- make SQLite pick one and filter the other row by row
+CREATE INDEX idx_raw_data_widget_timestamp ON raw_data(widget_id, timestamp);
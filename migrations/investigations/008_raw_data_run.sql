@@ -0,0 +1,3 @@
+-- Which command run a recorded line came from, by the run's start time (µs since the epoch).
+-- NULL for lines recorded before runs were tracked
+ALTER TABLE raw_data ADD COLUMN run INTEGER;
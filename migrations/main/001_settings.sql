@@ -0,0 +1,5 @@
+-- Application settings stored as key/value pairs
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
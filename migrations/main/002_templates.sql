@@ -0,0 +1,7 @@
+-- Named widget setups that can be applied to new investigations
+CREATE TABLE templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    widgets_json TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
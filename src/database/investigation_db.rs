@@ -1,6 +1,8 @@
-use sqlx::{SqlitePool, Row, sqlite::SqliteConnectOptions};
+use sqlx::{SqlitePool, Row, Connection, sqlite::{SqliteConnectOptions, SqliteConnection, SqliteRow}};
 use std::path::PathBuf;
 use crate::widgets::Widget;
+use crate::widgets::command_widget::{OutputStream, TimedLine};
+use crate::database::raw_data_writer::RecordedLine;
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,12 +11,144 @@ pub struct Host {
     pub name: String,        // Display name like "Production Server"
     pub ssh_alias: String,   // SSH alias like "prod-server" or "user@hostname"
     pub description: String,
+    #[serde(default)]
+    pub command_prefix: String,  // Prepended to every command on this host, e.g. "docker exec web"
+    // Passed to ssh when set; otherwise ~/.ssh/config (or ssh's defaults) decides
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    #[serde(default)]
+    pub group: String,  // Hosts with the same group can run a command together; empty for none
     pub is_localhost: bool,
 }
 
+impl Host {
+    // A remote host named after its alias, with no connection options or group
+    #[cfg(test)]
+    pub fn for_test(ssh_alias: &str, command_prefix: &str) -> Self {
+        Host {
+            id: None,
+            name: ssh_alias.to_string(),
+            ssh_alias: ssh_alias.to_string(),
+            description: String::new(),
+            command_prefix: command_prefix.to_string(),
+            port: None,
+            username: None,
+            identity_file: None,
+            group: String::new(),
+            is_localhost: false,
+        }
+    }
+    
+    // The local machine itself; a local alias with a prefix (e.g. a local container) counts as its own host
+    pub fn is_plain_localhost(ssh_alias: &str, command_prefix: &str) -> bool {
+        (ssh_alias == "localhost" || ssh_alias == "127.0.0.1") && command_prefix.trim().is_empty()
+    }
+    
+    // Empty means "let ssh decide"
+    pub fn parse_port(text: &str) -> Result<Option<u16>, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        match text.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(Some(port)),
+            _ => Err("Port must be a number from 1 to 65535".to_string()),
+        }
+    }
+    
+    // Trimmed text from a form field, None when left empty
+    pub fn optional_field(text: &str) -> Option<String> {
+        Some(text.trim().to_string()).filter(|text| !text.is_empty())
+    }
+    
+    // Options to put before the alias on the ssh command line
+    pub fn ssh_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(port) = self.port {
+            options.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(ref username) = self.username {
+            options.extend(["-l".to_string(), username.clone()]);
+        }
+        if let Some(ref identity_file) = self.identity_file {
+            options.extend(["-i".to_string(), identity_file.clone()]);
+        }
+        options
+    }
+    
+    // An ~/.ssh/config alias or [user@]host. The alias is passed to ssh as one argument, so a leading
+    // dash would be read as an option and whitespace can never match a host
+    pub fn validate_ssh_alias(ssh_alias: &str) -> Result<(), String> {
+        if ssh_alias.is_empty() {
+            return Err("SSH alias is required".to_string());
+        }
+        if ssh_alias.chars().any(char::is_whitespace) {
+            return Err("SSH alias can't contain spaces".to_string());
+        }
+        if ssh_alias.starts_with('-') {
+            return Err("SSH alias can't start with '-'".to_string());
+        }
+        let host = match ssh_alias.rsplit_once('@') {
+            Some((user, host)) => {
+                if user.is_empty() {
+                    return Err("Missing user before '@'".to_string());
+                }
+                host
+            }
+            None => ssh_alias,
+        };
+        if host.is_empty() {
+            return Err("Missing host after '@'".to_string());
+        }
+        if let Some(c) = host.chars().find(|c| !(c.is_alphanumeric() || ".-_:[]%".contains(*c))) {
+            return Err(format!("'{}' isn't allowed in a host name", c));
+        }
+        Ok(())
+    }
+}
+
+// Position and size written for widgets before layouts were saved
+const UNSAVED_LAYOUT: crate::widgets::WidgetLayout = [0.0, 0.0, 600.0, 400.0];
+
+// How long `release` waits for the pool's other holders, longer than a recorder's flush interval
+const RELEASE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Output lookups for restoring and replaying widgets, shared with the test that keeps them on an index.
+// Lines come back in recording order: ORDER BY id follows the rowid each index entry ends in, so no sort is needed
+const WIDGET_VERSION_LINES: &str = "SELECT line_content FROM raw_data WHERE widget_id = ? AND widget_version = ? ORDER BY id ASC";
+const WIDGET_VERSION_TIMED_LINES: &str = "SELECT timestamp, line_content, stream FROM raw_data WHERE widget_id = ? AND widget_version = ? ORDER BY id ASC";
+const WIDGET_LINES_AT_TIME: &str = "SELECT timestamp, line_content, stream FROM raw_data WHERE widget_id = ? AND timestamp <= ?
+     AND widget_version = (SELECT MAX(widget_version) FROM raw_data WHERE widget_id = ? AND timestamp <= ?)
+     ORDER BY id ASC";
+
+// Tries at compacting while the file is busy, waiting a little longer after each
+const COMPACT_ATTEMPTS: u32 = 5;
+const COMPACT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+// SQLITE_BUSY or SQLITE_LOCKED, including their extended codes
+fn is_busy(error: &sqlx::Error) -> bool {
+    error.as_database_error()
+        .and_then(|e| e.code())
+        .and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| matches!(code & 0xff, 5 | 6))
+}
+
 #[derive(Clone)]
 pub struct InvestigationDB {
     pool: SqlitePool,
+    file_path: PathBuf,
+}
+
+// Column names and stringified rows from an ad-hoc query
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub truncated: bool,
 }
 
 impl InvestigationDB {
@@ -23,7 +157,7 @@ impl InvestigationDB {
             .filename(file_path)
             .create_if_missing(true);
         let pool = SqlitePool::connect_with(options).await?;
-        let mut db = Self { pool };
+        let mut db = Self { pool, file_path: file_path.clone() };
         db.initialize(name, description, color).await?;
         Ok(db)
     }
@@ -33,26 +167,45 @@ impl InvestigationDB {
             .filename(file_path)
             .create_if_missing(false);
         let pool = SqlitePool::connect_with(options).await?;
-        let db = Self { pool };
+        let db = Self { pool, file_path: file_path.clone() };
         
         // Run migrations for existing databases
-        sqlx::migrate!("./migrations/investigations").run(&db.pool).await?;
+        crate::database::migrate(&db.pool, &crate::database::INVESTIGATION_MIGRATOR).await?;
+        
+        if !crate::redaction::is_loaded(file_path) {
+            let settings = crate::redaction::RedactionSettings::load(&db).await?;
+            crate::redaction::set_settings(file_path, &settings);
+        }
         
         Ok(db)
     }
     
+    // Wait for everything else holding this pool, like recorders still flushing, to let go of it, then
+    // close its connections so the file is no longer open. Gives up if something keeps it in use
+    pub async fn release(db: std::sync::Arc<Self>) -> Result<(), String> {
+        let deadline = tokio::time::Instant::now() + RELEASE_TIMEOUT;
+        while std::sync::Arc::strong_count(&db) > 1 {
+            if tokio::time::Instant::now() >= deadline {
+                return Err("the investigation file is still being written to; try again in a moment".to_string());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        db.pool.close().await;
+        Ok(())
+    }
+    
     async fn initialize(&mut self, name: &str, description: &str, color: &[f32; 3]) -> Result<(), sqlx::Error> {
         // Run SQLx migrations for investigation database
-        sqlx::migrate!("./migrations/investigations").run(&self.pool).await?;
+        crate::database::migrate(&self.pool, &crate::database::INVESTIGATION_MIGRATOR).await?;
         
         // Set initial metadata
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_micros() as i64;
-            
+        
         let color_string = format!("{},{},{}", color[0], color[1], color[2]);
-            
+        
         sqlx::query(
             "INSERT INTO metadata (name, description, color_rgb, created_at, version) VALUES (?, ?, ?, ?, ?)"
         )
@@ -67,16 +220,48 @@ impl InvestigationDB {
     }
     
     
+    pub async fn get_setting(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool).await?;
+        
+        Ok(row.map(|row| row.get::<String, _>("value")))
+    }
+    
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    // Redaction rules for this file, loaded once per process and shared by every connection
+    pub fn redactor(&self) -> std::sync::Arc<crate::redaction::Redactor> {
+        crate::redaction::redactor_for(&self.file_path)
+    }
+    
+    // Schema version of a file, read without migrating it
+    pub async fn read_schema_status(file_path: &std::path::Path) -> Result<crate::database::SchemaStatus, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(file_path)
+            .read_only(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        let status = crate::database::schema_status(&pool, &crate::database::INVESTIGATION_MIGRATOR).await;
+        pool.close().await;
+        status
+    }
+    
     pub async fn get_metadata(&self) -> Result<Option<(String, String, [f32; 3], i64, String)>, sqlx::Error> {
         let row = sqlx::query("SELECT name, description, color_rgb, created_at, version FROM metadata LIMIT 1")
             .fetch_optional(&self.pool).await?;
-            
+        
         match row {
             Some(row) => {
                 let color_string = row.get::<String, _>("color_rgb");
                 let color_parts: Vec<&str> = color_string.split(',').collect();
                 let color = [
-                    color_parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0.2),
+                    color_parts.first().and_then(|s| s.parse().ok()).unwrap_or(0.2),
                     color_parts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0.4),
                     color_parts.get(2).and_then(|s| s.parse().ok()).unwrap_or(0.85),
                 ];
@@ -93,6 +278,29 @@ impl InvestigationDB {
         }
     }
     
+    // Insert a metadata row into a file that lost (or never got) one; no-op if one exists
+    pub async fn repair_metadata(&self, name: &str, description: &str, color: &[f32; 3]) -> Result<bool, sqlx::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as i64;
+        
+        let color_string = format!("{},{},{}", color[0], color[1], color[2]);
+        
+        let result = sqlx::query(
+            "INSERT INTO metadata (name, description, color_rgb, created_at, version)
+             SELECT ?, ?, ?, ?, ? WHERE NOT EXISTS (SELECT 1 FROM metadata)"
+        )
+        .bind(name)
+        .bind(description)
+        .bind(color_string)
+        .bind(now)
+        .bind("1.0")
+        .execute(&self.pool).await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
     pub async fn update_metadata(&self, name: &str, description: &str, color: &[f32; 3]) -> Result<(), sqlx::Error> {
         let color_string = format!("{},{},{}", color[0], color[1], color[2]);
         
@@ -101,26 +309,31 @@ impl InvestigationDB {
             .bind(description)
             .bind(color_string)
             .execute(&self.pool).await?;
-            
+        
         if result.rows_affected() == 0 {
             eprintln!("WARNING: No rows were updated in metadata table. The metadata table may be empty.");
         } else {
             println!("Successfully updated {} row(s) in metadata table", result.rows_affected());
         }
-            
+        
         Ok(())
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub async fn save_widget(&self, widget_id: i32, widget_version: i32, widget_type: &str, config_json: &str, 
                             pos_x: f32, pos_y: f32, size_x: f32, size_y: f32, collapsed: bool) -> Result<(), sqlx::Error> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_micros() as i64;
-            
+        
+        // A new config version keeps the widget's creation time, so replaying an earlier moment still shows it
         sqlx::query(
-            "INSERT OR REPLACE INTO widgets (id, version, widget_type, config_json, position_x, position_y, size_x, size_y, created_at, collapsed) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO widgets (id, version, widget_type, config_json, position_x, position_y, size_x, size_y, created_at, collapsed) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET version = excluded.version, widget_type = excluded.widget_type, config_json = excluded.config_json,
+                position_x = excluded.position_x, position_y = excluded.position_y, size_x = excluded.size_x, size_y = excluded.size_y,
+                collapsed = excluded.collapsed, archived_at = NULL"
         )
         .bind(widget_id)
         .bind(widget_version)
@@ -136,22 +349,48 @@ impl InvestigationDB {
         
         Ok(())
     }
-
-    pub async fn record_raw_data(&self, widget_id: i32, widget_version: i32, line_content: &str, line_number: i32) -> Result<(), sqlx::Error> {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as i64;
+    
+    // A batch of lines and their samples in one transaction. All series of one reading share its line's timestamp
+    pub async fn record_raw_data(&self, widget_id: i32, widget_version: i32, lines: &[RecordedLine]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for line in lines {
+            if !line.samples_only {
+                sqlx::query("INSERT INTO raw_data (widget_id, widget_version, timestamp, line_content, line_number, stream, host, run) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+                    .bind(widget_id)
+                    .bind(widget_version)
+                    .bind(line.timestamp)
+                    .bind(&line.line_content)
+                    .bind(line.line_number)
+                    .bind(line.stream)
+                    .bind(&line.host)
+                    .bind(line.run)
+                    .execute(&mut *tx).await?;
+            }
             
-        sqlx::query("INSERT INTO raw_data (widget_id, widget_version, timestamp, line_content, line_number) VALUES (?, ?, ?, ?, ?)")
+            for (series, value) in &line.samples {
+                sqlx::query("INSERT INTO samples (widget_id, widget_version, timestamp, series, value) VALUES (?, ?, ?, ?, ?)")
+                    .bind(widget_id)
+                    .bind(widget_version)
+                    .bind(line.timestamp)
+                    .bind(series)
+                    .bind(value)
+                    .execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await
+    }
+    
+    // (timestamp, series, value) for every recorded sample of a widget, oldest first
+    pub async fn get_samples(&self, widget_id: i32) -> Result<Vec<(i64, String, f64)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT timestamp, series, value FROM samples WHERE widget_id = ? ORDER BY timestamp, id")
             .bind(widget_id)
-            .bind(widget_version)
-            .bind(now)
-            .bind(line_content)
-            .bind(line_number)
-            .execute(&self.pool).await?;
-            
-        Ok(())
+            .fetch_all(&self.pool).await?;
+        
+        Ok(rows.into_iter().map(|row| (
+            row.get::<i64, _>("timestamp"),
+            row.get::<String, _>("series"),
+            row.get::<f64, _>("value"),
+        )).collect())
     }
     
     pub async fn load_widgets(&self) -> Result<Vec<(i32, i32, String, String, f32, f32, f32, f32, bool)>, sqlx::Error> {
@@ -163,7 +402,7 @@ impl InvestigationDB {
             AND version = (SELECT MAX(version) FROM widgets w2 WHERE w2.id = w1.id AND w2.archived_at IS NULL)
         ")
             .fetch_all(&self.pool).await?;
-            
+        
         let mut widgets = Vec::new();
         for row in rows {
             widgets.push((
@@ -187,7 +426,7 @@ impl InvestigationDB {
             .bind(timestamp)
             .bind(timestamp)
             .fetch_all(&self.pool).await?;
-            
+        
         let mut widgets = Vec::new();
         for row in rows {
             widgets.push((
@@ -212,18 +451,40 @@ impl InvestigationDB {
         let widget_type = widget.widget_type_name();
         let widget_json = serde_json::to_string(widget).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
         
-        self.save_widget(widget_id, widget_version, widget_type, &widget_json, 0.0, 0.0, 600.0, 400.0, false).await
+        // A new config version keeps the window where it was
+        let [pos_x, pos_y, size_x, size_y] = sqlx::query("SELECT position_x, position_y, size_x, size_y FROM widgets WHERE id = ? ORDER BY version DESC LIMIT 1")
+            .bind(widget_id)
+            .fetch_optional(&self.pool).await?
+            .map(|row| [row.get("position_x"), row.get("position_y"), row.get("size_x"), row.get("size_y")])
+            .unwrap_or(UNSAVED_LAYOUT);
+        
+        self.save_widget(widget_id, widget_version, widget_type, &widget_json, pos_x, pos_y, size_x, size_y, false).await
+    }
+    
+    // Layout isn't versioned: every version of the widget moves with its window
+    pub async fn save_widget_layout(&self, widget_id: i32, layout: crate::widgets::WidgetLayout) -> Result<(), sqlx::Error> {
+        let [pos_x, pos_y, size_x, size_y] = layout;
+        sqlx::query("UPDATE widgets SET position_x = ?, position_y = ?, size_x = ?, size_y = ? WHERE id = ?")
+            .bind(pos_x)
+            .bind(pos_y)
+            .bind(size_x)
+            .bind(size_y)
+            .bind(widget_id)
+            .execute(&self.pool).await?;
+        Ok(())
     }
     
-    pub async fn load_widget_instances(&self) -> Result<Vec<crate::widgets::WidgetType>, Box<dyn std::error::Error>> {
+    // Widgets with their saved window layout, None for ones whose window was never saved
+    pub async fn load_widget_instances(&self) -> Result<Vec<(crate::widgets::WidgetType, Option<crate::widgets::WidgetLayout>)>, Box<dyn std::error::Error>> {
         use crate::widgets::WidgetType;
         
         let widgets_data = self.load_widgets().await?;
         let mut widgets = Vec::new();
         
-        for (_widget_id, _widget_version, widget_type, widget_json, _pos_x, _pos_y, _size_x, _size_y, _collapsed) in widgets_data {
+        for (_widget_id, _widget_version, widget_type, widget_json, pos_x, pos_y, size_x, size_y, _collapsed) in widgets_data {
+            let layout = [pos_x, pos_y, size_x, size_y];
             match serde_json::from_str::<WidgetType>(&widget_json) {
-                Ok(widget) => widgets.push(widget),
+                Ok(widget) => widgets.push((widget, (layout != UNSAVED_LAYOUT).then_some(layout))),
                 Err(e) => {
                     eprintln!("Failed to load widget {}: {}", widget_type, e);
                     continue; // Skip invalid widgets
@@ -234,15 +495,16 @@ impl InvestigationDB {
         Ok(widgets)
     }
     
-    pub async fn load_widget_instances_at_time(&self, timestamp: i64) -> Result<Vec<crate::widgets::WidgetType>, Box<dyn std::error::Error>> {
+    pub async fn load_widget_instances_at_time(&self, timestamp: i64) -> Result<Vec<(crate::widgets::WidgetType, Option<crate::widgets::WidgetLayout>)>, Box<dyn std::error::Error>> {
         use crate::widgets::WidgetType;
         
         let widgets_data = self.load_widgets_at_time(timestamp).await?;
         let mut widgets = Vec::new();
         
-        for (_widget_id, _widget_version, widget_type, widget_json, _pos_x, _pos_y, _size_x, _size_y, _collapsed) in widgets_data {
+        for (_widget_id, _widget_version, widget_type, widget_json, pos_x, pos_y, size_x, size_y, _collapsed) in widgets_data {
+            let layout = [pos_x, pos_y, size_x, size_y];
             match serde_json::from_str::<WidgetType>(&widget_json) {
-                Ok(widget) => widgets.push(widget),
+                Ok(widget) => widgets.push((widget, (layout != UNSAVED_LAYOUT).then_some(layout))),
                 Err(e) => {
                     eprintln!("Failed to load widget {}: {}", widget_type, e);
                     continue; // Skip invalid widgets
@@ -258,7 +520,7 @@ impl InvestigationDB {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_micros() as i64;
-            
+        
         sqlx::query("UPDATE widgets SET archived_at = ? WHERE id = ?")
             .bind(now)
             .bind(widget_id)
@@ -284,11 +546,12 @@ impl InvestigationDB {
     }
     
     pub async fn get_widget_data(&self, widget_id: i32, widget_version: i32) -> Result<Vec<String>, sqlx::Error> {
-        let rows = sqlx::query("SELECT line_content FROM raw_data WHERE widget_id = ? AND widget_version = ? ORDER BY line_number ASC")
+        // Periodic widgets restart line numbers on every run, so keep recording order
+        let rows = sqlx::query(WIDGET_VERSION_LINES)
             .bind(widget_id)
             .bind(widget_version)
             .fetch_all(&self.pool).await?;
-            
+        
         let mut lines = Vec::new();
         for row in rows {
             lines.push(row.get::<String, _>("line_content"));
@@ -297,6 +560,104 @@ impl InvestigationDB {
         Ok(lines)
     }
     
+    // Like get_widget_data, with the time (µs since the epoch) each line was recorded
+    pub async fn get_timed_widget_data(&self, widget_id: i32, widget_version: i32) -> Result<Vec<TimedLine>, sqlx::Error> {
+        let rows = sqlx::query(WIDGET_VERSION_TIMED_LINES)
+            .bind(widget_id)
+            .bind(widget_version)
+            .fetch_all(&self.pool).await?;
+        
+        Ok(rows.into_iter().map(timed_line).collect())
+    }
+    
+    // The output a widget was showing at `timestamp`: lines recorded by then, from the config version it was running
+    pub async fn get_widget_data_at_time(&self, widget_id: i32, timestamp: i64) -> Result<Vec<TimedLine>, sqlx::Error> {
+        let rows = sqlx::query(WIDGET_LINES_AT_TIME)
+            .bind(widget_id)
+            .bind(timestamp)
+            .bind(widget_id)
+            .bind(timestamp)
+            .fetch_all(&self.pool).await?;
+        
+        Ok(rows.into_iter().map(timed_line).collect())
+    }
+    
+    // Delete lines recorded before `recorded_before` (µs since the epoch), then all but each widget's newest
+    // `max_lines_per_widget`. Samples follow the same limits, the line cap counting each series on its own
+    // since one recorded line holds at most one value per series. Returns the number of lines removed
+    pub async fn prune_raw_data(&self, recorded_before: Option<i64>, max_lines_per_widget: Option<u32>) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut removed = 0;
+        
+        if let Some(cutoff) = recorded_before {
+            removed += sqlx::query("DELETE FROM raw_data WHERE timestamp < ?")
+                .bind(cutoff)
+                .execute(&mut *tx).await?
+                .rows_affected();
+            sqlx::query("DELETE FROM samples WHERE timestamp < ?")
+                .bind(cutoff)
+                .execute(&mut *tx).await?;
+        }
+        
+        if let Some(max_lines) = max_lines_per_widget {
+            removed += sqlx::query(
+                "DELETE FROM raw_data WHERE id IN (
+                    SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY widget_id ORDER BY id DESC) AS newest FROM raw_data)
+                    WHERE newest > ?
+                )"
+            )
+                .bind(max_lines)
+                .execute(&mut *tx).await?
+                .rows_affected();
+            sqlx::query(
+                "DELETE FROM samples WHERE id IN (
+                    SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY widget_id, series ORDER BY id DESC) AS newest FROM samples)
+                    WHERE newest > ?
+                )"
+            )
+                .bind(max_lines)
+                .execute(&mut *tx).await?;
+        }
+        
+        tx.commit().await?;
+        Ok(removed)
+    }
+    
+    // All recorded lines for a widget across every version, in recording order
+    pub async fn get_all_widget_data(&self, widget_id: i32) -> Result<Vec<String>, sqlx::Error> {
+        let rows = sqlx::query("SELECT line_content FROM raw_data WHERE widget_id = ? ORDER BY id ASC")
+            .bind(widget_id)
+            .fetch_all(&self.pool).await?;
+        
+        Ok(rows.into_iter().map(|row| row.get::<String, _>("line_content")).collect())
+    }
+    
+    // Recorded output split into command runs, with the time of each run's first line
+    pub async fn get_widget_runs(&self, widget_id: i32) -> Result<Vec<(i64, Vec<String>)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT timestamp, line_number, line_content, stream, run FROM raw_data WHERE widget_id = ? ORDER BY id ASC")
+            .bind(widget_id)
+            .fetch_all(&self.pool).await?;
+        
+        let mut runs: Vec<(i64, Vec<String>)> = Vec::new();
+        let mut previous_run = None;
+        for row in rows {
+            let line = row.get::<String, _>("line_content");
+            let run = row.get::<Option<i64>, _>("run");
+            let starts_run = match run {
+                Some(_) => run != previous_run,
+                // Recorded before runs were tracked: stdout line numbers restart at 1 on each run
+                None => row.get::<&str, _>("stream") == "stdout" && row.get::<i32, _>("line_number") == 1,
+            };
+            previous_run = run;
+            match runs.last_mut() {
+                Some((_, lines)) if !starts_run => lines.push(line),
+                _ => runs.push((row.get::<i64, _>("timestamp"), vec![line])),
+            }
+        }
+        
+        Ok(runs)
+    }
+    
     pub async fn get_widget_summary(&self) -> Result<(usize, std::collections::HashMap<String, usize>), sqlx::Error> {
         let rows = sqlx::query("SELECT widget_type, COUNT(*) as count FROM widgets WHERE archived_at IS NULL GROUP BY widget_type")
             .fetch_all(&self.pool).await?;
@@ -314,21 +675,177 @@ impl InvestigationDB {
         Ok((total_count, type_counts))
     }
     
+    // Write a consistent copy of the whole database to another file
+    pub async fn export_to(&self, destination: &std::path::Path) -> Result<(), sqlx::Error> {
+        // VACUUM INTO refuses to overwrite, and the user already confirmed replacing the file
+        if destination.exists() {
+            std::fs::remove_file(destination)?;
+        }
+        
+        sqlx::query("VACUUM INTO ?")
+            .bind(destination.to_string_lossy().to_string())
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    // Size of the investigation file on disk
+    pub fn file_size(&self) -> std::io::Result<u64> {
+        Ok(std::fs::metadata(&self.file_path)?.len())
+    }
+    
+    // Rebuild the file so space left by pruned or removed rows goes back to the disk; returns the bytes reclaimed.
+    // VACUUM needs the file to itself, so it's retried while recorders are writing
+    pub async fn compact(&self) -> Result<u64, sqlx::Error> {
+        let before = self.file_size()?;
+        let mut attempt = 1;
+        loop {
+            match sqlx::query("VACUUM").execute(&self.pool).await {
+                Ok(_) => break,
+                Err(e) if is_busy(&e) && attempt < COMPACT_ATTEMPTS => {
+                    tokio::time::sleep(COMPACT_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(before.saturating_sub(self.file_size()?))
+    }
+    
+    // Run a user-supplied SELECT statement on a separate read-only connection
+    pub async fn run_read_only_query(&self, sql: &str, max_rows: usize) -> Result<QueryResult, sqlx::Error> {
+        use futures::TryStreamExt;
+        use sqlx::{Column, TypeInfo, ValueRef};
+        
+        let statement = sql.trim().trim_end_matches(';').trim();
+        let first_keyword = statement.split_whitespace().next().unwrap_or("").to_lowercase();
+        if first_keyword != "select" && first_keyword != "with" {
+            return Err(sqlx::Error::Protocol("Only SELECT statements are allowed".into()));
+        }
+        if statement.contains(';') {
+            return Err(sqlx::Error::Protocol("Only a single statement is allowed".into()));
+        }
+        
+        let options = SqliteConnectOptions::new()
+            .filename(&self.file_path)
+            .read_only(true);
+        let mut conn = SqliteConnection::connect_with(&options).await?;
+        
+        let mut result = QueryResult::default();
+        let mut rows = sqlx::query(statement).fetch(&mut conn);
+        
+        while let Some(row) = rows.try_next().await? {
+            if result.columns.is_empty() {
+                result.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+            }
+            if result.rows.len() >= max_rows {
+                result.truncated = true;
+                break;
+            }
+            
+            let mut values = Vec::with_capacity(row.len());
+            for idx in 0..row.len() {
+                let raw = row.try_get_raw(idx)?;
+                let value = if raw.is_null() {
+                    "NULL".to_string()
+                } else {
+                    match raw.type_info().name() {
+                        "INTEGER" | "BOOLEAN" => row.try_get::<i64, _>(idx)?.to_string(),
+                        "REAL" => row.try_get::<f64, _>(idx)?.to_string(),
+                        "BLOB" => format!("<{} bytes>", row.try_get::<Vec<u8>, _>(idx)?.len()),
+                        _ => row.try_get::<String, _>(idx)?,
+                    }
+                };
+                values.push(value);
+            }
+            result.rows.push(values);
+        }
+        
+        Ok(result)
+    }
+    
+    // Copy all widgets, recorded data, and hosts from another investigation file into this one.
+    // Widget ids from the source are shifted past this investigation's ids to avoid collisions.
+    pub async fn merge_from(&self, source_path: &std::path::Path) -> Result<u64, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        
+        sqlx::query("ATTACH DATABASE ? AS source")
+            .bind(source_path.to_string_lossy().to_string())
+            .execute(&mut *conn).await?;
+        
+        let result = Self::copy_attached_source(&mut conn).await;
+        
+        sqlx::query("DETACH DATABASE source")
+            .execute(&mut *conn).await?;
+        
+        result
+    }
+    
+    async fn copy_attached_source(conn: &mut SqliteConnection) -> Result<u64, sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        
+        let id_offset = sqlx::query("SELECT COALESCE(MAX(id), -1) + 1 AS id_offset FROM main.widgets")
+            .fetch_one(&mut *tx).await?
+            .get::<i64, _>("id_offset");
+        
+        // The serialized widget config carries its own id, so it gets remapped as well
+        let widgets = sqlx::query(
+            "INSERT INTO main.widgets (id, version, widget_type, config_json, position_x, position_y, size_x, size_y, created_at, collapsed, archived_at)
+             SELECT id + ?, version, widget_type, json_set(config_json, '$.id', id + ?), position_x, position_y, size_x, size_y, created_at, collapsed, archived_at
+             FROM source.widgets"
+        )
+        .bind(id_offset)
+        .bind(id_offset)
+        .execute(&mut *tx).await?;
+        
+        sqlx::query(
+            "INSERT INTO main.raw_data (widget_id, widget_version, timestamp, line_content, line_number, stream, host, run)
+             SELECT widget_id + ?, widget_version, timestamp, line_content, line_number, stream, host, run
+             FROM source.raw_data ORDER BY id"
+        )
+        .bind(id_offset)
+        .execute(&mut *tx).await?;
+        
+        sqlx::query(
+            "INSERT INTO main.samples (widget_id, widget_version, timestamp, series, value)
+             SELECT widget_id + ?, widget_version, timestamp, series, value
+             FROM source.samples ORDER BY id"
+        )
+        .bind(id_offset)
+        .execute(&mut *tx).await?;
+        
+        // Hosts are matched by name; existing hosts in this investigation win
+        sqlx::query(
+            "INSERT OR IGNORE INTO main.hosts (name, ssh_alias, description, command_prefix, port, username, identity_file, host_group, created_at, is_localhost)
+             SELECT name, ssh_alias, description, command_prefix, port, username, identity_file, host_group, created_at, is_localhost FROM source.hosts"
+        )
+        .execute(&mut *tx).await?;
+        
+        tx.commit().await?;
+        
+        Ok(widgets.rows_affected())
+    }
+    
     // Host management methods
-    pub async fn add_host(&self, name: &str, ssh_alias: &str, description: &str) -> Result<i64, sqlx::Error> {
+    pub async fn add_host(&self, host: &Host) -> Result<i64, sqlx::Error> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_micros() as i64;
         
-        let is_localhost = ssh_alias == "localhost" || ssh_alias == "127.0.0.1";
+        let is_localhost = Host::is_plain_localhost(&host.ssh_alias, &host.command_prefix);
         
         let result = sqlx::query(
-            "INSERT INTO hosts (name, ssh_alias, description, created_at, is_localhost) VALUES (?, ?, ?, ?, ?)"
+            "INSERT INTO hosts (name, ssh_alias, description, command_prefix, port, username, identity_file, host_group, created_at, is_localhost)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
-        .bind(name)
-        .bind(ssh_alias)
-        .bind(description)
+        .bind(&host.name)
+        .bind(&host.ssh_alias)
+        .bind(&host.description)
+        .bind(&host.command_prefix)
+        .bind(host.port)
+        .bind(&host.username)
+        .bind(&host.identity_file)
+        .bind(&host.group)
         .bind(now)
         .bind(is_localhost)
         .execute(&self.pool).await?;
@@ -337,32 +854,27 @@ impl InvestigationDB {
     }
     
     pub async fn list_hosts(&self) -> Result<Vec<Host>, sqlx::Error> {
-        let rows = sqlx::query("SELECT id, name, ssh_alias, description, is_localhost FROM hosts ORDER BY is_localhost DESC, name ASC")
+        let rows = sqlx::query(&format!("SELECT {} FROM hosts ORDER BY is_localhost DESC, name ASC", HOST_COLUMNS))
             .fetch_all(&self.pool).await?;
         
-        let mut hosts = Vec::new();
-        for row in rows {
-            hosts.push(Host {
-                id: Some(row.get::<i64, _>("id")),
-                name: row.get::<String, _>("name"),
-                ssh_alias: row.get::<String, _>("ssh_alias"),
-                description: row.get::<String, _>("description"),
-                is_localhost: row.get::<bool, _>("is_localhost"),
-            });
-        }
-        
-        Ok(hosts)
+        Ok(rows.iter().map(host_from_row).collect())
     }
     
-    pub async fn update_host(&self, id: i64, name: &str, ssh_alias: &str, description: &str) -> Result<(), sqlx::Error> {
-        let is_localhost = ssh_alias == "localhost" || ssh_alias == "127.0.0.1";
+    pub async fn update_host(&self, id: i64, host: &Host) -> Result<(), sqlx::Error> {
+        let is_localhost = Host::is_plain_localhost(&host.ssh_alias, &host.command_prefix);
         
         let result = sqlx::query(
-            "UPDATE hosts SET name = ?, ssh_alias = ?, description = ?, is_localhost = ? WHERE id = ?"
+            "UPDATE hosts SET name = ?, ssh_alias = ?, description = ?, command_prefix = ?, port = ?, username = ?, identity_file = ?, host_group = ?, is_localhost = ?
+             WHERE id = ?"
         )
-        .bind(name)
-        .bind(ssh_alias)
-        .bind(description)
+        .bind(&host.name)
+        .bind(&host.ssh_alias)
+        .bind(&host.description)
+        .bind(&host.command_prefix)
+        .bind(host.port)
+        .bind(&host.username)
+        .bind(&host.identity_file)
+        .bind(&host.group)
         .bind(is_localhost)
         .bind(id)
         .execute(&self.pool).await?;
@@ -381,7 +893,7 @@ impl InvestigationDB {
         let row = sqlx::query("SELECT is_localhost FROM hosts WHERE id = ?")
             .bind(id)
             .fetch_optional(&self.pool).await?;
-            
+        
         if let Some(row) = row {
             if row.get::<bool, _>("is_localhost") {
                 return Err(sqlx::Error::Protocol("Cannot delete localhost host".into()));
@@ -402,19 +914,463 @@ impl InvestigationDB {
     }
     
     pub async fn get_host_by_name(&self, name: &str) -> Result<Option<Host>, sqlx::Error> {
-        let row = sqlx::query("SELECT id, name, ssh_alias, description, is_localhost FROM hosts WHERE name = ?")
+        let row = sqlx::query(&format!("SELECT {} FROM hosts WHERE name = ?", HOST_COLUMNS))
             .bind(name)
             .fetch_optional(&self.pool).await?;
         
-        match row {
-            Some(row) => Ok(Some(Host {
-                id: Some(row.get::<i64, _>("id")),
-                name: row.get::<String, _>("name"),
-                ssh_alias: row.get::<String, _>("ssh_alias"),
-                description: row.get::<String, _>("description"),
-                is_localhost: row.get::<bool, _>("is_localhost"),
-            })),
-            None => Ok(None)
+        Ok(row.as_ref().map(host_from_row))
+    }
+}
+
+const HOST_COLUMNS: &str = "id, name, ssh_alias, description, command_prefix, port, username, identity_file, host_group, is_localhost";
+
+fn host_from_row(row: &SqliteRow) -> Host {
+    Host {
+        id: Some(row.get::<i64, _>("id")),
+        name: row.get::<String, _>("name"),
+        ssh_alias: row.get::<String, _>("ssh_alias"),
+        description: row.get::<String, _>("description"),
+        command_prefix: row.get::<String, _>("command_prefix"),
+        port: row.get::<Option<u16>, _>("port"),
+        username: row.get::<Option<String>, _>("username"),
+        identity_file: row.get::<Option<String>, _>("identity_file"),
+        group: row.get::<String, _>("host_group"),
+        is_localhost: row.get::<bool, _>("is_localhost"),
+    }
+}
+
+fn timed_line(row: SqliteRow) -> TimedLine {
+    (row.get::<i64, _>("timestamp"), row.get::<String, _>("line_content"), OutputStream::from_recorded(row.get::<&str, _>("stream")))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::WidgetType;
+    
+    fn line(line_content: &str, line_number: i32, stream: &'static str, host: Option<&str>) -> RecordedLine {
+        RecordedLine {
+            timestamp: now_micros(),
+            line_content: line_content.to_string(),
+            line_number,
+            stream,
+            host: host.map(str::to_string),
+            run: None,
+            samples: vec![],
+            samples_only: false,
         }
     }
-}
\ No newline at end of file
+    
+    async fn create_db(dir: &tempfile::TempDir) -> InvestigationDB {
+        let path = dir.path().join("test.skop");
+        InvestigationDB::create(&path, "Test", "Lifecycle test", &[0.1, 0.2, 0.3]).await.unwrap()
+    }
+    
+    fn now_micros() -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as i64
+    }
+    
+    // Let the microsecond clock move on so timestamps either side of an event differ
+    async fn tick() {
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    
+    #[tokio::test]
+    async fn metadata_round_trips_through_create() {
+        let dir = tempfile::tempdir().unwrap();
+        let before = now_micros();
+        let db = create_db(&dir).await;
+        
+        let (name, description, color, created_at, version) = db.get_metadata().await.unwrap().unwrap();
+        assert_eq!(name, "Test");
+        assert_eq!(description, "Lifecycle test");
+        assert_eq!(color, [0.1, 0.2, 0.3]);
+        assert!(created_at >= before && created_at <= now_micros());
+        assert_eq!(version, "1.0");
+    }
+    
+    #[tokio::test]
+    async fn every_widget_type_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let widgets = vec![
+            WidgetType::new_raw_command(1),
+            WidgetType::new_cpu_monitor(2),
+            WidgetType::new_system_info(3),
+            WidgetType::new_process_monitor(4),
+            WidgetType::new_network_monitor(5),
+            WidgetType::new_about(6),
+            WidgetType::new_sql_query(7),
+            WidgetType::new_docker(8),
+            WidgetType::new_kube(9),
+            WidgetType::new_journal(10),
+            WidgetType::new_summary(11),
+            WidgetType::new_memory_monitor(12),
+            WidgetType::new_disk_monitor(13),
+            WidgetType::new_chart(14),
+        ];
+        for widget in &widgets {
+            db.save_widget_instance(widget).await.unwrap();
+        }
+        
+        let mut loaded = db.load_widget_instances().await.unwrap();
+        loaded.sort_by_key(|(widget, _)| widget.widget_id());
+        assert_eq!(loaded.len(), widgets.len());
+        for (saved, (loaded, layout)) in widgets.iter().zip(&loaded) {
+            assert_eq!(saved.widget_type_name(), loaded.widget_type_name());
+            assert_eq!(serde_json::to_value(saved).unwrap(), serde_json::to_value(loaded).unwrap());
+            assert_eq!(*layout, None);
+        }
+    }
+    
+    #[tokio::test]
+    async fn monitor_settings_survive_a_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let mut cpu = crate::widgets::CPUMonitorWidget::new(1);
+        cpu.interval_seconds = 7;
+        cpu.audio_enabled = true;
+        let mut network = crate::widgets::NetworkMonitorWidget::new(2);
+        network.refresh_interval_secs = 30;
+        network.filter_text = ":443".to_string();
+        network.show_established_only = true;
+        db.save_widget_instance(&WidgetType::CPUMonitor(cpu)).await.unwrap();
+        db.save_widget_instance(&WidgetType::NetworkMonitor(network)).await.unwrap();
+        
+        let mut loaded = db.load_widget_instances().await.unwrap();
+        loaded.sort_by_key(|(widget, _)| widget.widget_id());
+        let [(WidgetType::CPUMonitor(cpu), _), (WidgetType::NetworkMonitor(network), _)] = loaded.as_slice() else {
+            panic!("unexpected widgets after reload");
+        };
+        assert_eq!((cpu.interval_seconds, cpu.audio_enabled), (7, true));
+        assert_eq!(network.refresh_interval_secs, 30);
+        assert_eq!(network.filter_text, ":443");
+        assert!(network.show_established_only);
+    }
+    
+    #[test]
+    fn monitor_configs_from_before_a_field_existed_still_load() {
+        let cpu: WidgetType = serde_json::from_str(r#"{"type":"CPUMonitor","id":1,"version":0}"#).unwrap();
+        let network: WidgetType = serde_json::from_str(r#"{"type":"NetworkMonitor","id":2,"version":0}"#).unwrap();
+        assert!(matches!(cpu, WidgetType::CPUMonitor(ref cpu) if cpu.interval_seconds == 2));
+        assert!(matches!(network, WidgetType::NetworkMonitor(ref network) if network.refresh_interval_secs == 5));
+    }
+    
+    #[tokio::test]
+    async fn layout_is_kept_across_config_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let mut widget = WidgetType::new_sql_query(1);
+        db.save_widget_instance(&widget).await.unwrap();
+        
+        db.save_widget_layout(1, [120.0, 80.0, 700.0, 450.0]).await.unwrap();
+        widget.increment_version();
+        db.save_widget_instance(&widget).await.unwrap();
+        
+        let loaded = db.load_widget_instances().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0.widget_version(), 1);
+        assert_eq!(loaded[0].1, Some([120.0, 80.0, 700.0, 450.0]));
+    }
+    
+    #[tokio::test]
+    async fn archived_widgets_are_not_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_about(1)).await.unwrap();
+        db.save_widget_instance(&WidgetType::new_sql_query(2)).await.unwrap();
+        
+        db.archive_widget(1).await.unwrap();
+        
+        let ids: Vec<i32> = db.load_widgets().await.unwrap().into_iter().map(|widget| widget.0).collect();
+        assert_eq!(ids, vec![2]);
+    }
+    
+    #[tokio::test]
+    async fn widgets_at_time_respect_creation_and_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let ids_at = |timestamp| {
+            let db = db.clone();
+            async move {
+                db.load_widgets_at_time(timestamp).await.unwrap().into_iter().map(|widget| widget.0).collect::<Vec<i32>>()
+            }
+        };
+        
+        let before_created = now_micros();
+        tick().await;
+        db.save_widget_instance(&WidgetType::new_about(1)).await.unwrap();
+        tick().await;
+        let while_open = now_micros();
+        tick().await;
+        db.archive_widget(1).await.unwrap();
+        tick().await;
+        let after_archived = now_micros();
+        
+        assert!(ids_at(before_created).await.is_empty());
+        assert_eq!(ids_at(while_open).await, vec![1]);
+        assert!(ids_at(after_archived).await.is_empty());
+        
+        // Exactly at the archive time the widget is already gone
+        let archived_at: i64 = sqlx::query("SELECT archived_at FROM widgets WHERE id = 1")
+            .fetch_one(&db.pool).await.unwrap()
+            .get("archived_at");
+        assert!(ids_at(archived_at).await.is_empty());
+        assert_eq!(ids_at(archived_at - 1).await, vec![1]);
+    }
+    
+    #[tokio::test]
+    async fn replay_shows_the_output_recorded_by_then() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let mut widget = WidgetType::new_raw_command(1);
+        db.save_widget_instance(&widget).await.unwrap();
+        db.record_raw_data(1, 0, &[line("first config", 1, "stdout", None)]).await.unwrap();
+        tick().await;
+        let before_edit = now_micros();
+        tick().await;
+        
+        widget.increment_version();
+        db.save_widget_instance(&widget).await.unwrap();
+        db.record_raw_data(1, 1, &[line("second config", 1, "stdout", None)]).await.unwrap();
+        tick().await;
+        
+        // Editing the config doesn't move the widget's creation past earlier moments
+        let at_edit = db.load_widget_instances_at_time(before_edit).await.unwrap();
+        assert_eq!(at_edit.iter().map(|(widget, _)| widget.widget_id()).collect::<Vec<_>>(), vec![1]);
+        let lines_at = |timestamp| {
+            let db = db.clone();
+            async move {
+                db.get_widget_data_at_time(1, timestamp).await.unwrap().into_iter().map(|(_, line, _)| line).collect::<Vec<String>>()
+            }
+        };
+        assert_eq!(lines_at(before_edit).await, vec!["first config"]);
+        assert_eq!(lines_at(now_micros()).await, vec!["second config"]);
+    }
+    
+    #[tokio::test]
+    async fn release_lets_recorders_flush_before_the_file_is_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = std::sync::Arc::new(create_db(&dir).await);
+        db.save_widget_instance(&WidgetType::new_raw_command(1)).await.unwrap();
+        
+        let writer = crate::database::raw_data_writer::RawDataWriter::spawn(db.clone(), 1, 0);
+        writer.record(line("last line", 1, "stdout", None)).await;
+        drop(writer);
+        InvestigationDB::release(db).await.unwrap();
+        
+        // Closed, so the file can move with everything in it
+        let moved = dir.path().join("moved.skop");
+        std::fs::rename(dir.path().join("test.skop"), &moved).unwrap();
+        let db = InvestigationDB::open(&moved).await.unwrap();
+        assert_eq!(db.get_widget_data(1, 0).await.unwrap(), vec!["last line"]);
+    }
+    
+    #[tokio::test]
+    async fn recorded_lines_come_back_in_recording_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_raw_command(1)).await.unwrap();
+        db.save_widget_instance(&WidgetType::new_raw_command(2)).await.unwrap();
+        
+        // Two periodic runs, line numbers restart on the second; written as one batch
+        let runs: Vec<RecordedLine> = [("run 1 a", 1), ("run 1 b", 2), ("run 2 a", 1), ("run 2 b", 2)].into_iter()
+            .map(|(content, line_number)| line(content, line_number, "stdout", None))
+            .collect();
+        db.record_raw_data(1, 0, &runs).await.unwrap();
+        db.record_raw_data(1, 1, &[line("next version", 1, "stdout", None)]).await.unwrap();
+        db.record_raw_data(2, 0, &[line("other widget", 1, "stderr", Some("web-1"))]).await.unwrap();
+        
+        assert_eq!(db.get_widget_data(1, 0).await.unwrap(), vec!["run 1 a", "run 1 b", "run 2 a", "run 2 b"]);
+        let timed = db.get_timed_widget_data(1, 0).await.unwrap();
+        assert_eq!(timed.iter().map(|(_, line, _)| line.as_str()).collect::<Vec<_>>(), vec!["run 1 a", "run 1 b", "run 2 a", "run 2 b"]);
+        assert!(timed.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert_eq!(db.get_timed_widget_data(2, 0).await.unwrap()[0].2, OutputStream::Stderr);
+        assert_eq!(db.get_all_widget_data(1).await.unwrap(), vec!["run 1 a", "run 1 b", "run 2 a", "run 2 b", "next version"]);
+        
+        let runs: Vec<Vec<String>> = db.get_widget_runs(1).await.unwrap().into_iter().map(|(_, lines)| lines).collect();
+        assert_eq!(runs, vec![vec!["run 1 a", "run 1 b"], vec!["run 2 a", "run 2 b"], vec!["next version"]]);
+        
+        let host: Option<String> = sqlx::query("SELECT host FROM raw_data WHERE widget_id = 2")
+            .fetch_one(&db.pool).await.unwrap()
+            .get("host");
+        assert_eq!(host.as_deref(), Some("web-1"));
+    }
+    
+    #[tokio::test]
+    async fn runs_are_split_where_a_new_command_run_starts() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_network_monitor(1)).await.unwrap();
+        
+        // stderr numbers its lines from 1 too, and status lines are numbered 0
+        let lines: Vec<RecordedLine> = [
+            ("a", 1, "stdout", 100), ("warning", 1, "stderr", 100), ("b", 2, "stdout", 100), ("Command completed", 3, "stdout", 100),
+            ("--- SSH connection lost, reconnecting in 1s (attempt 1) ---", 0, "stdout", 200), ("c", 1, "stdout", 200), ("oops", 1, "stderr", 200),
+        ].into_iter()
+            .map(|(content, line_number, stream, run)| RecordedLine { run: Some(run), ..line(content, line_number, stream, None) })
+            .collect();
+        db.record_raw_data(1, 0, &lines).await.unwrap();
+        
+        let runs: Vec<Vec<String>> = db.get_widget_runs(1).await.unwrap().into_iter().map(|(_, lines)| lines).collect();
+        assert_eq!(runs, vec![
+            vec!["a", "warning", "b", "Command completed"],
+            vec!["--- SSH connection lost, reconnecting in 1s (attempt 1) ---", "c", "oops"],
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn widget_output_queries_use_indexes_on_a_large_investigation() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        for widget_id in 1..=4 {
+            db.save_widget_instance(&WidgetType::new_raw_command(widget_id)).await.unwrap();
+        }
+        let lines: Vec<RecordedLine> = (0..100_000)
+            .map(|i| RecordedLine { timestamp: i, ..line("output", i as i32 % 50 + 1, "stdout", None) })
+            .collect();
+        for (i, chunk) in lines.chunks(25_000).enumerate() {
+            db.record_raw_data(i as i32 + 1, 0, chunk).await.unwrap();
+        }
+        sqlx::query("ANALYZE").execute(&db.pool).await.unwrap();
+        
+        // The same statements the lookups run, bound the same way
+        let plan = |sql: &'static str, binds: &'static [i64]| {
+            let db = db.clone();
+            async move {
+                let explain = format!("EXPLAIN QUERY PLAN {}", sql);
+                let mut query = sqlx::query(&explain);
+                for value in binds {
+                    query = query.bind(*value);
+                }
+                query.fetch_all(&db.pool).await.unwrap()
+                    .into_iter().map(|row| row.get::<String, _>("detail")).collect::<Vec<_>>()
+            }
+        };
+        
+        assert_eq!(db.get_widget_data(2, 0).await.unwrap().len(), 25_000);
+        assert_eq!(db.get_timed_widget_data(2, 0).await.unwrap().len(), 25_000);
+        assert_eq!(db.get_widget_data_at_time(2, 30_000).await.unwrap().len(), 5_001);
+        
+        for sql in [WIDGET_VERSION_LINES, WIDGET_VERSION_TIMED_LINES] {
+            let by_version = plan(sql, &[2, 0]).await;
+            assert_eq!(by_version, vec!["SEARCH raw_data USING INDEX idx_raw_data_widget_version (widget_id=? AND widget_version=?)"]);
+        }
+        
+        let at_time = plan(WIDGET_LINES_AT_TIME, &[2, 30_000, 2, 30_000]).await;
+        assert_eq!(at_time, vec![
+            "SEARCH raw_data USING INDEX idx_raw_data_widget_version (widget_id=? AND widget_version=?)",
+            "SCALAR SUBQUERY 1",
+            "SEARCH raw_data USING INDEX idx_raw_data_widget_version (widget_id=?)",
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn pruning_drops_old_lines_then_keeps_the_newest_per_widget() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_raw_command(1)).await.unwrap();
+        db.save_widget_instance(&WidgetType::new_raw_command(2)).await.unwrap();
+        let at = |timestamp, content| RecordedLine { timestamp, ..line(content, 1, "stdout", None) };
+        db.record_raw_data(1, 0, &[at(100, "old"), at(200, "a"), at(300, "b"), at(400, "c")]).await.unwrap();
+        db.record_raw_data(2, 0, &[at(150, "old"), at(250, "x")]).await.unwrap();
+        
+        // Nothing goes without a limit
+        assert_eq!(db.prune_raw_data(None, None).await.unwrap(), 0);
+        
+        assert_eq!(db.prune_raw_data(Some(200), Some(2)).await.unwrap(), 3);
+        assert_eq!(db.get_widget_data(1, 0).await.unwrap(), vec!["b", "c"]);
+        assert_eq!(db.get_widget_data(2, 0).await.unwrap(), vec!["x"]);
+    }
+    
+    #[tokio::test]
+    async fn pruning_caps_samples_per_series_like_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_cpu_monitor(1)).await.unwrap();
+        let snapshot = |timestamp, usage| RecordedLine {
+            timestamp,
+            samples: vec![("cpu_core_0".to_string(), usage), ("cpu_core_1".to_string(), usage + 1.0)],
+            samples_only: true,
+            ..line("--", 1, "stdout", None)
+        };
+        db.record_raw_data(1, 0, &[snapshot(100, 10.0), snapshot(200, 20.0), snapshot(300, 30.0)]).await.unwrap();
+        
+        // Samples-only widgets have no lines to count, but their samples still fall under the cap
+        assert_eq!(db.prune_raw_data(None, Some(2)).await.unwrap(), 0);
+        assert_eq!(db.get_samples(1).await.unwrap(), vec![
+            (200, "cpu_core_0".to_string(), 20.0),
+            (200, "cpu_core_1".to_string(), 21.0),
+            (300, "cpu_core_0".to_string(), 30.0),
+            (300, "cpu_core_1".to_string(), 31.0),
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn compacting_gives_pruned_space_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_raw_command(1)).await.unwrap();
+        let lines: Vec<RecordedLine> = (0..5_000).map(|i| RecordedLine { timestamp: i, ..line(&"x".repeat(200), 1, "stdout", None) }).collect();
+        db.record_raw_data(1, 0, &lines).await.unwrap();
+        
+        db.prune_raw_data(Some(5_000), None).await.unwrap();
+        let before = db.file_size().unwrap();
+        let reclaimed = db.compact().await.unwrap();
+        assert!(reclaimed > 500_000, "reclaimed {} of {} bytes", reclaimed, before);
+        assert_eq!(db.file_size().unwrap(), before - reclaimed);
+    }
+    
+    #[tokio::test]
+    async fn samples_only_lines_leave_raw_data_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        db.save_widget_instance(&WidgetType::new_cpu_monitor(1)).await.unwrap();
+        let snapshot = RecordedLine { timestamp: 5, samples: vec![("cpu_core_0".to_string(), 42.0)], samples_only: true, ..line("--", 1, "stdout", None) };
+        db.record_raw_data(1, 0, &[line("vmstat output", 1, "stdout", None), snapshot]).await.unwrap();
+        
+        assert_eq!(db.get_all_widget_data(1).await.unwrap(), vec!["vmstat output"]);
+        assert_eq!(db.get_samples(1).await.unwrap(), vec![(5, "cpu_core_0".to_string(), 42.0)]);
+    }
+    
+    #[test]
+    fn ssh_aliases_are_validated_before_saving() {
+        for alias in ["prod-db", "deploy@10.0.0.5", "ops@web-1.example.com", "[fe80::1%eth0]", "user@host:2222"] {
+            assert_eq!(Host::validate_ssh_alias(alias), Ok(()), "{}", alias);
+        }
+        for alias in ["", "web 1", "-oProxyCommand=sh", "@web-1", "deploy@", "web;reboot", "a@b/c"] {
+            assert!(Host::validate_ssh_alias(alias).is_err(), "{}", alias);
+        }
+    }
+    
+    #[tokio::test]
+    async fn host_connection_options_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_db(&dir).await;
+        let mut host = Host {
+            name: "Bastion".to_string(),
+            port: Some(2222),
+            username: Some("ops".to_string()),
+            identity_file: Some("~/.ssh/ops_ed25519".to_string()),
+            ..Host::for_test("bastion.example.com", "")
+        };
+        let id = db.add_host(&host).await.unwrap();
+        
+        let saved = db.get_host_by_name("Bastion").await.unwrap().unwrap();
+        assert_eq!((saved.port, saved.username.as_deref(), saved.identity_file.as_deref()), (Some(2222), Some("ops"), Some("~/.ssh/ops_ed25519")));
+        
+        // Clearing them goes back to whatever ~/.ssh/config says
+        host.port = None;
+        host.username = None;
+        host.identity_file = None;
+        db.update_host(id, &host).await.unwrap();
+        let saved = db.get_host_by_name("Bastion").await.unwrap().unwrap();
+        assert!(saved.ssh_options().is_empty());
+        assert_eq!((saved.port, saved.username, saved.identity_file), (None, None, None));
+        
+        let localhost = db.get_host_by_name("localhost").await.unwrap().unwrap();
+        assert_eq!(localhost.port, None);
+    }
+}
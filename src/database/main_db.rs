@@ -8,7 +8,7 @@ pub struct MainDB {
 impl MainDB {
     pub async fn new() -> Result<Self, sqlx::Error> {
         let skop_dir = ensure_skop_dir().map_err(|e| 
-            sqlx::Error::Io(std::io::Error::new(std::io::ErrorKind::Other, 
+            sqlx::Error::Io(std::io::Error::other(
                 format!("Failed to create skop directory: {}", e))))?;
         
         let db_path = skop_dir.join("main.db");
@@ -22,10 +22,60 @@ impl MainDB {
         Ok(db)
     }
     
+    // Replace an unreadable main.db with a fresh one and re-register every .skop file in the skop dir.
+    // The old file is kept next to it; settings, templates and archived flags don't survive
+    pub async fn rebuild() -> Result<(Self, usize), sqlx::Error> {
+        let skop_dir = ensure_skop_dir().map_err(|e| 
+            sqlx::Error::Io(std::io::Error::other(
+                format!("Failed to create skop directory: {}", e))))?;
+        
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        for suffix in ["", "-wal", "-shm"] {
+            let path = skop_dir.join(format!("main.db{}", suffix));
+            if path.exists() {
+                std::fs::rename(&path, skop_dir.join(format!("main.db{}.corrupt-{}", suffix, now)))?;
+            }
+        }
+        
+        let db = Self::new().await?;
+        
+        let mut files: Vec<_> = std::fs::read_dir(&skop_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "skop"))
+            .collect();
+        files.sort();
+        
+        for path in &files {
+            // Keep the original creation time when the file still has its metadata
+            let created_at = match super::investigation_db::InvestigationDB::open(path).await {
+                Ok(investigation_db) => investigation_db.get_metadata().await.ok().flatten().map(|metadata| metadata.3),
+                Err(e) => {
+                    eprintln!("Registering {} without metadata: {}", path.display(), e);
+                    None
+                }
+            };
+            let id = db.add_investigation(&path.to_string_lossy()).await?;
+            if let Some(created_at) = created_at {
+                sqlx::query("UPDATE investigations SET created_at = ? WHERE id = ?")
+                    .bind(created_at)
+                    .bind(id)
+                    .execute(&db.pool).await?;
+            }
+        }
+        
+        Ok((db, files.len()))
+    }
+    
     async fn initialize(&mut self) -> Result<(), sqlx::Error> {
         // Run SQLx migrations for main database
-        sqlx::migrate!("./migrations/main").run(&self.pool).await?;
-        Ok(())
+        super::migrate(&self.pool, &super::MAIN_MIGRATOR).await
+    }
+    
+    pub async fn schema_status(&self) -> Result<super::SchemaStatus, sqlx::Error> {
+        super::schema_status(&self.pool, &super::MAIN_MIGRATOR).await
     }
     
     pub async fn add_investigation(&self, file_path: &str) -> Result<i64, sqlx::Error> {
@@ -71,6 +121,19 @@ impl MainDB {
         Ok(())
     }
     
+    // Fails if another registered investigation, archived ones included, already has the path
+    pub async fn update_investigation_path(&self, investigation_id: i64, file_path: &str) -> Result<(), sqlx::Error> {
+        let result = sqlx::query("UPDATE investigations SET file_path = ? WHERE id = ?")
+            .bind(file_path)
+            .bind(investigation_id)
+            .execute(&self.pool).await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+    
     pub async fn update_last_accessed(&self, investigation_id: i64) -> Result<(), sqlx::Error> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -91,4 +154,64 @@ impl MainDB {
         Ok(())
     }
     
+    pub async fn get_setting(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool).await?;
+        
+        Ok(row.map(|row| row.get::<String, _>("value")))
+    }
+    
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    // Saving under an existing name replaces that template's widgets
+    pub async fn save_template(&self, name: &str, widgets_json: &str) -> Result<(), sqlx::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as i64;
+        
+        sqlx::query(
+            "INSERT INTO templates (name, widgets_json, created_at) VALUES (?, ?, ?)
+             ON CONFLICT(name) DO UPDATE SET widgets_json = excluded.widgets_json"
+        )
+        .bind(name)
+        .bind(widgets_json)
+        .bind(now)
+        .execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    pub async fn list_templates(&self) -> Result<Vec<(i64, String, String, i64)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, widgets_json, created_at FROM templates ORDER BY name")
+            .fetch_all(&self.pool).await?;
+        
+        Ok(rows.into_iter().map(|row| (
+            row.get::<i64, _>("id"),
+            row.get::<String, _>("name"),
+            row.get::<String, _>("widgets_json"),
+            row.get::<i64, _>("created_at"),
+        )).collect())
+    }
+    
+    pub async fn rename_template(&self, id: i64, name: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE templates SET name = ? WHERE id = ?")
+            .bind(name)
+            .bind(id)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    pub async fn delete_template(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM templates WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -1,7 +1,84 @@
 pub mod main_db;
 pub mod investigation_db;
+pub mod raw_data_writer;
 
 use std::path::PathBuf;
+use sqlx::migrate::Migrator;
+use sqlx::SqlitePool;
+
+pub static MAIN_MIGRATOR: Migrator = sqlx::migrate!("./migrations/main");
+pub static INVESTIGATION_MIGRATOR: Migrator = sqlx::migrate!("./migrations/investigations");
+
+// Schema version applied to a database vs. the newest one this build knows about
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SchemaStatus {
+    pub applied: Option<i64>,
+    pub latest: i64,
+}
+
+impl SchemaStatus {
+    pub fn is_ahead(&self) -> bool {
+        self.applied.is_some_and(|applied| applied > self.latest)
+    }
+    
+    pub fn describe(&self) -> String {
+        match self.applied {
+            None => format!("not initialized (this build: v{})", self.latest),
+            Some(applied) if applied > self.latest => format!("v{}, newer than this build (v{})", applied, self.latest),
+            Some(applied) if applied < self.latest => format!("v{}, will upgrade to v{}", applied, self.latest),
+            Some(applied) => format!("v{} (up to date)", applied),
+        }
+    }
+}
+
+// Opening a database written by a newer skop, which this build can't migrate
+#[derive(Debug)]
+pub struct SchemaAheadError {
+    pub status: SchemaStatus,
+}
+
+impl std::fmt::Display for SchemaAheadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Created by a newer version of skop (schema v{}, this build supports up to v{}). Update skop to open it.",
+            self.status.applied.unwrap_or_default(), self.status.latest)
+    }
+}
+
+impl std::error::Error for SchemaAheadError {}
+
+impl SchemaAheadError {
+    pub fn from_sqlx(error: &sqlx::Error) -> Option<&SchemaAheadError> {
+        match error {
+            sqlx::Error::Configuration(e) => e.downcast_ref::<SchemaAheadError>(),
+            _ => None,
+        }
+    }
+}
+
+pub async fn schema_status(pool: &SqlitePool, migrator: &Migrator) -> Result<SchemaStatus, sqlx::Error> {
+    let latest = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+    
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"
+    ).fetch_one(pool).await?;
+    if !has_table {
+        return Ok(SchemaStatus { applied: None, latest });
+    }
+    
+    let applied: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+        .fetch_one(pool).await?;
+    Ok(SchemaStatus { applied, latest })
+}
+
+// Run migrations, refusing with a clear error when the database is ahead of this build
+pub async fn migrate(pool: &SqlitePool, migrator: &Migrator) -> Result<(), sqlx::Error> {
+    let status = schema_status(pool, migrator).await?;
+    if status.is_ahead() {
+        return Err(sqlx::Error::Configuration(Box::new(SchemaAheadError { status })));
+    }
+    migrator.run(pool).await?;
+    Ok(())
+}
 
 pub fn get_skop_dir() -> PathBuf {
     let home = std::env::var("HOME").expect("HOME environment variable not set");
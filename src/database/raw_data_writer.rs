@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::database::investigation_db::InvestigationDB;
+
+// Lines are written in one transaction per batch: whatever arrived within FLUSH_INTERVAL of the
+// first one, up to BATCH_LINES. A full queue makes the command's reader wait, so a slow disk
+// slows reading instead of buffering output without bound
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+const BATCH_LINES: usize = 500;
+const QUEUE_LINES: usize = 2000;
+
+// One output line on its way to raw_data, with the samples parsed from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedLine {
+    pub timestamp: i64,  // µs since the epoch, taken when the line arrived
+    pub line_content: String,
+    pub line_number: i32,
+    pub stream: &'static str,  // "stdout" or "stderr"
+    pub host: Option<String>,
+    pub run: Option<i64>,  // Start time of the command run it came from, µs since the epoch
+    pub samples: Vec<(String, f64)>,
+    pub samples_only: bool,  // Write just the samples, leaving the line out of raw_data
+}
+
+// Feeds one background task per widget version, so lines are written in the order they were sent
+#[derive(Clone)]
+pub struct RawDataWriter {
+    sender: mpsc::Sender<RecordedLine>,
+}
+
+impl RawDataWriter {
+    // The task flushes what's queued and exits once every clone of the writer is dropped
+    pub fn spawn(db: Arc<InvestigationDB>, widget_id: i32, widget_version: i32) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_LINES);
+        crate::runtime::runtime().spawn(write_batches(db, widget_id, widget_version, receiver));
+        Self { sender }
+    }
+    
+    pub async fn record(&self, line: RecordedLine) {
+        if self.sender.send(line).await.is_err() {
+            eprintln!("Failed to record raw data: writer has stopped");
+        }
+    }
+}
+
+async fn write_batches(db: Arc<InvestigationDB>, widget_id: i32, widget_version: i32, mut receiver: mpsc::Receiver<RecordedLine>) {
+    let mut batch = Vec::with_capacity(BATCH_LINES);
+    while let Some(first) = receiver.recv().await {
+        batch.push(first);
+        let deadline = tokio::time::Instant::now() + FLUSH_INTERVAL;
+        while batch.len() < BATCH_LINES {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(line)) => batch.push(line),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        
+        if let Err(e) = db.record_raw_data(widget_id, widget_version, &batch).await {
+            eprintln!("Failed to record {} lines of raw data: {}", batch.len(), e);
+        }
+        batch.clear();
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use eframe::egui;
+use crate::database::investigation_db::Host;
+use crate::widgets::command_widget::{is_remote_host, remote_command_line, with_command_prefix, CommandSpec};
+
+// ssh's own limit for connecting, then a ceiling on the whole check (auth, the prefix, `true`)
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Longer errors are cut short next to the host and shown in full on hover
+const MAX_REASON_CHARS: usize = 48;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionStatus {
+    Checking,
+    Reachable(Duration),
+    Failed(String),
+}
+
+// Latest result per check command line, so editing any connection detail asks for a fresh test.
+// Kept for the session so every screen shows the same answer
+static RESULTS: LazyLock<Mutex<HashMap<String, ConnectionStatus>>> = LazyLock::new(Default::default);
+
+// Runs `true` through the same prefix and SSH hop the widgets use. BatchMode fails instead of
+// prompting for a password nobody can type in
+pub fn check_command(host: &Host) -> CommandSpec {
+    let spec = with_command_prefix(CommandSpec::new("true"), &host.command_prefix);
+    if !is_remote_host(&host.ssh_alias) {
+        return spec;
+    }
+    let mut ssh = CommandSpec::new("ssh")
+        .arg("-o").arg("BatchMode=yes")
+        .arg("-o").arg(format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS));
+    ssh.args.extend(host.ssh_options());
+    ssh.arg(&host.ssh_alias)
+        .arg(remote_command_line(&spec))
+}
+
+fn result_key(spec: &CommandSpec) -> String {
+    remote_command_line(spec)
+}
+
+pub fn status(host: &Host) -> Option<ConnectionStatus> {
+    RESULTS.lock().unwrap().get(&result_key(&check_command(host))).cloned()
+}
+
+// Start a check in the background; the UI is woken when it finishes
+pub fn start_check(host: &Host) {
+    let spec = check_command(host);
+    let key = result_key(&spec);
+    {
+        let mut results = RESULTS.lock().unwrap();
+        if results.get(&key) == Some(&ConnectionStatus::Checking) {
+            return;
+        }
+        results.insert(key.clone(), ConnectionStatus::Checking);
+    }
+    
+    crate::runtime::runtime().spawn(async move {
+        let status = run_check(&spec).await;
+        RESULTS.lock().unwrap().insert(key, status);
+        crate::runtime::request_repaint();
+    });
+}
+
+async fn run_check(spec: &CommandSpec) -> ConnectionStatus {
+    let started = Instant::now();
+    let output = tokio::process::Command::new(&spec.program)
+        .args(&spec.args)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    
+    match tokio::time::timeout(CHECK_TIMEOUT, output).await {
+        Err(_) => ConnectionStatus::Failed(format!("No answer after {}s", CHECK_TIMEOUT.as_secs())),
+        Ok(Err(e)) => ConnectionStatus::Failed(format!("Failed to run {}: {}", spec.program, e)),
+        Ok(Ok(output)) if output.status.success() => ConnectionStatus::Reachable(started.elapsed()),
+        Ok(Ok(output)) => {
+            // ssh puts the reason on the last line ("Permission denied (publickey).", "Could not resolve hostname ...")
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().rev().find(|line| !line.trim().is_empty())
+                .map(|line| line.trim().to_string())
+                .unwrap_or_else(|| match output.status.code() {
+                    Some(code) => format!("Exited with code {}", code),
+                    None => "Killed by a signal".to_string(),
+                });
+            ConnectionStatus::Failed(reason)
+        }
+    }
+}
+
+// "Test Connection" plus the outcome of the last test of this host
+pub fn render_test_button(ui: &mut egui::Ui, host: &Host) {
+    let checking = status(host) == Some(ConnectionStatus::Checking);
+    if ui.add_enabled(!checking, egui::Button::new("Test Connection")).clicked() {
+        start_check(host);
+    }
+    render_status(ui, host);
+}
+
+// A spinner, green check or red error for the last test; nothing if it hasn't been tested
+pub fn render_status(ui: &mut egui::Ui, host: &Host) {
+    match status(host) {
+        None => {}
+        Some(ConnectionStatus::Checking) => {
+            ui.spinner();
+        }
+        Some(ConnectionStatus::Reachable(elapsed)) => {
+            ui.colored_label(egui::Color32::from_rgb(80, 180, 80), "✔")
+                .on_hover_text(format!("Connected in {} ms", elapsed.as_millis()));
+        }
+        Some(ConnectionStatus::Failed(reason)) => {
+            let reason = crate::redaction::mask_secrets(&reason).into_owned();
+            let short: String = if reason.chars().count() > MAX_REASON_CHARS {
+                reason.chars().take(MAX_REASON_CHARS).chain(std::iter::once('…')).collect()
+            } else {
+                reason.clone()
+            };
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("✖ {}", short))
+                .on_hover_text(reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn remote_checks_go_through_ssh_without_prompting() {
+        let spec = check_command(&Host::for_test("deploy@web-1", "docker exec web"));
+        assert_eq!(spec.program, "ssh");
+        assert_eq!(spec.args, vec!["-o", "BatchMode=yes", "-o", "ConnectTimeout=5", "deploy@web-1", "docker exec web true"]);
+        
+        let local = check_command(&Host::for_test("localhost", "docker exec web"));
+        assert_eq!(local.program, "docker");
+        assert_eq!(local.args, vec!["exec", "web", "true"]);
+    }
+    
+    #[test]
+    fn connection_options_are_part_of_the_check() {
+        let plain = Host::for_test("web-1", "");
+        let custom = Host { port: Some(2222), username: Some("ops".to_string()), identity_file: Some("~/.ssh/ops_ed25519".to_string()), ..plain.clone() };
+        let spec = check_command(&custom);
+        assert_eq!(spec.args, vec!["-o", "BatchMode=yes", "-o", "ConnectTimeout=5", "-p", "2222", "-l", "ops", "-i", "~/.ssh/ops_ed25519", "web-1", "true"]);
+        
+        // A result for the plain alias doesn't carry over to the customised one
+        assert_ne!(result_key(&check_command(&plain)), result_key(&spec));
+    }
+    
+    #[tokio::test]
+    async fn failures_report_the_last_stderr_line() {
+        let spec = CommandSpec::new("sh")
+            .arg("-c")
+            .arg("echo 'debug1: connecting' >&2; echo 'deploy@web-1: Permission denied (publickey).' >&2; echo >&2; exit 255");
+        assert_eq!(run_check(&spec).await, ConnectionStatus::Failed("deploy@web-1: Permission denied (publickey).".to_string()));
+        
+        let silent = CommandSpec::new("sh").arg("-c").arg("exit 3");
+        assert_eq!(run_check(&silent).await, ConnectionStatus::Failed("Exited with code 3".to_string()));
+        
+        assert!(matches!(run_check(&CommandSpec::new("true")).await, ConnectionStatus::Reachable(_)));
+    }
+}
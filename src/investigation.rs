@@ -18,21 +18,91 @@ const ANIMALS: &[&str] = &[
     "Cat", "Dog", "Rabbit", "Turtle", "Penguin", "Octopus", "Whale", "Elephant", "Giraffe", "Zebra"
 ];
 
-fn generate_random_name_and_color() -> (String, [f32; 3]) {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    use std::time::SystemTime;
+// Scrambles the seed so consecutive seeds land on unrelated combinations (splitmix64)
+fn mix_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Same seed, same result. A taken name moves on to the next color/animal combination,
+// and once all of them are taken a number is appended
+pub fn name_and_color_for_seed(seed: u64, is_taken: impl Fn(&str) -> bool) -> (String, [f32; 3]) {
+    let hash = mix_seed(seed);
+    let combinations = COLORS.len() * ANIMALS.len();
+    let start = (hash as usize % COLORS.len()) * ANIMALS.len() + (hash >> 8) as usize % ANIMALS.len();
     
-    let now = SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
-    let mut hasher = DefaultHasher::new();
-    now.hash(&mut hasher);
-    let hash = hasher.finish();
+    let combination = |index: usize| {
+        let (color_name, color_rgb) = COLORS[index / ANIMALS.len()];
+        (format!("{} {}", color_name, ANIMALS[index % ANIMALS.len()]), color_rgb)
+    };
     
-    let (color_name, color_rgb) = COLORS[hash as usize % COLORS.len()];
-    let animal_name = ANIMALS[(hash >> 8) as usize % ANIMALS.len()];
+    for offset in 0..combinations {
+        let (name, color) = combination((start + offset) % combinations);
+        if !is_taken(&name) {
+            return (name, color);
+        }
+    }
     
-    let name = format!("{} {}", color_name, animal_name);
-    (name, color_rgb)
+    let (name, color) = combination(start);
+    let suffix = (2..).find(|n| !is_taken(&format!("{} {}", name, n))).unwrap();
+    (format!("{} {}", name, suffix), color)
+}
+
+fn generate_random_name_and_color(existing_names: &[String]) -> (String, [f32; 3]) {
+    let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos() as u64;
+    name_and_color_for_seed(seed, |name| {
+        existing_names.iter().any(|existing| existing == name) || Investigation::file_path_for(name).exists()
+    })
+}
+
+// Used for investigations whose metadata had to be recreated
+const DEFAULT_COLOR: [f32; 3] = [0.2, 0.4, 0.85];
+
+// "blue_tiger.skop" -> "Blue Tiger"
+fn name_from_file_path(path: &std::path::Path) -> String {
+    let stem = path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Recovered investigation".to_string());
+    
+    stem.split(['_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Why a name can't be used for a new investigation: it's blank, isn't a plain file name, or
+// its .skop file is already taken, on disk or by a listed investigation
+pub fn check_new_name(name: &str, existing: &[Investigation]) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name can't be empty".to_string());
+    }
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err("Name can't contain slashes or start with a dot".to_string());
+    }
+    let path = Investigation::file_path_for(name);
+    if path.exists() || existing.iter().any(|investigation| investigation.file_path == path) {
+        let filename = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+        return Err(format!("{} already exists", filename));
+    }
+    Ok(())
+}
+
+// Why the file can't follow a rename to `name`; a name that keeps the same file is always fine
+pub fn check_file_rename(name: &str, investigation: &Investigation, existing: &[Investigation]) -> Result<(), String> {
+    if Investigation::file_path_for(name.trim()) == investigation.file_path {
+        return Ok(());
+    }
+    check_new_name(name, existing)
 }
 
 pub fn find_color_name(color: [f32; 3]) -> Option<&'static str> {
@@ -46,6 +116,31 @@ pub fn find_color_name(color: [f32; 3]) -> Option<&'static str> {
         .map(|(name, _)| *name)
 }
 
+// What the New Investigation dialog is editing; nothing is created until it's confirmed
+#[derive(Debug, Clone)]
+pub struct InvestigationDraft {
+    pub name: String,
+    pub description: String,  // Blank for the default
+    pub color: [f32; 3],
+}
+
+impl InvestigationDraft {
+    // Starts from a free random name and its color, avoiding the given names and any investigation file on disk
+    pub fn random(existing_names: &[String]) -> Self {
+        let (name, color) = generate_random_name_and_color(existing_names);
+        Self { name, description: String::new(), color }
+    }
+    
+    pub fn into_investigation(self) -> Investigation {
+        let name = self.name.trim().to_string();
+        let description = match self.description.trim() {
+            "" => format!("Investigation: {}", name),
+            description => description.to_string(),
+        };
+        Investigation::new(name, description, self.color)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Investigation {
     pub id: Option<i64>,
@@ -55,23 +150,22 @@ pub struct Investigation {
     pub created_at: i64,
     pub last_accessed: i64,
     pub color: [f32; 3],
+    // The file has no metadata row; name and color are placeholders until repaired
+    #[serde(default)]
+    pub needs_repair: bool,
+    // Why the file can't be opened by this build, e.g. it was written by a newer skop
+    #[serde(default)]
+    pub open_error: Option<String>,
 }
 
 impl Investigation {
-    pub fn new_with_random_name() -> Self {
-        let (name, color) = generate_random_name_and_color();
-        let description = format!("Investigation: {}", name);
-        Self::new(name, description, color)
-    }
-    
     pub fn new(name: String, description: String, color: [f32; 3]) -> Self {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_micros() as i64;
             
-        let filename = format!("{}.skop", name.replace(" ", "_").to_lowercase());
-        let file_path = crate::database::get_skop_dir().join(filename);
+        let file_path = Self::file_path_for(&name);
         
         Self {
             id: None,
@@ -81,9 +175,17 @@ impl Investigation {
             created_at: now,
             last_accessed: now,
             color,
+            needs_repair: false,
+            open_error: None,
         }
     }
     
+    // "Blue Tiger" -> <skop dir>/blue_tiger.skop
+    fn file_path_for(name: &str) -> PathBuf {
+        let filename = format!("{}.skop", name.replace(" ", "_").to_lowercase());
+        crate::database::get_skop_dir().join(filename)
+    }
+    
     pub async fn create(&mut self, main_db: &MainDB) -> Result<(), sqlx::Error> {
         // Create the investigation database file with name, description, and color
         let _investigation_db = InvestigationDB::create(&self.file_path, &self.name, &self.description, &self.color).await?;
@@ -103,8 +205,31 @@ impl Investigation {
             let path_buf = PathBuf::from(&file_path);
             
             // Try to load metadata from the investigation file
-            if let Ok(db) = InvestigationDB::open(&path_buf).await {
-                if let Ok(Some((name, description, color, _created_at, _version))) = db.get_metadata().await {
+            let db = match InvestigationDB::open(&path_buf).await {
+                Ok(db) => db,
+                Err(e) => {
+                    // Files from a newer skop stay listed with an explanation instead of vanishing
+                    if let Some(ahead) = crate::database::SchemaAheadError::from_sqlx(&e) {
+                        investigations.push(Investigation {
+                            id: Some(id),
+                            name: name_from_file_path(&path_buf),
+                            description: String::new(),
+                            file_path: path_buf,
+                            created_at,
+                            last_accessed,
+                            color: DEFAULT_COLOR,
+                            needs_repair: false,
+                            open_error: Some(ahead.to_string()),
+                        });
+                    } else {
+                        eprintln!("Failed to open investigation {}: {}", file_path, e);
+                    }
+                    continue;
+                }
+            };
+            
+            match db.get_metadata().await {
+                Ok(Some((name, description, color, _created_at, _version))) => {
                     investigations.push(Investigation {
                         id: Some(id),
                         name,
@@ -113,14 +238,39 @@ impl Investigation {
                         created_at,
                         last_accessed,
                         color,
+                        needs_repair: false,
+                        open_error: None,
                     });
                 }
+                Ok(None) => {
+                    // Keep partially created files visible so they can be repaired
+                    investigations.push(Investigation {
+                        id: Some(id),
+                        name: name_from_file_path(&path_buf),
+                        description: String::new(),
+                        file_path: path_buf,
+                        created_at,
+                        last_accessed,
+                        color: DEFAULT_COLOR,
+                        needs_repair: true,
+                        open_error: None,
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Failed to read metadata from {}: {}", file_path, e);
+                }
             }
         }
         
         Ok(investigations)
     }
     
+    // Search box match on name or description, ignoring case; a blank query matches everything
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        self.name.to_lowercase().contains(&query) || self.description.to_lowercase().contains(&query)
+    }
+    
     pub async fn open(&self) -> Result<InvestigationDB, sqlx::Error> {
         InvestigationDB::open(&self.file_path).await
     }
@@ -135,6 +285,17 @@ impl Investigation {
         Ok(())
     }
     
+    // Initialize the missing metadata row from the placeholder name and color
+    pub async fn repair_metadata(&mut self) -> Result<(), sqlx::Error> {
+        let db = self.open().await?;
+        if !db.repair_metadata(&self.name, &self.description, &self.color).await? {
+            // Another row appeared in the meantime, use it
+            self.load_metadata().await?;
+        }
+        self.needs_repair = false;
+        Ok(())
+    }
+    
     pub async fn update_last_accessed(&self, main_db: &MainDB) -> Result<(), sqlx::Error> {
         if let Some(id) = self.id {
             main_db.update_last_accessed(id).await?;
@@ -147,6 +308,63 @@ impl Investigation {
         investigation_db.update_metadata(&self.name, &self.description, &self.color).await
     }
     
+    // Where the file would be for the current name, if that isn't where it already is
+    pub fn renamed_file_path(&self) -> Option<PathBuf> {
+        let path = Self::file_path_for(self.name.trim());
+        (path != self.file_path).then_some(path)
+    }
+    
+    // Move the file to match the current name and point the registry entry at it. The file goes
+    // back if the registry can't be updated; nothing should have it open while this runs
+    pub async fn rename_file(&mut self, main_db: &MainDB) -> Result<(), sqlx::Error> {
+        let Some(target) = self.renamed_file_path() else {
+            return Ok(());
+        };
+        if target.exists() {
+            let filename = target.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", filename)).into());
+        }
+        
+        std::fs::rename(&self.file_path, &target)?;
+        if let Some(id) = self.id {
+            if let Err(e) = main_db.update_investigation_path(id, &target.to_string_lossy()).await {
+                let _ = std::fs::rename(&target, &self.file_path);
+                return Err(e);
+            }
+        }
+        
+        // A journal left by a crash has to stay next to its database
+        for suffix in ["-journal", "-wal", "-shm"] {
+            let sidecar = |path: &PathBuf| PathBuf::from(format!("{}{}", path.display(), suffix));
+            if sidecar(&self.file_path).exists() {
+                let _ = std::fs::rename(sidecar(&self.file_path), sidecar(&target));
+            }
+        }
+        
+        self.file_path = target;
+        Ok(())
+    }
+    
+    // Ask for a destination and export a consistent copy of the investigation file
+    pub fn export_database_file(&self, extension: &str) -> Result<(), sqlx::Error> {
+        let stem = self.file_path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "investigation".to_string());
+        
+        let Some(destination) = rfd::FileDialog::new()
+            .set_file_name(format!("{}.{}", stem, extension))
+            .add_filter("Database file", &[extension])
+            .save_file() else {
+            return Ok(());
+        };
+        
+        let rt = crate::runtime::runtime();
+        rt.block_on(async {
+            let db = self.open().await?;
+            db.export_to(&destination).await
+        })
+    }
+    
     pub fn format_timestamp(timestamp: i64) -> String {
         use std::time::{SystemTime, UNIX_EPOCH};
         
@@ -188,10 +406,116 @@ impl Investigation {
         Ok(())
     }
     
+    // Start a fresh investigation with the given widget configs, hosts and redaction rules, but no recorded data
+    pub async fn fork(&self, widgets: &[crate::widgets::WidgetType], main_db: &MainDB) -> Result<Investigation, sqlx::Error> {
+        let existing_names: Vec<String> = Self::load_all(main_db).await?.into_iter().map(|i| i.name).collect();
+        let (name, color) = generate_random_name_and_color(&existing_names);
+        let description = format!("Cloned from {}", self.name);
+        let mut fork = Investigation::new(name, description, color);
+        fork.create(main_db).await?;
+        
+        let source_db = self.open().await?;
+        let fork_db = fork.open().await?;
+        
+        // The new file already has its own localhost entry
+        for host in source_db.list_hosts().await?.iter().filter(|h| !h.is_localhost) {
+            fork_db.add_host(host).await?;
+        }
+        
+        let redaction = crate::redaction::RedactionSettings::load(&source_db).await?;
+        redaction.save(&fork_db).await?;
+        crate::redaction::set_settings(&fork.file_path, &redaction);
+        
+        for widget in widgets {
+            fork_db.save_widget_instance(widget).await?;
+        }
+        
+        Ok(fork)
+    }
+    
+    // Copy this investigation's widgets and data into the target, then archive this one
+    pub async fn merge_into(&self, target: &Investigation, main_db: &MainDB) -> Result<u64, sqlx::Error> {
+        // Opening the source brings its schema up to date before copying
+        let _source_db = self.open().await?;
+        let target_db = target.open().await?;
+        let merged_widgets = target_db.merge_from(&self.file_path).await?;
+        self.archive(main_db).await?;
+        Ok(merged_widgets)
+    }
+    
     pub async fn archive(&self, main_db: &MainDB) -> Result<(), sqlx::Error> {
         if let Some(id) = self.id {
             main_db.archive_investigation(id).await?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn same_seed_gives_the_same_name_and_color() {
+        let (name, color) = name_and_color_for_seed(42, |_| false);
+        assert_eq!(name, "Emerald Jaguar");
+        assert_eq!(find_color_name(color), name.split(' ').next());
+        assert_eq!(name_and_color_for_seed(42, |_| false), (name, color));
+    }
+    
+    #[test]
+    fn neighbouring_seeds_give_different_names() {
+        let (first, _) = name_and_color_for_seed(1_000, |_| false);
+        let (second, _) = name_and_color_for_seed(1_001, |_| false);
+        assert_ne!(first, second);
+    }
+    
+    #[test]
+    fn taken_name_moves_on_to_the_next_combination() {
+        let (first, _) = name_and_color_for_seed(42, |_| false);
+        let (second, _) = name_and_color_for_seed(42, |name| name == first);
+        assert_eq!(second, "Emerald Viper");
+        
+        // The next one is stable too, and skips both
+        let (third, _) = name_and_color_for_seed(42, |name| name == first || name == second);
+        assert_eq!(third, "Emerald Phoenix");
+    }
+    
+    #[test]
+    fn new_names_must_be_filled_in_and_not_clash_with_an_existing_file() {
+        let existing = vec![Investigation::new("Blue Tiger".to_string(), String::new(), COLORS[1].1)];
+        assert!(check_new_name("  ", &existing).is_err());
+        assert!(check_new_name("logs/today", &existing).is_err());
+        // Same file as "Blue Tiger"
+        assert_eq!(check_new_name("blue tiger", &existing), Err("blue_tiger.skop already exists".to_string()));
+        assert_eq!(check_new_name("Disk full on db-3 (checked by tests)", &existing), Ok(()));
+    }
+    
+    #[test]
+    fn a_renamed_file_may_keep_its_own_path_but_not_take_another() {
+        let current = Investigation::new("Blue Tiger".to_string(), String::new(), COLORS[1].1);
+        let existing = vec![current.clone(), Investigation::new("Red Fox".to_string(), String::new(), COLORS[0].1)];
+        assert_eq!(current.renamed_file_path(), None);
+        assert_eq!(check_file_rename("blue tiger ", &current, &existing), Ok(()));
+        assert_eq!(check_file_rename("Red Fox", &current, &existing), Err("red_fox.skop already exists".to_string()));
+        
+        let renamed = Investigation { name: "Prod Outage".to_string(), ..current };
+        assert_eq!(renamed.renamed_file_path().and_then(|path| path.file_name().map(|f| f.to_owned())), Some("prod_outage.skop".into()));
+    }
+    
+    #[test]
+    fn search_matches_name_or_description_ignoring_case() {
+        let investigation = Investigation::new("Prod Outage".to_string(), "Checkout returns 502s".to_string(), COLORS[0].1);
+        assert!(investigation.matches_search(""));
+        assert!(investigation.matches_search("prod out"));
+        assert!(investigation.matches_search(" CHECKOUT "));
+        assert!(!investigation.matches_search("staging"));
+    }
+    
+    #[test]
+    fn number_is_appended_once_every_combination_is_taken() {
+        let (first, _) = name_and_color_for_seed(42, |_| false);
+        let taken = format!("{} 2", first);
+        let (name, _) = name_and_color_for_seed(42, |name| !name.ends_with(char::is_numeric) || name == taken);
+        assert_eq!(name, format!("{} 3", first));
+    }
+}
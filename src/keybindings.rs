@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+// Actions that can be triggered with a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyAction {
+    GoHome,
+    NewInvestigation,
+    OpenSettings,
+    CloseWidget,
+}
+
+impl KeyAction {
+    pub const ALL: &'static [KeyAction] = &[
+        KeyAction::GoHome,
+        KeyAction::NewInvestigation,
+        KeyAction::OpenSettings,
+        KeyAction::CloseWidget,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyAction::GoHome => "Return to home",
+            KeyAction::NewInvestigation => "New investigation",
+            KeyAction::OpenSettings => "Open settings",
+            KeyAction::CloseWidget => "Close the front widget",
+        }
+    }
+    
+    pub fn default_shortcut(&self) -> egui::KeyboardShortcut {
+        match self {
+            KeyAction::GoHome => egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::H),
+            KeyAction::NewInvestigation => egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::N),
+            KeyAction::OpenSettings => egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Comma),
+            KeyAction::CloseWidget => egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::W),
+        }
+    }
+}
+
+// Captured combos need Ctrl, Cmd or Alt, otherwise they would fire while typing
+pub fn has_modifier(shortcut: &egui::KeyboardShortcut) -> bool {
+    let modifiers = shortcut.modifiers;
+    modifiers.command || modifiers.ctrl || modifiers.mac_cmd || modifiers.alt
+}
+
+// Serializable form of an egui::KeyboardShortcut. `command` is Cmd on macOS and Ctrl elsewhere;
+// `ctrl` and `mac_cmd` keep the physical keys apart, e.g. Ctrl+W and Cmd+W on macOS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredShortcut {
+    pub key: String,
+    pub command: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub mac_cmd: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl StoredShortcut {
+    pub fn from_shortcut(shortcut: &egui::KeyboardShortcut) -> Self {
+        Self {
+            key: shortcut.logical_key.name().to_string(),
+            command: shortcut.modifiers.command,
+            ctrl: shortcut.modifiers.ctrl,
+            mac_cmd: shortcut.modifiers.mac_cmd,
+            shift: shortcut.modifiers.shift,
+            alt: shortcut.modifiers.alt,
+        }
+    }
+    
+    pub fn to_shortcut(&self) -> Option<egui::KeyboardShortcut> {
+        let key = egui::Key::from_name(&self.key)?;
+        let modifiers = egui::Modifiers {
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+            mac_cmd: self.mac_cmd,
+            command: self.command,
+        };
+        Some(egui::KeyboardShortcut::new(modifiers, key))
+    }
+}
+
+// User overrides on top of the default shortcut for each action
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub overrides: HashMap<KeyAction, StoredShortcut>,
+}
+
+impl KeyBindings {
+    pub fn shortcut(&self, action: KeyAction) -> egui::KeyboardShortcut {
+        self.overrides.get(&action)
+            .and_then(|stored| stored.to_shortcut())
+            .unwrap_or_else(|| action.default_shortcut())
+    }
+    
+    pub fn set_shortcut(&mut self, action: KeyAction, shortcut: egui::KeyboardShortcut) {
+        if shortcut == action.default_shortcut() {
+            self.overrides.remove(&action);
+        } else {
+            self.overrides.insert(action, StoredShortcut::from_shortcut(&shortcut));
+        }
+    }
+    
+    pub fn reset(&mut self, action: KeyAction) {
+        self.overrides.remove(&action);
+    }
+    
+    // Other actions already bound to the same shortcut
+    pub fn conflicts(&self, action: KeyAction) -> Vec<KeyAction> {
+        let shortcut = self.shortcut(action);
+        KeyAction::ALL.iter()
+            .copied()
+            .filter(|other| *other != action && self.shortcut(*other) == shortcut)
+            .collect()
+    }
+    
+    // Consume the first triggered shortcut from this frame's input
+    pub fn triggered_action(&self, ctx: &egui::Context) -> Option<KeyAction> {
+        KeyAction::ALL.iter()
+            .copied()
+            .find(|action| ctx.input_mut(|i| i.consume_shortcut(&self.shortcut(*action))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn stored_shortcut_keeps_ctrl_and_cmd_apart() {
+        let mac_ctrl = egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::W);
+        let stored = StoredShortcut::from_shortcut(&mac_ctrl);
+        assert_eq!(stored.to_shortcut(), Some(mac_ctrl));
+        
+        let mac_cmd = egui::KeyboardShortcut::new(egui::Modifiers::MAC_CMD | egui::Modifiers::COMMAND, egui::Key::W);
+        let stored = StoredShortcut::from_shortcut(&mac_cmd);
+        assert_eq!(stored.to_shortcut(), Some(mac_cmd));
+    }
+    
+    #[test]
+    fn shortcuts_saved_before_ctrl_and_cmd_were_split_still_load() {
+        let stored: StoredShortcut = serde_json::from_str(r#"{"key":"N","command":true,"shift":false,"alt":false}"#).unwrap();
+        assert_eq!(stored.to_shortcut(), Some(egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::N)));
+    }
+    
+    #[test]
+    fn combos_without_a_modifier_are_not_shortcuts() {
+        assert!(!has_modifier(&egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::W)));
+        assert!(!has_modifier(&egui::KeyboardShortcut::new(egui::Modifiers::SHIFT, egui::Key::W)));
+        assert!(has_modifier(&egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::W)));
+        for action in KeyAction::ALL {
+            assert!(has_modifier(&action.default_shortcut()));
+        }
+    }
+}
@@ -2,25 +2,19 @@ use eframe::egui;
 
 mod widgets;
 mod database;
+mod host_check;
 mod investigation;
+mod keybindings;
+mod redaction;
+mod retention;
+mod runtime;
+mod settings;
+mod ssh_multiplex;
+mod template;
 mod views;
 
 fn main() -> eframe::Result {
-    // Initialize database on startup
-    if let Err(e) = database::ensure_skop_dir() {
-        eprintln!("Failed to create skop directory: {}", e);
-        std::process::exit(1);
-    }
-    
-    // Initialize database in tokio runtime
-    let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-    rt.block_on(async {
-        if let Err(e) = database::main_db::MainDB::new().await {
-            eprintln!("Failed to initialize main database: {}", e);
-            std::process::exit(1);
-        }
-    });
-    
+    // The skop dir and main.db are opened by the app, so failures get an error screen instead of an exit
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1280.0, 720.0]),
@@ -37,6 +31,8 @@ fn main() -> eframe::Result {
 use widgets::{WidgetType, Widget};
 use investigation::Investigation;
 use database::main_db::MainDB;
+use settings::AppSettings;
+use keybindings::KeyAction;
 
 #[derive(PartialEq)]
 pub enum AppMode {
@@ -45,6 +41,7 @@ pub enum AppMode {
     Settings,
     About,
     Help,
+    Error,  // Startup failed, see Skop::startup_error
 }
 
 pub struct Skop {
@@ -55,9 +52,19 @@ pub struct Skop {
     pub investigations: Vec<Investigation>,
     pub current_investigation: Option<Investigation>,
     pub main_db: Option<MainDB>,
+    pub startup_error: Option<views::error::StartupError>,
+    pub settings: AppSettings,
+    pub capturing_key_action: Option<KeyAction>,
     pub show_delete_confirmation: bool,
     pub investigation_to_delete: Option<usize>,
+    pub investigation_to_merge: Option<usize>,
+    pub investigation_to_repair: Option<usize>,
+    pub merge_target: Option<usize>,
+    pub bulk_select_mode: bool,
+    pub selected_investigations: std::collections::HashSet<std::path::PathBuf>,
+    pub pending_bulk_action: Option<views::home::BulkAction>,
     pub home_quote_index: usize,
+    pub investigation_filter: String,  // Search box above the home screen's list
     
     // Widget system (for workspace mode)
     pub widgets: Vec<WidgetType>,
@@ -66,6 +73,30 @@ pub struct Skop {
     // Host management
     pub hosts: Vec<crate::database::investigation_db::Host>,
     
+    // Redaction rules being edited for the open investigation
+    pub redaction_editor: Option<crate::redaction::RedactionSettings>,
+    
+    // Retention policy being edited for the open investigation
+    pub retention_editor: Option<crate::retention::RetentionPolicy>,
+    
+    // New investigation being named before its file is created
+    pub new_investigation: Option<crate::investigation::InvestigationDraft>,
+    
+    // Name being typed for "Save as template"
+    pub template_name_prompt: Option<String>,
+    
+    // Widget whose saved config is shown in the inspector
+    pub inspected_widget: Option<usize>,
+    
+    // Window layouts as last saved, by widget id; sent to egui on the frame after loading
+    pub widget_layouts: std::collections::HashMap<usize, crate::widgets::WidgetLayout>,
+    pub restore_layouts: bool,
+    
+    // Set while scrubbing back through the investigation's history
+    pub replay: Option<views::replay::Replay>,
+    
+    // Connection pool shared by the open investigation's widgets
+    pub investigation_db: Option<std::sync::Arc<database::investigation_db::InvestigationDB>>,
 }
 
 impl Skop {
@@ -80,46 +111,65 @@ impl Skop {
         style.text_styles.insert(egui::TextStyle::Monospace, egui::FontId::new(14.0, egui::FontFamily::Monospace));
         cc.egui_ctx.set_style(style);
         
+        // Widgets redraw the window when their output changes
+        crate::runtime::set_repaint_context(&cc.egui_ctx);
+        
         Self {
             mode: AppMode::Home,
             
             investigations: vec![],
             current_investigation: None,
             main_db: None,
+            startup_error: None,
+            settings: AppSettings::default(),
+            capturing_key_action: None,
             show_delete_confirmation: false,
             investigation_to_delete: None,
+            investigation_to_merge: None,
+            investigation_to_repair: None,
+            merge_target: None,
+            bulk_select_mode: false,
+            selected_investigations: std::collections::HashSet::new(),
+            pending_bulk_action: None,
             home_quote_index: 0,
+            investigation_filter: String::new(),
             
             widgets: vec![],
             next_widget_id: 0,
             
             hosts: vec![],
+            
+            redaction_editor: None,
+            retention_editor: None,
+            
+            new_investigation: None,
+            template_name_prompt: None,
+            inspected_widget: None,
+            widget_layouts: std::collections::HashMap::new(),
+            restore_layouts: false,
+            replay: None,
+            investigation_db: None,
         }
     }
     
     pub fn add_widget(&mut self, mut widget: WidgetType) {
-        // First, save widget to database to satisfy foreign key constraint
-        if let Some(ref current_investigation) = self.current_investigation {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            if let Err(e) = rt.block_on(async {
-                let db = current_investigation.open().await?;
-                db.save_widget_instance(&widget).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
-            }) {
-                eprintln!("Failed to save widget: {}", e);
+        // Open the investigation's pool once and share it between every widget
+        if self.investigation_db.is_none() {
+            if let Some(ref current_investigation) = self.current_investigation {
+                match crate::runtime::runtime().block_on(current_investigation.open()) {
+                    Ok(db) => self.investigation_db = Some(std::sync::Arc::new(db)),
+                    Err(e) => eprintln!("Failed to open investigation database: {}", e),
+                }
             }
         }
         
-        // Set database connection for data capture
-        if let Some(ref current_investigation) = self.current_investigation {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            match rt.block_on(current_investigation.open()) {
-                Ok(db) => {
-                    widget.set_database(Some(std::sync::Arc::new(db)));
-                }
-                Err(e) => {
-                    eprintln!("Failed to open investigation database: {}", e);
-                }
+        if let Some(ref db) = self.investigation_db {
+            // Save widget to database first to satisfy foreign key constraint
+            if let Err(e) = crate::runtime::runtime().block_on(db.save_widget_instance(&widget)) {
+                eprintln!("Failed to save widget: {}", e);
             }
+            // Set database connection for data capture
+            widget.set_database(Some(db.clone()));
         }
         
         // Set available hosts for command widgets
@@ -131,10 +181,47 @@ impl Skop {
         self.next_widget_id += 1;
     }
     
+    // Load the investigation list and settings from a freshly opened main database
+    pub fn use_main_db(&mut self, db: MainDB) {
+        let rt = crate::runtime::runtime();
+        match rt.block_on(Investigation::load_all(&db)) {
+            Ok(investigations) => {
+                self.investigations = investigations;
+                println!("Loaded {} investigations", self.investigations.len());
+            }
+            Err(e) => {
+                println!("Failed to load investigations: {}", e);
+                self.show_startup_error(views::error::StartupError::from_db_error(views::error::StartupStage::InvestigationList, &e));
+            }
+        }
+        match rt.block_on(AppSettings::load(&db)) {
+            Ok(settings) => {
+                crate::runtime::set_max_ssh_connects(settings.max_ssh_connects);
+                self.settings = settings;
+            }
+            Err(e) => println!("Failed to load settings: {}", e),
+        }
+        self.main_db = Some(db);
+    }
+    
+    // Stop and drop all widgets of the currently loaded workspace
+    pub fn close_workspace_widgets(&mut self) {
+        for widget in &self.widgets {
+            widget.stop();
+        }
+        self.widgets.clear();
+        self.replay = None;
+        self.investigation_db = None;
+        // Nothing is using the investigation's SSH connections any more
+        ssh_multiplex::close_all();
+    }
+    
     pub async fn load_widgets_from_db(&mut self, investigation: &Investigation) -> Result<(), Box<dyn std::error::Error>> {
         let db = investigation.open().await?;
         let loaded_widgets = db.load_widget_instances().await?;
         let db_arc = std::sync::Arc::new(db);
+        crate::retention::prune_on_open(db_arc.clone());
+        self.investigation_db = Some(db_arc.clone());
         
         // Load hosts
         match db_arc.list_hosts().await {
@@ -150,13 +237,22 @@ impl Skop {
                     name: "localhost".to_string(),
                     ssh_alias: "localhost".to_string(),
                     description: "Local machine".to_string(),
+                    command_prefix: String::new(),
+                    port: None,
+                    username: None,
+                    identity_file: None,
+                    group: String::new(),
                     is_localhost: true,
                 }];
             }
         }
         
-        for mut widget in loaded_widgets {
+        self.widget_layouts.clear();
+        for (mut widget, layout) in loaded_widgets {
             let widget_id = widget.widget_id();
+            if let Some(layout) = layout {
+                self.widget_layouts.insert(widget_id, layout);
+            }
             
             // Set database connection for data capture
             widget.set_database(Some(db_arc.clone()));
@@ -174,6 +270,7 @@ impl Skop {
                 self.next_widget_id = widget_id + 1;
             }
         }
+        self.restore_layouts = true;
         
         Ok(())
     }
@@ -183,7 +280,7 @@ impl Skop {
         let widget_version = widget.widget_version();
         
         // Get historical data from database
-        let historical_lines = db.get_widget_data(widget_id, widget_version).await?;
+        let historical_lines = db.get_timed_widget_data(widget_id, widget_version).await?;
         
         // Restore widget data using trait method
         widget.restore_widget_data(historical_lines);
@@ -195,27 +292,16 @@ impl Skop {
 
 impl eframe::App for Skop {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Request repaint for live updates
-        ctx.request_repaint();
-        
         // Load database and investigations if not loaded
-        if self.main_db.is_none() {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            match rt.block_on(MainDB::new()) {
-                Ok(db) => {
-                    match rt.block_on(Investigation::load_all(&db)) {
-                        Ok(investigations) => {
-                            self.investigations = investigations;
-                            println!("Loaded {} investigations", self.investigations.len());
-                        }
-                        Err(e) => println!("Failed to load investigations: {}", e),
-                    }
-                    self.main_db = Some(db);
-                    println!("Database initialized successfully");
-                }
-                Err(e) => {
-                    println!("Failed to initialize database: {}", e);
-                }
+        if self.main_db.is_none() && self.mode != AppMode::Error {
+            self.open_main_db();
+        }
+        
+        // Keyboard shortcuts are paused while a new binding is being captured in settings, and
+        // while typing so they can't fire from text
+        if self.capturing_key_action.is_none() && !ctx.wants_keyboard_input() {
+            if let Some(action) = self.settings.keybindings.triggered_action(ctx) {
+                self.handle_key_action(ctx, action);
             }
         }
         
@@ -225,6 +311,42 @@ impl eframe::App for Skop {
             AppMode::Settings => self.render_settings(ctx),
             AppMode::About => self.render_about(ctx),
             AppMode::Help => self.render_help(ctx),
+            AppMode::Error => self.render_error(ctx),
+        }
+    }
+}
+
+impl Skop {
+    fn handle_key_action(&mut self, ctx: &egui::Context, action: KeyAction) {
+        match action {
+            KeyAction::GoHome => {
+                if self.mode == AppMode::InvestigationWorkspace {
+                    self.leave_workspace();
+                } else {
+                    self.mode = AppMode::Home;
+                    self.home_quote_index = 0;
+                }
+            }
+            KeyAction::NewInvestigation => {
+                if self.mode == AppMode::Home {
+                    self.open_new_investigation_dialog();
+                }
+            }
+            KeyAction::OpenSettings => {
+                if self.mode == AppMode::InvestigationWorkspace {
+                    self.leave_workspace();
+                }
+                self.mode = AppMode::Settings;
+            }
+            KeyAction::CloseWidget => {
+                if self.mode != AppMode::InvestigationWorkspace || self.replay.is_some() {
+                    return;
+                }
+                let front = widgets::front_widget(ctx, self.widgets.iter().map(|widget| widget.widget_id()));
+                if let Some(idx) = front.and_then(|id| self.widgets.iter().position(|widget| widget.widget_id() == id)) {
+                    self.close_widget(idx);
+                }
+            }
         }
     }
 }
@@ -235,5 +357,6 @@ impl Drop for Skop {
         for widget in &self.widgets {
             widget.stop();
         }
+        ssh_multiplex::close_all();
     }
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::database::investigation_db::InvestigationDB;
+
+const REDACTION_SETTINGS_KEY: &str = "redaction";
+
+// Common sensitive values that can be switched on without writing a regex
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuiltinPattern {
+    Ipv4,
+    Email,
+    BearerToken,
+}
+
+impl BuiltinPattern {
+    pub const ALL: &'static [BuiltinPattern] = &[
+        BuiltinPattern::Ipv4,
+        BuiltinPattern::Email,
+        BuiltinPattern::BearerToken,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            BuiltinPattern::Ipv4 => "IPv4 addresses",
+            BuiltinPattern::Email => "Email addresses",
+            BuiltinPattern::BearerToken => "Bearer tokens",
+        }
+    }
+    
+    fn pattern(&self) -> &'static str {
+        match self {
+            BuiltinPattern::Ipv4 => r"\b(?:\d{1,3}\.){3}\d{1,3}\b",
+            BuiltinPattern::Email => r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+            BuiltinPattern::BearerToken => r"(?i)\bbearer\s+[A-Za-z0-9\-._~+/]+=*",
+        }
+    }
+    
+    fn replacement(&self) -> &'static str {
+        match self {
+            BuiltinPattern::Ipv4 => "<ip>",
+            BuiltinPattern::Email => "<email>",
+            BuiltinPattern::BearerToken => "Bearer <token>",
+        }
+    }
+}
+
+// User-defined regex and its replacement ($1 etc. refer to capture groups)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+// Per-investigation redaction settings, stored as JSON in the investigation file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    pub builtins: Vec<BuiltinPattern>,
+    pub rules: Vec<RedactionRule>,
+    
+    // Show redacted text in widgets too, not only in what gets recorded
+    pub redact_live_view: bool,
+}
+
+impl RedactionSettings {
+    pub async fn load(db: &InvestigationDB) -> Result<Self, sqlx::Error> {
+        match db.get_setting(REDACTION_SETTINGS_KEY).await? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse redaction settings, using defaults: {}", e);
+                Self::default()
+            })),
+            None => Ok(Self::default()),
+        }
+    }
+    
+    pub async fn save(&self, db: &InvestigationDB) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(self).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        db.set_setting(REDACTION_SETTINGS_KEY, &json).await
+    }
+}
+
+// Credentials on a command line or in a URL, masked regardless of the investigation's rules
+const SECRET_PATTERNS: &[&str] = &[
+    // sshpass -p secret
+    r"(\bsshpass\s+-p\s*)[^\s;&|]+",
+    // --password=secret, --token secret
+    r"(?i)(--?(?:password|passwd|pass|token|secret|api[-_]?key)(?:=|\s+))[^\s;&|]+",
+    // PASSWORD=secret, api_key: secret
+    r"(?i)(\b\w*(?:password|passwd|secret|token|api[-_]?key)[=:]\s*)[^\s;&|]+",
+    // user:secret@host, including inside URLs
+    r"(\b[\w.-]+:)[^\s@/:]+@",
+];
+
+const SECRET_MASK: &str = "****";
+
+fn secret_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| SECRET_PATTERNS.iter().map(|pattern| Regex::new(pattern).unwrap()).collect())
+}
+
+pub fn contains_secret(text: &str) -> bool {
+    secret_patterns().iter().any(|regex| regex.is_match(text))
+}
+
+pub fn mask_secrets(text: &str) -> Cow<'_, str> {
+    let mut result = Cow::Borrowed(text);
+    for regex in secret_patterns() {
+        let replaced = regex.replace_all(&result, |caps: &regex::Captures| {
+            // Keep the prefix (flag, key or user) and the trailing @ so the text stays readable
+            let suffix = if caps[0].ends_with('@') { "@" } else { "" };
+            format!("{}{}{}", &caps[1], SECRET_MASK, suffix)
+        });
+        if let Cow::Owned(replaced) = replaced {
+            result = Cow::Owned(replaced);
+        }
+    }
+    result
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    Regex::new(pattern).map(|_| ()).map_err(|e| e.to_string())
+}
+
+// Compiled rules, applied to each line
+#[derive(Default)]
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+    pub redact_live_view: bool,
+}
+
+impl Redactor {
+    pub fn new(settings: &RedactionSettings) -> Self {
+        let builtins = settings.builtins.iter()
+            .map(|builtin| (builtin.pattern(), builtin.replacement()));
+        let custom = settings.rules.iter()
+            .filter(|rule| !rule.pattern.is_empty())
+            .map(|rule| (rule.pattern.as_str(), rule.replacement.as_str()));
+        
+        // Invalid custom patterns are flagged in the editor and skipped here
+        let rules = builtins.chain(custom)
+            .filter_map(|(pattern, replacement)| Regex::new(pattern).ok().map(|regex| (regex, replacement.to_string())))
+            .collect();
+        
+        Self {
+            rules,
+            redact_live_view: settings.redact_live_view,
+        }
+    }
+    
+    pub fn redact<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut result = mask_secrets(line);
+        for (regex, replacement) in &self.rules {
+            if let Cow::Owned(replaced) = regex.replace_all(&result, replacement.as_str()) {
+                result = Cow::Owned(replaced);
+            }
+        }
+        result
+    }
+    
+    // Line as it should appear in a widget
+    pub fn for_display<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if self.redact_live_view {
+            self.redact(line)
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+}
+
+// Every connection to the same file shares one redactor, keyed by path
+static REDACTORS: OnceLock<Mutex<HashMap<PathBuf, Arc<Redactor>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<HashMap<PathBuf, Arc<Redactor>>> {
+    REDACTORS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn is_loaded(path: &Path) -> bool {
+    registry().lock().unwrap().contains_key(path)
+}
+
+pub fn set_settings(path: &Path, settings: &RedactionSettings) {
+    registry().lock().unwrap().insert(path.to_path_buf(), Arc::new(Redactor::new(settings)));
+}
+
+pub fn redactor_for(path: &Path) -> Arc<Redactor> {
+    registry().lock().unwrap().get(path).cloned().unwrap_or_default()
+}
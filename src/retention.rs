@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use crate::database::investigation_db::InvestigationDB;
+
+const RETENTION_SETTINGS_KEY: &str = "retention";
+
+// How much recorded output an investigation keeps, stored as JSON in the investigation file.
+// Both limits are off by default so nothing is deleted unless asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub max_age_days: Option<u32>,
+    pub max_lines_per_widget: Option<u32>,
+}
+
+impl RetentionPolicy {
+    pub async fn load(db: &InvestigationDB) -> Result<Self, sqlx::Error> {
+        match db.get_setting(RETENTION_SETTINGS_KEY).await? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse retention settings, keeping everything: {}", e);
+                Self::default()
+            })),
+            None => Ok(Self::default()),
+        }
+    }
+    
+    pub async fn save(&self, db: &InvestigationDB) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(self).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        db.set_setting(RETENTION_SETTINGS_KEY, &json).await
+    }
+    
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age_days.is_none() && self.max_lines_per_widget.is_none()
+    }
+    
+    // Delete what falls outside the policy; returns the number of lines removed
+    pub async fn apply(&self, db: &InvestigationDB) -> Result<u64, sqlx::Error> {
+        if self.is_unlimited() {
+            return Ok(0);
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as i64;
+        let recorded_before = self.max_age_days.map(|days| now - days as i64 * 86_400 * 1_000_000);
+        db.prune_raw_data(recorded_before, self.max_lines_per_widget).await
+    }
+}
+
+// Prune an investigation in the background when it's opened, so a large cleanup doesn't hold up loading
+pub fn prune_on_open(db: std::sync::Arc<InvestigationDB>) {
+    crate::runtime::runtime().spawn(async move {
+        let result = async {
+            let policy = RetentionPolicy::load(&db).await?;
+            policy.apply(&db).await
+        }.await;
+        match result {
+            Ok(0) => {}
+            Ok(removed) => println!("Retention policy removed {} recorded lines", removed),
+            Err(e) => eprintln!("Failed to apply retention policy: {}", e),
+        }
+    });
+}
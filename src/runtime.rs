@@ -0,0 +1,118 @@
+use std::sync::{Mutex, OnceLock};
+use tokio::runtime::Runtime;
+use tokio::sync::Semaphore;
+
+// Worker threads shared by every widget and the UI thread's block_on calls
+const WORKER_THREADS: usize = 4;
+
+// Commands allowed to run at once; further starts wait for a free slot
+pub const MAX_RUNNING_COMMANDS: usize = 32;
+
+// SSH connections being set up at once, so opening many remote widgets doesn't trip MaxStartups
+pub const DEFAULT_MAX_SSH_CONNECTS: usize = 5;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+static COMMAND_SLOTS: Semaphore = Semaphore::const_new(MAX_RUNNING_COMMANDS);
+static SSH_CONNECT_SLOTS: Semaphore = Semaphore::const_new(DEFAULT_MAX_SSH_CONNECTS);
+static SSH_CONNECT_LIMIT: Mutex<SlotLimit> = Mutex::new(SlotLimit { limit: DEFAULT_MAX_SSH_CONNECTS, pending_forgets: 0 });
+static REPAINT_CONTEXT: OnceLock<eframe::egui::Context> = OnceLock::new();
+
+pub fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(WORKER_THREADS)
+            .thread_name("skop-worker")
+            .enable_all()
+            .build()
+            .expect("Failed to create shared tokio runtime")
+    })
+}
+
+// The UI only redraws on input or when background work asks it to, so give that work a way to ask
+pub fn set_repaint_context(ctx: &eframe::egui::Context) {
+    let _ = REPAINT_CONTEXT.set(ctx.clone());
+}
+
+// Wake the UI to show new data; a no-op before the window exists (and in tests)
+pub fn request_repaint() {
+    if let Some(ctx) = REPAINT_CONTEXT.get() {
+        ctx.request_repaint();
+    }
+}
+
+pub fn command_slots() -> &'static Semaphore {
+    &COMMAND_SLOTS
+}
+
+pub fn ssh_connect_slots() -> &'static Semaphore {
+    &SSH_CONNECT_SLOTS
+}
+
+// A semaphore's size, and how many of its permits in use are to be forgotten once released
+struct SlotLimit {
+    limit: usize,
+    pending_forgets: usize,
+}
+
+// Grow or shrink the SSH connect limit; slots in use are only taken away once they're released
+pub fn set_max_ssh_connects(limit: usize) {
+    resize_slots(&SSH_CONNECT_SLOTS, &SSH_CONNECT_LIMIT, limit);
+}
+
+fn resize_slots(slots: &'static Semaphore, state: &'static Mutex<SlotLimit>, limit: usize) {
+    let limit = limit.max(1);
+    let mut current = state.lock().unwrap();
+    if limit > current.limit {
+        // Permits still waiting to be forgotten are kept instead
+        let growth = limit - current.limit;
+        let kept = growth.min(current.pending_forgets);
+        current.pending_forgets -= kept;
+        slots.add_permits(growth - kept);
+    } else {
+        let excess = current.limit - limit;
+        let in_use = excess - slots.forget_permits(excess);
+        // Anything still in use is forgotten as it comes back, unless the limit grows again first
+        if in_use > 0 {
+            current.pending_forgets += in_use;
+            runtime().spawn(async move {
+                while let Ok(permit) = slots.acquire().await {
+                    let mut current = state.lock().unwrap();
+                    if current.pending_forgets == 0 {
+                        break;
+                    }
+                    current.pending_forgets -= 1;
+                    permit.forget();
+                }
+            });
+        }
+    }
+    current.limit = limit;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn raising_the_limit_again_keeps_slots_that_were_still_in_use() {
+        static SLOTS: Semaphore = Semaphore::const_new(5);
+        static LIMIT: Mutex<SlotLimit> = Mutex::new(SlotLimit { limit: 5, pending_forgets: 0 });
+        
+        let in_use: Vec<_> = (0..3).map(|_| SLOTS.try_acquire().unwrap()).collect();
+        resize_slots(&SLOTS, &LIMIT, 2);
+        assert_eq!(SLOTS.available_permits(), 0);
+        resize_slots(&SLOTS, &LIMIT, 5);
+        drop(in_use);
+        
+        // The background forgetter may hold one for a moment before giving it back
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while SLOTS.available_permits() < 5 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(SLOTS.available_permits(), 5);
+        
+        // Shrinking with nothing in use takes effect at once
+        resize_slots(&SLOTS, &LIMIT, 3);
+        assert_eq!(SLOTS.available_permits(), 3);
+    }
+}
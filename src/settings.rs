@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use crate::database::main_db::MainDB;
+use crate::keybindings::KeyBindings;
+
+const APP_SETTINGS_KEY: &str = "app_settings";
+
+// Application-wide settings, stored as JSON in the main database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    // Keep workspace widgets running (and recording) while browsing the home screen
+    pub keep_widgets_running: bool,
+    
+    // Remapped keyboard shortcuts
+    pub keybindings: KeyBindings,
+    
+    // Template whose widgets are added to every new investigation
+    pub default_template: Option<i64>,
+    
+    // SSH connections allowed to be setting up at once; further remote starts queue
+    pub max_ssh_connects: usize,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            keep_widgets_running: false,
+            keybindings: KeyBindings::default(),
+            default_template: None,
+            max_ssh_connects: crate::runtime::DEFAULT_MAX_SSH_CONNECTS,
+        }
+    }
+}
+
+impl AppSettings {
+    pub async fn load(main_db: &MainDB) -> Result<Self, sqlx::Error> {
+        match main_db.get_setting(APP_SETTINGS_KEY).await? {
+            Some(json) => Ok(serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Failed to parse app settings, using defaults: {}", e);
+                Self::default()
+            })),
+            None => Ok(Self::default()),
+        }
+    }
+    
+    pub async fn save(&self, main_db: &MainDB) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(self).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        main_db.set_setting(APP_SETTINGS_KEY, &json).await
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+// Remote runs share one authenticated connection per host (OpenSSH ControlMaster) instead of
+// logging in again on every poll. The first run starts the master; ControlPersist keeps it
+// around between runs so periodic widgets don't tear it down and rebuild it each interval.
+// Masters are closed with the investigation, and exit on their own once idle for this long
+const CONTROL_PERSIST_SECS: u64 = 300;
+
+// Destinations (options plus alias) that may have a master running, for closing them later
+static DESTINATIONS: LazyLock<Mutex<HashSet<Vec<String>>>> = LazyLock::new(Default::default);
+
+// Sockets live in a private directory per process. /tmp rather than the temp dir because socket
+// paths are limited to ~104 bytes and macOS's per-user temp dir already uses half of that
+#[cfg(unix)]
+fn control_dir() -> Option<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+    let dir = PathBuf::from("/tmp").join(format!("skop-ssh-{}", std::process::id()));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => Some(dir),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Some(dir),
+        Err(e) => {
+            eprintln!("Failed to create SSH control directory, connecting without multiplexing: {}", e);
+            None
+        }
+    }
+}
+
+// Windows OpenSSH has no ControlMaster
+#[cfg(not(unix))]
+fn control_dir() -> Option<PathBuf> {
+    None
+}
+
+fn control_path_option(dir: &std::path::Path) -> String {
+    // %C is a hash of the local host, remote host, port and user, so it's short and unique per destination
+    format!("ControlPath={}/%C", dir.display())
+}
+
+// Options that route a connection to `destination` (its -p/-l/-i options and alias) through the shared master
+pub fn options_for(destination: &[String]) -> Vec<String> {
+    let Some(dir) = control_dir() else {
+        return vec![];
+    };
+    DESTINATIONS.lock().unwrap().insert(destination.to_vec());
+    vec![
+        "-o".to_string(), "ControlMaster=auto".to_string(),
+        "-o".to_string(), control_path_option(&dir),
+        "-o".to_string(), format!("ControlPersist={}", CONTROL_PERSIST_SECS),
+    ]
+}
+
+// Ask every master started so far to exit; runs still using one are cut off, so stop widgets first
+pub fn close_all() {
+    let destinations: Vec<Vec<String>> = DESTINATIONS.lock().unwrap().drain().collect();
+    if destinations.is_empty() {
+        return;
+    }
+    let Some(dir) = control_dir() else {
+        return;
+    };
+    for destination in destinations {
+        // Fails quickly (and harmlessly) if the master already timed out
+        let _ = std::process::Command::new("ssh")
+            .arg("-o").arg(control_path_option(&dir))
+            .arg("-O").arg("exit")
+            .args(&destination)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+    let _ = std::fs::remove_dir(&dir);
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn connections_share_a_master_per_destination() {
+        let destination = vec!["-p".to_string(), "2222".to_string(), "web-1".to_string()];
+        let options = options_for(&destination);
+        assert_eq!(options[..2], ["-o", "ControlMaster=auto"]);
+        assert!(options[3].starts_with("ControlPath=/tmp/skop-ssh-") && options[3].ends_with("/%C"));
+        assert!(DESTINATIONS.lock().unwrap().contains(&destination));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::database::{main_db::MainDB, investigation_db::InvestigationDB};
+use crate::widgets::WidgetType;
+
+// A named set of widget configs, without any recorded data
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub id: i64,
+    pub name: String,
+    pub widgets_json: String,
+    pub created_at: i64,
+}
+
+impl Template {
+    pub async fn save(name: &str, widgets: &[WidgetType], main_db: &MainDB) -> Result<(), sqlx::Error> {
+        let widgets_json = serde_json::to_string(widgets).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        main_db.save_template(name, &widgets_json).await
+    }
+    
+    pub async fn load_all(main_db: &MainDB) -> Result<Vec<Template>, sqlx::Error> {
+        let rows = main_db.list_templates().await?;
+        Ok(rows.into_iter()
+            .map(|(id, name, widgets_json, created_at)| Template { id, name, widgets_json, created_at })
+            .collect())
+    }
+    
+    pub fn widgets(&self) -> Result<Vec<WidgetType>, serde_json::Error> {
+        serde_json::from_str(&self.widgets_json)
+    }
+    
+    // Add this template's widgets to an investigation file
+    pub async fn apply_to(&self, db: &InvestigationDB) -> Result<usize, sqlx::Error> {
+        let widgets = self.widgets().map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        for widget in &widgets {
+            db.save_widget_instance(widget).await?;
+        }
+        Ok(widgets.len())
+    }
+}
@@ -0,0 +1,142 @@
+use eframe::egui;
+use crate::{AppMode, Skop};
+use crate::database::main_db::MainDB;
+
+// What startup was doing when it failed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartupStage {
+    SkopDirectory,
+    MainDatabase,
+    InvestigationList,
+}
+
+impl StartupStage {
+    pub fn title(&self) -> &'static str {
+        match self {
+            StartupStage::SkopDirectory => "The skop data directory couldn't be created",
+            StartupStage::MainDatabase => "The investigation list couldn't be opened",
+            StartupStage::InvestigationList => "The investigation list couldn't be read",
+        }
+    }
+}
+
+// Shown instead of the home screen until startup succeeds
+pub struct StartupError {
+    pub stage: StartupStage,
+    pub message: String,
+    pub can_rebuild: bool,  // Not for a main.db from a newer skop, which is fine, just unreadable here
+    pub rebuild_error: Option<String>,
+}
+
+impl StartupError {
+    pub fn new(stage: StartupStage, message: String) -> Self {
+        Self {
+            stage,
+            message,
+            can_rebuild: false,
+            rebuild_error: None,
+        }
+    }
+    
+    pub fn from_db_error(stage: StartupStage, error: &sqlx::Error) -> Self {
+        Self {
+            can_rebuild: crate::database::SchemaAheadError::from_sqlx(error).is_none(),
+            ..Self::new(stage, error.to_string())
+        }
+    }
+}
+
+impl Skop {
+    // Create the skop dir and open main.db, switching to the error screen if either fails
+    pub fn open_main_db(&mut self) {
+        if let Err(e) = crate::database::ensure_skop_dir() {
+            self.show_startup_error(StartupError::new(StartupStage::SkopDirectory, e.to_string()));
+            return;
+        }
+        
+        let rt = crate::runtime::runtime();
+        match rt.block_on(MainDB::new()) {
+            Ok(db) => {
+                self.use_main_db(db);
+                println!("Database initialized successfully");
+            }
+            Err(e) => {
+                println!("Failed to initialize database: {}", e);
+                self.show_startup_error(StartupError::from_db_error(StartupStage::MainDatabase, &e));
+            }
+        }
+    }
+    
+    pub fn show_startup_error(&mut self, error: StartupError) {
+        self.startup_error = Some(error);
+        self.mode = AppMode::Error;
+    }
+    
+    pub fn render_error(&mut self, ctx: &egui::Context) {
+        let Some(ref mut error) = self.startup_error else {
+            self.mode = AppMode::Home;
+            return;
+        };
+        let mut rebuild = false;
+        let mut retry = false;
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(60.0);
+                ui.heading(error.stage.title());
+                ui.add_space(10.0);
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), &error.message);
+                ui.add_space(10.0);
+                
+                match error.stage {
+                    StartupStage::SkopDirectory => {
+                        ui.label(format!("skop keeps its data in {}. Check that the location is writable.",
+                            crate::database::get_skop_dir().display()));
+                    }
+                    StartupStage::MainDatabase | StartupStage::InvestigationList => {
+                        ui.label(format!("Your investigations are separate .skop files in {} and are not affected.",
+                            crate::database::get_skop_dir().display()));
+                    }
+                }
+                
+                if error.can_rebuild {
+                    ui.label("Rebuilding creates a new main.db and re-adds every .skop file found there.");
+                    ui.label("The old main.db is kept alongside. App settings, templates and archived flags are reset.");
+                }
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    if error.can_rebuild {
+                        rebuild = ui.button("Rebuild investigation list").clicked();
+                    }
+                    retry = ui.button("Try again").clicked();
+                    if ui.button("Quit").clicked() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                });
+                
+                if let Some(ref rebuild_error) = error.rebuild_error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("Rebuild failed: {}", rebuild_error));
+                }
+            });
+        });
+        
+        if rebuild {
+            let rt = crate::runtime::runtime();
+            match rt.block_on(MainDB::rebuild()) {
+                Ok((db, count)) => {
+                    println!("Rebuilt investigation list with {} files", count);
+                    self.startup_error = None;
+                    self.mode = AppMode::Home;
+                    self.use_main_db(db);
+                }
+                Err(e) => error.rebuild_error = Some(e.to_string()),
+            }
+        } else if retry {
+            // The update loop opens main.db again on the next frame
+            self.startup_error = None;
+            self.main_db = None;
+            self.mode = AppMode::Home;
+        }
+    }
+}
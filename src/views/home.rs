@@ -1,6 +1,12 @@
 use eframe::egui;
 use crate::{AppMode, Skop};
-use crate::investigation::Investigation;
+use crate::investigation::{Investigation, InvestigationDraft, COLORS, check_new_name};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BulkAction {
+    Archive,
+    Delete,
+}
 
 impl Skop {
     pub fn render_home(&mut self, ctx: &egui::Context) {
@@ -54,13 +60,179 @@ impl Skop {
                 });
         }
         
+        // Merge dialog
+        let mut confirmed_merge = None;
+        
+        if let Some(source_idx) = self.investigation_to_merge {
+            if source_idx < self.investigations.len() {
+                let mut keep_open = true;
+                egui::Window::new("Merge Investigation")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                    .show(ctx, |ui| {
+                        let source = &self.investigations[source_idx];
+                        ui.label(format!("Merge '{}' into:", source.name));
+                        
+                        let selected_text = self.merge_target
+                            .and_then(|idx| self.investigations.get(idx))
+                            .map(|inv| inv.name.clone())
+                            .unwrap_or_else(|| "Select investigation".to_string());
+                        
+                        egui::ComboBox::from_id_salt("merge_target")
+                            .selected_text(selected_text)
+                            .width(250.0)
+                            .show_ui(ui, |ui| {
+                                for (idx, investigation) in self.investigations.iter().enumerate() {
+                                    if idx == source_idx {
+                                        continue;
+                                    }
+                                    let label = if investigation.name.eq_ignore_ascii_case(&source.name) {
+                                        format!("{} (same name)", investigation.name)
+                                    } else {
+                                        investigation.name.clone()
+                                    };
+                                    ui.selectable_value(&mut self.merge_target, Some(idx), label);
+                                }
+                            });
+                        
+                        ui.separator();
+                        ui.label("Widgets, recorded data, and hosts are copied into the target");
+                        ui.label("The merged investigation is archived afterwards");
+                        
+                        ui.add_space(10.0);
+                        
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(self.merge_target.is_some(), |ui| {
+                                if ui.button("Merge").clicked() {
+                                    confirmed_merge = self.merge_target.map(|target_idx| (source_idx, target_idx));
+                                    keep_open = false;
+                                }
+                            });
+                            
+                            if ui.button("Cancel").clicked() {
+                                keep_open = false;
+                            }
+                        });
+                    });
+                
+                if !keep_open {
+                    self.investigation_to_merge = None;
+                    self.merge_target = None;
+                }
+            }
+        }
+        
+        if let Some((source_idx, target_idx)) = confirmed_merge {
+            if source_idx < self.investigations.len() && target_idx < self.investigations.len() {
+                let source = self.investigations[source_idx].clone();
+                let target = self.investigations[target_idx].clone();
+                
+                // Widgets loaded for either side would be stale after the merge
+                let is_affected = |inv: &Investigation| inv.file_path == source.file_path || inv.file_path == target.file_path;
+                if self.current_investigation.as_ref().is_some_and(is_affected) {
+                    self.close_workspace_widgets();
+                    self.current_investigation = None;
+                }
+                
+                if let Some(ref db) = self.main_db {
+                    let rt = crate::runtime::runtime();
+                    match rt.block_on(source.merge_into(&target, db)) {
+                        Ok(merged_widgets) => {
+                            println!("Merged {} widgets from '{}' into '{}'", merged_widgets, source.name, target.name);
+                            self.investigations.remove(source_idx);
+                        }
+                        Err(e) => {
+                            eprintln!("ERROR: Failed to merge '{}' into '{}': {}", source.name, target.name, e);
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Bulk action confirmation dialog
+        let mut confirmed_bulk_action = None;
+        
+        if let Some(action) = self.pending_bulk_action {
+            egui::Window::new("Confirm Bulk Action")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .show(ctx, |ui| {
+                    let count = self.selected_investigations.len();
+                    match action {
+                        BulkAction::Archive => {
+                            ui.label(format!("Archive {} investigation(s)?", count));
+                            ui.label("Archived investigations are hidden from view but keep their data");
+                        }
+                        BulkAction::Delete => {
+                            ui.label(format!("Permanently delete {} investigation(s)?", count));
+                            ui.label("All of their data will be removed");
+                        }
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        let confirm_label = match action {
+                            BulkAction::Archive => "Archive",
+                            BulkAction::Delete => "Delete Forever",
+                        };
+                        if ui.button(confirm_label).clicked() {
+                            confirmed_bulk_action = Some(action);
+                            self.pending_bulk_action = None;
+                        }
+                        
+                        if ui.button("Cancel").clicked() {
+                            self.pending_bulk_action = None;
+                        }
+                    });
+                });
+        }
+        
+        if let Some(action) = confirmed_bulk_action {
+            let selected = std::mem::take(&mut self.selected_investigations);
+            let (targets, remaining): (Vec<Investigation>, Vec<Investigation>) = std::mem::take(&mut self.investigations)
+                .into_iter()
+                .partition(|inv| selected.contains(&inv.file_path));
+            self.investigations = remaining;
+            
+            // Widgets may still be running in the background for one of these investigations
+            if self.current_investigation.as_ref().is_some_and(|inv| selected.contains(&inv.file_path)) {
+                self.close_workspace_widgets();
+                self.current_investigation = None;
+            }
+            
+            if let Some(ref db) = self.main_db {
+                let rt = crate::runtime::runtime();
+                for investigation in targets {
+                    let name = investigation.name.clone();
+                    let result = match action {
+                        BulkAction::Archive => rt.block_on(investigation.archive(db)),
+                        BulkAction::Delete => rt.block_on(investigation.delete(db)),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Failed to process investigation '{}': {}", name, e);
+                    }
+                }
+            }
+            
+            self.bulk_select_mode = false;
+        }
+        
         // Handle actions
         if delete_investigation || archive_investigation {
             if let Some(delete_idx) = self.investigation_to_delete.take() {
                 if delete_idx < self.investigations.len() {
                     let investigation = self.investigations.remove(delete_idx);
+                    
+                    // Widgets may still be running in the background for this investigation
+                    if self.current_investigation.as_ref().map(|inv| &inv.file_path) == Some(&investigation.file_path) {
+                        self.close_workspace_widgets();
+                        self.current_investigation = None;
+                    }
                     if let Some(ref db) = self.main_db {
-                        let rt = tokio::runtime::Runtime::new().unwrap();
+                        let rt = crate::runtime::runtime();
                         if delete_investigation {
                             let _ = rt.block_on(investigation.delete(db));
                         } else if archive_investigation {
@@ -74,17 +246,122 @@ impl Skop {
         // Handle investigation selection - store the clicked investigation
         let mut selected_investigation: Option<Investigation> = None;
         
+        // Repair prompt for investigations whose file has no metadata
+        if let Some(repair_idx) = self.investigation_to_repair {
+            if repair_idx < self.investigations.len() {
+                let mut keep_open = true;
+                let mut repair = false;
+                egui::Window::new("Repair Investigation")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                    .show(ctx, |ui| {
+                        let investigation = &self.investigations[repair_idx];
+                        ui.label(format!("{} has no investigation metadata.", investigation.file_path.display()));
+                        ui.label("It may have been partially created or damaged.");
+                        ui.add_space(5.0);
+                        ui.label(format!("Initialize it as '{}' with the default color? Widgets and recorded data are kept.", investigation.name));
+                        
+                        ui.add_space(10.0);
+                        
+                        ui.horizontal(|ui| {
+                            if ui.button("Repair and Open").clicked() {
+                                repair = true;
+                                keep_open = false;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                keep_open = false;
+                            }
+                        });
+                    });
+                
+                if repair {
+                    let rt = crate::runtime::runtime();
+                    let investigation = &mut self.investigations[repair_idx];
+                    match rt.block_on(investigation.repair_metadata()) {
+                        Ok(()) => selected_investigation = Some(investigation.clone()),
+                        Err(e) => eprintln!("Failed to repair investigation metadata: {}", e),
+                    }
+                }
+                if !keep_open {
+                    self.investigation_to_repair = None;
+                }
+            } else {
+                self.investigation_to_repair = None;
+            }
+        }
+        
+        self.render_new_investigation_dialog(ctx);
+        
         // Left panel - Investigations list
         egui::SidePanel::left("investigations_panel")
             .default_width(400.0)
             .resizable(true)
             .show(ctx, |ui| {
                 if self.investigations.is_empty() {
+                    // First-run guide
                     ui.add_space(20.0);
-                    ui.label("No investigations yet");
+                    ui.heading("No investigations yet");
+                    ui.add_space(10.0);
+                    ui.label("An investigation is a workspace for one problem you are chasing down.");
+                    ui.add_space(5.0);
+                    ui.label("Add widgets to it to run commands and monitor CPU, processes, and network connections, locally or over SSH.");
+                    ui.add_space(5.0);
+                    ui.label("Everything the widgets produce is recorded with timestamps in a single .skop file, so you can come back to it or share it later.");
+                    ui.add_space(15.0);
+                    if ui.button(egui::RichText::new("Create your first investigation").size(16.0)).clicked() {
+                        self.open_new_investigation_dialog();
+                    }
+                    ui.add_space(5.0);
+                    if ui.link("Browse templates").on_hover_text("Templates start a new investigation with a saved set of widgets").clicked() {
+                        self.open_template_settings(ctx);
+                    }
                 } else {
+                    // Selection mode toolbar
+                    ui.horizontal(|ui| {
+                        if self.bulk_select_mode {
+                            let count = self.selected_investigations.len();
+                            ui.add_enabled_ui(count > 0, |ui| {
+                                if ui.button(format!("Archive selected ({})", count)).clicked() {
+                                    self.pending_bulk_action = Some(BulkAction::Archive);
+                                }
+                                if ui.button(format!("Delete selected ({})", count)).clicked() {
+                                    self.pending_bulk_action = Some(BulkAction::Delete);
+                                }
+                            });
+                            if ui.button("Done").clicked() {
+                                self.bulk_select_mode = false;
+                                self.selected_investigations.clear();
+                            }
+                        } else if ui.button("Select").clicked() {
+                            self.bulk_select_mode = true;
+                        }
+                    });
+                    ui.add_space(5.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.investigation_filter)
+                            .hint_text("🔍 Search investigations")
+                            .desired_width(ui.available_width() - 30.0));
+                        if !self.investigation_filter.is_empty() && ui.small_button("✖").on_hover_text("Clear search").clicked() {
+                            self.investigation_filter.clear();
+                        }
+                    });
+                    ui.add_space(5.0);
+                    
+                    // Investigations sharing a name are likely duplicates of the same incident
+                    let mut name_counts = std::collections::HashMap::new();
+                    for investigation in &self.investigations {
+                        *name_counts.entry(investigation.name.to_lowercase()).or_insert(0) += 1;
+                    }
+                    
                     egui::ScrollArea::vertical().show(ui, |ui| {
-                        for (idx, investigation) in self.investigations.iter().enumerate() {
+                        // Indices stay those of self.investigations, which the actions below refer to
+                        let filter = self.investigation_filter.as_str();
+                        if !self.investigations.iter().any(|investigation| investigation.matches_search(filter)) {
+                            ui.weak(format!("No investigations match \"{}\"", filter.trim()));
+                        }
+                        for (idx, investigation) in self.investigations.iter().enumerate().filter(|(_, investigation)| investigation.matches_search(filter)) {
                             let response = ui.allocate_response(
                                 egui::vec2(ui.available_width(), 80.0),
                                 egui::Sense::click()
@@ -129,9 +406,19 @@ impl Skop {
                             ui.painter().rect_filled(response.rect, 4.0, bg_color);
                             ui.painter().rect_stroke(response.rect, 4.0, ui.style().visuals.window_stroke(), egui::StrokeKind::Inside);
                             
-                            // Click to open investigation
+                            // Click to open investigation, or toggle it in selection mode
                             if response.clicked() {
-                                selected_investigation = Some(investigation.clone());
+                                if self.bulk_select_mode {
+                                    if !self.selected_investigations.remove(&investigation.file_path) {
+                                        self.selected_investigations.insert(investigation.file_path.clone());
+                                    }
+                                } else if investigation.open_error.is_some() {
+                                    // Can't be opened by this build, the row explains why
+                                } else if investigation.needs_repair {
+                                    self.investigation_to_repair = Some(idx);
+                                } else {
+                                    selected_investigation = Some(investigation.clone());
+                                }
                             }
                             
                             // Content within the rect
@@ -139,12 +426,38 @@ impl Skop {
                             ui.allocate_new_ui(egui::UiBuilder::new().max_rect(inner_rect), |ui| {
                                 ui.vertical(|ui| {
                                     ui.horizontal(|ui| {
+                                        if self.bulk_select_mode {
+                                            let mut is_selected = self.selected_investigations.contains(&investigation.file_path);
+                                            if ui.checkbox(&mut is_selected, "").changed() {
+                                                if is_selected {
+                                                    self.selected_investigations.insert(investigation.file_path.clone());
+                                                } else {
+                                                    self.selected_investigations.remove(&investigation.file_path);
+                                                }
+                                            }
+                                        }
                                         ui.label(egui::RichText::new(&investigation.name).size(14.0).strong());
                                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                            if ui.small_button("🗑").clicked() {
+                                            if !self.bulk_select_mode && ui.small_button("🗑").clicked() {
                                                 self.investigation_to_delete = Some(idx);
                                                 self.show_delete_confirmation = true;
                                             }
+                                            if !self.bulk_select_mode && self.investigations.len() > 1
+                                                && ui.small_button("⇄").on_hover_text("Merge into…").clicked() {
+                                                self.investigation_to_merge = Some(idx);
+                                                self.merge_target = None;
+                                            }
+                                            if let Some(ref error) = investigation.open_error {
+                                                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), egui::RichText::new("⚠ can't open").size(10.0))
+                                                    .on_hover_text(error);
+                                            }
+                                            if investigation.needs_repair {
+                                                ui.colored_label(egui::Color32::from_rgb(230, 160, 60), egui::RichText::new("⚠ missing metadata").size(10.0))
+                                                    .on_hover_text("Click to repair this investigation");
+                                            }
+                                            if name_counts.get(&investigation.name.to_lowercase()).copied().unwrap_or(0) > 1 {
+                                                ui.label(egui::RichText::new("possible duplicate").size(10.0).italics());
+                                            }
                                         });
                                     });
                                     ui.add_space(2.0);
@@ -159,7 +472,7 @@ impl Skop {
                                         .color(secondary_text_color));
                                     
                                     // Widget summary
-                                    let rt = tokio::runtime::Runtime::new().unwrap();
+                                    let rt = crate::runtime::runtime();
                                     match rt.block_on(async {
                                         let db = investigation.open().await?;
                                         db.get_widget_summary().await
@@ -194,17 +507,25 @@ impl Skop {
         
         // Handle investigation selection outside the borrow
         if let Some(investigation) = selected_investigation {
-            self.current_investigation = Some(investigation.clone());
+            let rt = crate::runtime::runtime();
             
-            // Clear existing widgets
-            self.widgets.clear();
+            // Widgets kept running in the background are resumed as-is instead of reloaded
+            let is_resuming = self.settings.keep_widgets_running
+                && self.current_investigation.as_ref().map(|inv| &inv.file_path) == Some(&investigation.file_path)
+                && !self.widgets.is_empty();
             
-            // Load saved widgets from database
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            if let Err(e) = rt.block_on(self.load_widgets_from_db(&investigation)) {
-                eprintln!("Failed to load widgets: {}", e);
+            if !is_resuming {
+                // Stop and clear widgets from any previously opened investigation
+                self.close_workspace_widgets();
+                
+                // Load saved widgets from database
+                if let Err(e) = rt.block_on(self.load_widgets_from_db(&investigation)) {
+                    eprintln!("Failed to load widgets: {}", e);
+                }
             }
             
+            self.current_investigation = Some(investigation.clone());
+            
             self.mode = AppMode::InvestigationWorkspace;
             
             if let Some(ref db) = self.main_db {
@@ -238,25 +559,7 @@ impl Skop {
                 ui.vertical_centered(|ui| {
                     if ui.add_sized([280.0, 50.0], egui::Button::new(egui::RichText::new("New Investigation").size(18.0))).clicked() {
                         println!("New Investigation button clicked");
-                        let mut investigation = Investigation::new_with_random_name();
-                        println!("Created investigation: {}", investigation.name);
-                        
-                        if let Some(ref db) = self.main_db {
-                            let rt = tokio::runtime::Runtime::new().unwrap();
-                            match rt.block_on(investigation.create(db)) {
-                                Ok(_) => {
-                                    println!("Investigation created successfully");
-                                    self.investigations.push(investigation.clone());
-                                    self.current_investigation = Some(investigation);
-                                    // Clear widgets for new investigation
-                                    self.widgets.clear();
-                                    self.mode = AppMode::InvestigationWorkspace;
-                                }
-                                Err(e) => println!("Failed to create investigation: {}", e),
-                            }
-                        } else {
-                            println!("No database available");
-                        }
+                        self.open_new_investigation_dialog();
                     }
                     
                     ui.add_space(10.0);
@@ -286,4 +589,143 @@ impl Skop {
             });
         });
     }
+    
+    pub fn open_new_investigation_dialog(&mut self) {
+        let existing_names: Vec<String> = self.investigations.iter().map(|i| i.name.clone()).collect();
+        self.new_investigation = Some(InvestigationDraft::random(&existing_names));
+    }
+    
+    // Name, description and color for a new investigation; its file is only created on "Create"
+    fn render_new_investigation_dialog(&mut self, ctx: &egui::Context) {
+        let Some(ref mut draft) = self.new_investigation else {
+            return;
+        };
+        let mut open = true;
+        let mut create = false;
+        let mut cancel = false;
+        let name_error = check_new_name(&draft.name, &self.investigations).err();
+        
+        egui::Window::new("New Investigation")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("new_investigation_form")
+                    .num_columns(2)
+                    .spacing([10.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label("Name:");
+                        let response = ui.add(egui::TextEdit::singleline(&mut draft.name).desired_width(260.0));
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            create = true;
+                        }
+                        ui.end_row();
+                        
+                        ui.label("Description:");
+                        ui.add(egui::TextEdit::multiline(&mut draft.description)
+                            .hint_text(format!("Investigation: {}", draft.name.trim()))
+                            .desired_rows(2)
+                            .desired_width(260.0));
+                        ui.end_row();
+                        
+                        ui.label("Color:");
+                        ui.horizontal_wrapped(|ui| {
+                            ui.set_max_width(260.0);
+                            for (color_name, color_rgb) in COLORS {
+                                let color = egui::Color32::from_rgb(
+                                    (color_rgb[0] * 255.0) as u8,
+                                    (color_rgb[1] * 255.0) as u8,
+                                    (color_rgb[2] * 255.0) as u8,
+                                );
+                                let (rect, response) = ui.allocate_exact_size(egui::vec2(20.0, 20.0), egui::Sense::click());
+                                ui.painter().rect_filled(rect, 2.0, color);
+                                if draft.color == *color_rgb {
+                                    ui.painter().rect_stroke(rect.expand(2.0), 3.0, egui::Stroke::new(2.0_f32, ui.visuals().strong_text_color()), egui::StrokeKind::Outside);
+                                }
+                                if response.on_hover_text(*color_name).clicked() {
+                                    draft.color = *color_rgb;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    });
+                
+                if let Some(ref error) = name_error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                }
+                
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(name_error.is_none(), egui::Button::new("Create")).clicked() {
+                        create = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if create && name_error.is_none() {
+            if let Some(draft) = self.new_investigation.take() {
+                self.create_investigation(draft.into_investigation());
+            }
+        } else if cancel || !open {
+            self.new_investigation = None;
+        }
+    }
+    
+    pub fn create_investigation(&mut self, mut investigation: Investigation) {
+        println!("Created investigation: {}", investigation.name);
+        
+        if let Some(ref db) = self.main_db {
+            let rt = crate::runtime::runtime();
+            match rt.block_on(investigation.create(db)) {
+                Ok(_) => {
+                    println!("Investigation created successfully");
+                    self.investigations.push(investigation.clone());
+                    self.current_investigation = Some(investigation.clone());
+                    // Stop and clear widgets for new investigation
+                    self.close_workspace_widgets();
+                    
+                    if let Some(template_id) = self.settings.default_template {
+                        self.apply_default_template(template_id, &investigation);
+                    }
+                    
+                    self.mode = AppMode::InvestigationWorkspace;
+                }
+                Err(e) => println!("Failed to create investigation: {}", e),
+            }
+        } else {
+            println!("No database available");
+        }
+    }
+    
+    fn apply_default_template(&mut self, template_id: i64, investigation: &Investigation) {
+        let Some(ref main_db) = self.main_db else {
+            return;
+        };
+        
+        let rt = crate::runtime::runtime();
+        let result = rt.block_on(async {
+            let templates = crate::template::Template::load_all(main_db).await?;
+            // The default may have been deleted since it was chosen
+            let Some(template) = templates.into_iter().find(|t| t.id == template_id) else {
+                return Ok(None);
+            };
+            let db = investigation.open().await?;
+            template.apply_to(&db).await.map(|count| Some((template.name, count)))
+        });
+        
+        match result {
+            Ok(Some((name, count))) => {
+                println!("Applied template '{}' ({} widgets)", name, count);
+                if let Err(e) = rt.block_on(self.load_widgets_from_db(investigation)) {
+                    eprintln!("Failed to load widgets: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("ERROR: Failed to apply default template: {}", e),
+        }
+    }
 }
\ No newline at end of file
@@ -2,4 +2,6 @@ pub mod home;
 pub mod workspace;
 pub mod settings;
 pub mod about;
-pub mod help;
\ No newline at end of file
+pub mod help;
+pub mod error;
+pub mod replay;
\ No newline at end of file
@@ -0,0 +1,145 @@
+use eframe::egui;
+use crate::Skop;
+use crate::widgets::{WidgetType, Widget};
+use crate::investigation::Investigation;
+
+// The workspace as it was at `timestamp`, rebuilt from the investigation file. Replayed widgets
+// are never started or given the database, so nothing is run or recorded while scrubbing
+pub struct Replay {
+    pub timestamp: i64,
+    pub widgets: Vec<WidgetType>,
+}
+
+fn now_micros() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64
+}
+
+// "+1h 02m 05s" after the investigation was created
+fn format_offset(micros: i64) -> String {
+    let secs = micros.max(0) / 1_000_000;
+    let (hours, mins, secs) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("+{}h {:02}m {:02}s", hours, mins, secs)
+    } else {
+        format!("+{}m {:02}s", mins, secs)
+    }
+}
+
+impl Skop {
+    // Live widgets keep running (and recording) behind the replay
+    pub fn start_replay(&mut self, ctx: &egui::Context) {
+        self.replay = Some(Replay { timestamp: now_micros(), widgets: vec![] });
+        self.load_replay(ctx, now_micros());
+    }
+    
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+        // Replayed windows share ids with the live ones, so put those back where they were saved
+        self.restore_layouts = true;
+    }
+    
+    fn load_replay(&mut self, ctx: &egui::Context, timestamp: i64) {
+        let (Some(investigation), Some(replay)) = (&self.current_investigation, &mut self.replay) else {
+            return;
+        };
+        replay.timestamp = timestamp;
+        
+        let rt = crate::runtime::runtime();
+        let loaded = rt.block_on(async {
+            let db = investigation.open().await?;
+            let mut widgets = Vec::new();
+            for (mut widget, layout) in db.load_widget_instances_at_time(timestamp).await? {
+                let lines = db.get_widget_data_at_time(widget.widget_id() as i32, timestamp).await?;
+                widget.restore_widget_data(lines);
+                widgets.push((widget, layout));
+            }
+            Ok::<_, Box<dyn std::error::Error>>(widgets)
+        });
+        
+        match loaded {
+            Ok(loaded) => {
+                let mut widgets = Vec::new();
+                for (mut widget, layout) in loaded {
+                    widget.set_available_hosts(self.hosts.clone());
+                    // Windows that weren't on screen a moment ago open where they were last saved
+                    if let Some(layout) = layout {
+                        if !replay.widgets.iter().any(|w| w.widget_id() == widget.widget_id()) {
+                            crate::widgets::restore_layout(ctx, widget.widget_id(), layout);
+                        }
+                    }
+                    widgets.push(widget);
+                }
+                replay.widgets = widgets;
+            }
+            Err(e) => eprintln!("ERROR: Failed to load widgets for replay: {}", e),
+        }
+    }
+    
+    // Timeline from the investigation's creation to now; dragging it reloads the workspace at that moment
+    pub fn render_replay_bar(&mut self, ctx: &egui::Context) {
+        let (Some(replay), Some(investigation)) = (&self.replay, &self.current_investigation) else {
+            return;
+        };
+        let start = investigation.created_at;
+        let now = now_micros();
+        let mut timestamp = replay.timestamp.clamp(start, now);
+        let mut changed = false;
+        let mut leave = false;
+        
+        egui::TopBottomPanel::bottom("replay_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("⏪ Replay").strong());
+                
+                ui.spacing_mut().slider_width = (ui.available_width() - 320.0).max(200.0);
+                changed = ui.add(egui::Slider::new(&mut timestamp, start..=now)
+                    .show_value(false))
+                    .changed();
+                
+                ui.monospace(format_offset(timestamp - start));
+                ui.weak(Investigation::format_timestamp(timestamp));
+                
+                if ui.button("Now").on_hover_text("Jump to the latest recorded output").clicked() {
+                    timestamp = now;
+                    changed = true;
+                }
+                if ui.button("Back to live").clicked() {
+                    leave = true;
+                }
+            });
+        });
+        
+        if leave {
+            self.stop_replay();
+        } else if changed {
+            self.load_replay(ctx, timestamp);
+        }
+    }
+    
+    pub fn render_replay_widgets(&mut self, ctx: &egui::Context) {
+        let Some(ref mut replay) = self.replay else {
+            return;
+        };
+        
+        let mut closed = vec![];
+        for (idx, widget) in replay.widgets.iter_mut().enumerate() {
+            let (open, refresh_clicked) = widget.render(ctx, idx);
+            
+            // Start in a replayed window would run the command again; replay only shows what was recorded
+            if refresh_clicked {
+                widget.stop();
+            }
+            
+            // Closing a replayed window only hides it from this replay
+            if !open {
+                closed.push(idx);
+            }
+        }
+        for idx in closed.into_iter().rev() {
+            replay.widgets.remove(idx);
+        }
+    }
+}
+
@@ -1,18 +1,460 @@
 use eframe::egui;
 use crate::{AppMode, Skop};
+use crate::widgets::Widget;
+use crate::keybindings::KeyAction;
+use crate::database::investigation_db::Host;
+
+// Set for one frame to bring the templates section into view
+const SCROLL_TO_TEMPLATES: &str = "settings_scroll_to_templates";
 
 impl Skop {
     pub fn render_settings(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.add_space(50.0);
-                if ui.button("← Back").clicked() {
-                    self.mode = AppMode::Home;
-                    self.home_quote_index = 0; // Reset to trigger new quote selection
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(50.0);
+                    if ui.button("← Back").clicked() {
+                        self.mode = AppMode::Home;
+                        self.home_quote_index = 0; // Reset to trigger new quote selection
+                    }
+                    ui.add_space(50.0);
+                    ui.heading(egui::RichText::new("Settings").size(32.0));
+                    ui.add_space(30.0);
+                    
+                    let mut settings_changed = false;
+                    
+                    if ui.checkbox(&mut self.settings.keep_widgets_running, "Keep monitoring when viewing home").changed() {
+                        settings_changed = true;
+                    }
+                    ui.label(egui::RichText::new("Widgets keep running and recording while you browse other investigations")
+                        .size(11.0)
+                        .color(ui.style().visuals.weak_text_color()));
+                    
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Max SSH connections opening at once:");
+                        if ui.add(egui::DragValue::new(&mut self.settings.max_ssh_connects).range(1..=50)).changed() {
+                            crate::runtime::set_max_ssh_connects(self.settings.max_ssh_connects);
+                            settings_changed = true;
+                        }
+                    });
+                    ui.label(egui::RichText::new("Remote widgets beyond this wait as \"Queued\" until a connection is established")
+                        .size(11.0)
+                        .color(ui.style().visuals.weak_text_color()));
+                    
+                    ui.add_space(30.0);
+                    ui.heading("Keyboard Shortcuts");
+                    ui.add_space(10.0);
+                    
+                    if self.render_keybindings(ui) {
+                        settings_changed = true;
+                    }
+                    
+                    ui.add_space(30.0);
+                    ui.heading("Hosts");
+                    ui.add_space(10.0);
+                    self.render_hosts(ui);
+                    
+                    ui.add_space(30.0);
+                    let templates_heading = ui.heading("Templates");
+                    if ui.data_mut(|d| d.remove_temp::<bool>(egui::Id::new(SCROLL_TO_TEMPLATES))).unwrap_or(false) {
+                        templates_heading.scroll_to_me(Some(egui::Align::TOP));
+                    }
+                    ui.add_space(10.0);
+                    
+                    if self.render_templates(ui) {
+                        settings_changed = true;
+                    }
+                    
+                    ui.add_space(30.0);
+                    ui.heading("Diagnostics");
+                    ui.add_space(10.0);
+                    self.render_diagnostics(ui);
+                    
+                    if settings_changed {
+                        // Widgets left running in the background stop once the setting is turned off
+                        if !self.settings.keep_widgets_running {
+                            for widget in &self.widgets {
+                                widget.stop();
+                            }
+                        }
+                        
+                        if let Some(ref db) = self.main_db {
+                            let rt = crate::runtime::runtime();
+                            if let Err(e) = rt.block_on(self.settings.save(db)) {
+                                eprintln!("Failed to save settings: {}", e);
+                            }
+                        }
+                    }
+                });
+            });
+        });
+    }
+    
+    // Open settings scrolled down to the templates
+    pub fn open_template_settings(&mut self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(egui::Id::new(SCROLL_TO_TEMPLATES), true));
+        self.mode = AppMode::Settings;
+    }
+    
+    // Edit, delete and test the open investigation's hosts
+    fn render_hosts(&mut self, ui: &mut egui::Ui) {
+        let Some(investigation) = self.current_investigation.clone() else {
+            ui.label(egui::RichText::new("Hosts are saved per investigation. Open one to manage its hosts.")
+                .size(11.0)
+                .color(ui.style().visuals.weak_text_color()));
+            return;
+        };
+        ui.label(egui::RichText::new(format!("Hosts of \"{}\". Widgets on a deleted host move to localhost.", investigation.name))
+            .size(11.0)
+            .color(ui.style().visuals.weak_text_color()));
+        
+        enum HostAction {
+            Update(i64, Host),
+            Delete(i64),
+        }
+        let mut action = None;
+        
+        egui::Grid::new("hosts_grid")
+            .num_columns(9)
+            .spacing([12.0, 8.0])
+            .show(ui, |ui| {
+                for title in ["Name", "SSH Alias", "Command Prefix", "Description", "Port", "Username", "Identity File", "Group", ""] {
+                    ui.strong(title);
+                }
+                ui.end_row();
+                
+                for host in &self.hosts {
+                    let Some(id) = host.id else {
+                        continue;
+                    };
+                    let saved = [
+                        host.name.clone(),
+                        host.ssh_alias.clone(),
+                        host.command_prefix.clone(),
+                        host.description.clone(),
+                        host.port.map(|port| port.to_string()).unwrap_or_default(),
+                        host.username.clone().unwrap_or_default(),
+                        host.identity_file.clone().unwrap_or_default(),
+                        host.group.clone(),
+                    ];
+                    
+                    // The local machine is always there as-is; it can only be tested
+                    if host.is_localhost {
+                        ui.label(format!("🏠 {}", host.name));
+                        ui.monospace(&host.ssh_alias);
+                        ui.label("");
+                        ui.label(&host.description);
+                        for _ in 0..3 {
+                            ui.label("");
+                        }
+                        ui.label(&host.group);
+                        ui.horizontal(|ui| {
+                            crate::host_check::render_test_button(ui, host);
+                        });
+                        ui.end_row();
+                        continue;
+                    }
+                    
+                    let edit_id = egui::Id::new(("host_edit", id));
+                    let mut edit = ui.data_mut(|d| d.get_temp::<[String; 8]>(edit_id)).unwrap_or_else(|| saved.clone());
+                    let mut edited = false;
+                    for (field, width) in edit.iter_mut().zip([110.0, 140.0, 140.0, 160.0, 50.0, 80.0, 140.0, 80.0]) {
+                        edited |= ui.add(egui::TextEdit::singleline(field).desired_width(width)).changed();
+                    }
+                    if edited {
+                        ui.data_mut(|d| d.insert_temp(edit_id, edit.clone()));
+                    }
+                    
+                    ui.horizontal(|ui| {
+                        let dirty = edit.iter().map(|f| f.trim()).ne(saved.iter().map(String::as_str));
+                        let edited_host = host_from_fields(&edit);
+                        if ui.add_enabled(dirty && edited_host.is_ok(), egui::Button::new("Save"))
+                            .on_hover_text("Widgets on this host follow it to the new alias and prefix")
+                            .clicked() {
+                            if let Ok(ref edited_host) = edited_host {
+                                action = Some(HostAction::Update(id, edited_host.clone()));
+                            }
+                            ui.data_mut(|d| d.remove::<[String; 8]>(edit_id));
+                        }
+                        if ui.add_enabled(dirty, egui::Button::new("Revert")).clicked() {
+                            ui.data_mut(|d| d.remove::<[String; 8]>(edit_id));
+                        }
+                        
+                        let confirm_id = egui::Id::new(("host_delete_confirm", id));
+                        if ui.data_mut(|d| d.get_temp::<bool>(confirm_id)).unwrap_or(false) {
+                            if ui.button("Confirm Delete").clicked() {
+                                action = Some(HostAction::Delete(id));
+                                ui.data_mut(|d| d.remove::<bool>(confirm_id));
+                            }
+                            if ui.button("Cancel").clicked() {
+                                ui.data_mut(|d| d.remove::<bool>(confirm_id));
+                            }
+                        } else if ui.button("Delete").clicked() {
+                            ui.data_mut(|d| d.insert_temp(confirm_id, true));
+                        }
+                        
+                        crate::host_check::render_test_button(ui, host);
+                        
+                        if let Err(e) = edited_host {
+                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        
+        let Some(action) = action else {
+            return;
+        };
+        // The host as it was before the edit, for widgets to follow it
+        let edited = match action {
+            HostAction::Update(id, _) => self.hosts.iter().find(|host| host.id == Some(id)).cloned(),
+            HostAction::Delete(_) => None,
+        };
+        let rt = crate::runtime::runtime();
+        let result = rt.block_on(async {
+            let db = investigation.open().await?;
+            match action {
+                HostAction::Update(id, ref host) => db.update_host(id, host).await?,
+                HostAction::Delete(id) => db.delete_host(id).await?,
+            }
+            db.list_hosts().await
+        });
+        match result {
+            Ok(hosts) => {
+                self.hosts = hosts;
+                let updated = edited.as_ref().and_then(|from| self.hosts.iter().find(|host| host.id == from.id));
+                for widget in &mut self.widgets {
+                    match (&edited, updated) {
+                        (Some(from), Some(to)) => widget.host_edited(from, to, self.hosts.clone()),
+                        // Widgets whose host is gone fall back to localhost
+                        _ => widget.set_available_hosts(self.hosts.clone()),
+                    }
+                }
+            }
+            Err(e) => eprintln!("ERROR: Failed to update hosts: {}", e),
+        }
+    }
+    
+    // Rename, delete and pick the default template; returns true when the default changed
+    fn render_templates(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(ref main_db) = self.main_db else {
+            return false;
+        };
+        
+        let id = egui::Id::new("template_list");
+        let rt = crate::runtime::runtime();
+        let templates = ui.data_mut(|d| d.get_temp::<Vec<crate::template::Template>>(id)).unwrap_or_else(|| {
+            let templates = rt.block_on(crate::template::Template::load_all(main_db)).unwrap_or_else(|e| {
+                eprintln!("Failed to load templates: {}", e);
+                vec![]
+            });
+            ui.data_mut(|d| d.insert_temp(id, templates.clone()));
+            templates
+        });
+        
+        if templates.is_empty() {
+            ui.label(egui::RichText::new("No templates yet. Use File → Save as template… in a workspace.")
+                .size(11.0)
+                .color(ui.style().visuals.weak_text_color()));
+            return false;
+        }
+        
+        let mut default_changed = false;
+        let mut reload = false;
+        
+        ui.horizontal(|ui| {
+            ui.label("Default for new investigations:");
+            let selected = templates.iter()
+                .find(|t| Some(t.id) == self.settings.default_template)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| "None".to_string());
+            egui::ComboBox::from_id_salt("default_template")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    default_changed |= ui.selectable_value(&mut self.settings.default_template, None, "None").changed();
+                    for template in &templates {
+                        default_changed |= ui.selectable_value(&mut self.settings.default_template, Some(template.id), &template.name).changed();
+                    }
+                });
+        });
+        
+        egui::Grid::new("templates_grid")
+            .num_columns(3)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                for template in &templates {
+                    let name_id = egui::Id::new(("template_name", template.id));
+                    let mut name = ui.data_mut(|d| d.get_temp::<String>(name_id)).unwrap_or_else(|| template.name.clone());
+                    if ui.text_edit_singleline(&mut name).changed() {
+                        ui.data_mut(|d| d.insert_temp(name_id, name.clone()));
+                    }
+                    
+                    let widget_count = template.widgets().map(|w| w.len()).unwrap_or(0);
+                    ui.label(format!("{} widgets, saved {}", widget_count, crate::investigation::Investigation::format_timestamp(template.created_at)));
+                    
+                    ui.horizontal(|ui| {
+                        let renamed = !name.trim().is_empty() && name.trim() != template.name;
+                        if ui.add_enabled(renamed, egui::Button::new("Rename")).clicked() {
+                            match rt.block_on(main_db.rename_template(template.id, name.trim())) {
+                                Ok(()) => {
+                                    ui.data_mut(|d| d.remove::<String>(name_id));
+                                    reload = true;
+                                }
+                                Err(e) => eprintln!("Failed to rename template: {}", e),
+                            }
+                        }
+                        if ui.button("Delete").clicked() {
+                            match rt.block_on(main_db.delete_template(template.id)) {
+                                Ok(()) => {
+                                    if self.settings.default_template == Some(template.id) {
+                                        self.settings.default_template = None;
+                                        default_changed = true;
+                                    }
+                                    reload = true;
+                                }
+                                Err(e) => eprintln!("Failed to delete template: {}", e),
+                            }
+                        }
+                    });
+                    ui.end_row();
                 }
-                ui.add_space(50.0);
-                ui.heading(egui::RichText::new("Settings").size(32.0));
             });
+        
+        if reload {
+            ui.data_mut(|d| d.remove::<Vec<crate::template::Template>>(id));
+        }
+        
+        default_changed
+    }
+    
+    // Schema versions of the main database and each investigation file
+    fn render_diagnostics(&mut self, ui: &mut egui::Ui) {
+        let id = egui::Id::new("schema_diagnostics");
+        let mut report = ui.data_mut(|d| d.get_temp::<Vec<(String, String)>>(id));
+        
+        if ui.button("Refresh").clicked() {
+            report = None;
+        }
+        
+        // Opening every file is slow, so only do it on demand
+        let report = report.unwrap_or_else(|| {
+            let rt = crate::runtime::runtime();
+            let mut report = vec![];
+            
+            let main_status = match self.main_db {
+                Some(ref db) => rt.block_on(db.schema_status()).map(|s| s.describe()).unwrap_or_else(|e| e.to_string()),
+                None => "not open".to_string(),
+            };
+            report.push(("Main database".to_string(), main_status));
+            
+            for investigation in &self.investigations {
+                let status = rt.block_on(crate::database::investigation_db::InvestigationDB::read_schema_status(&investigation.file_path))
+                    .map(|s| s.describe())
+                    .unwrap_or_else(|e| e.to_string());
+                report.push((investigation.name.clone(), status));
+            }
+            
+            ui.data_mut(|d| d.insert_temp(id, report.clone()));
+            report
         });
+        
+        egui::Grid::new("schema_diagnostics_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                for (name, status) in &report {
+                    ui.label(name);
+                    ui.monospace(status);
+                    ui.end_row();
+                }
+            });
+    }
+    
+    // Returns true when a binding was changed
+    fn render_keybindings(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        
+        // Capture the next key combo for the action being remapped
+        if let Some(action) = self.capturing_key_action {
+            let captured = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            // Keys without Ctrl, Cmd or Alt are ignored and capturing carries on
+            match captured {
+                Some((egui::Key::Escape, _)) => self.capturing_key_action = None,
+                Some((key, modifiers)) => {
+                    let shortcut = egui::KeyboardShortcut::new(modifiers, key);
+                    if crate::keybindings::has_modifier(&shortcut) {
+                        self.settings.keybindings.set_shortcut(action, shortcut);
+                        changed = true;
+                        self.capturing_key_action = None;
+                    }
+                }
+                None => {}
+            }
+        }
+        
+        egui::Grid::new("keybindings_grid")
+            .num_columns(3)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                for action in KeyAction::ALL {
+                    ui.label(action.label());
+                    
+                    if self.capturing_key_action == Some(*action) {
+                        ui.label(egui::RichText::new("Press a key combo with Ctrl, Cmd or Alt… (Esc to cancel)").italics());
+                    } else {
+                        let shortcut = self.settings.keybindings.shortcut(*action);
+                        let text = ui.ctx().format_shortcut(&shortcut);
+                        let conflicts = self.settings.keybindings.conflicts(*action);
+                        if conflicts.is_empty() {
+                            ui.monospace(text);
+                        } else {
+                            let names: Vec<&str> = conflicts.iter().map(|a| a.label()).collect();
+                            ui.monospace(egui::RichText::new(text).color(egui::Color32::from_rgb(220, 80, 80)))
+                                .on_hover_text(format!("Conflicts with: {}", names.join(", ")));
+                        }
+                    }
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button("Change").clicked() {
+                            self.capturing_key_action = Some(*action);
+                        }
+                        if ui.button("Reset").clicked() {
+                            self.settings.keybindings.reset(*action);
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        
+        changed
+    }
+}
+
+// Name, alias, prefix, description, port, username, identity file and group as typed in the hosts table
+fn host_from_fields(fields: &[String; 8]) -> Result<Host, String> {
+    let [name, ssh_alias, command_prefix, description, port, username, identity_file, group] = fields;
+    if name.trim().is_empty() {
+        return Err("Name is required".to_string());
     }
-}
\ No newline at end of file
+    Host::validate_ssh_alias(ssh_alias.trim())?;
+    Ok(Host {
+        id: None,
+        name: name.trim().to_string(),
+        ssh_alias: ssh_alias.trim().to_string(),
+        description: description.trim().to_string(),
+        command_prefix: command_prefix.trim().to_string(),
+        port: Host::parse_port(port)?,
+        username: Host::optional_field(username),
+        identity_file: Host::optional_field(identity_file),
+        group: group.trim().to_string(),
+        is_localhost: Host::is_plain_localhost(ssh_alias.trim(), command_prefix),
+    })
+}
@@ -1,37 +1,95 @@
 use eframe::egui;
 use crate::{AppMode, Skop};
 use crate::widgets::{WidgetType, Widget};
-use crate::investigation::{Investigation, COLORS, find_color_name};
+use crate::investigation::{Investigation, COLORS, find_color_name, check_file_rename};
 use crate::database::investigation_db::Host;
 
+// Progress of "Compact database", for the file it was started on
+#[derive(Clone)]
+enum CompactStatus {
+    Running,
+    Done(u64),
+    Failed(String),
+}
+
+static COMPACT_STATUS: std::sync::LazyLock<std::sync::Mutex<Option<(std::path::PathBuf, CompactStatus)>>> = std::sync::LazyLock::new(Default::default);
+
 impl Skop {
+    pub fn leave_workspace(&mut self) {
+        // Stop all widgets before leaving workspace, unless they should keep monitoring
+        for widget in &self.widgets {
+            if self.settings.keep_widgets_running {
+                widget.leave_view();
+            } else {
+                widget.stop();
+            }
+        }
+        
+        // Reload investigations to reflect any changes made in workspace
+        if let Some(ref main_db) = self.main_db {
+            let rt = crate::runtime::runtime();
+            match rt.block_on(Investigation::load_all(main_db)) {
+                Ok(investigations) => {
+                    self.investigations = investigations;
+                    println!("Reloaded {} investigations when returning to home", self.investigations.len());
+                }
+                Err(e) => {
+                    eprintln!("ERROR: Failed to reload investigations: {}", e);
+                }
+            }
+        }
+        
+        self.replay = None;
+        self.mode = AppMode::Home;
+        self.home_quote_index = 0; // Reset to trigger new quote selection
+    }
+    
     pub fn render_investigation_workspace(&mut self, ctx: &egui::Context) {
         // Top menu bar
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 if ui.button("Home").clicked() {
-                    // Stop all widgets before leaving workspace
-                    for widget in &self.widgets {
-                        widget.stop();
+                    self.leave_workspace();
+                }
+                
+                ui.menu_button("File", |ui| {
+                    let mut export_extension = None;
+                    if ui.button("Copy database file (.skop)…").clicked() {
+                        export_extension = Some("skop");
+                    }
+                    if ui.button("Copy database file as SQLite (.sqlite)…").clicked() {
+                        export_extension = Some("sqlite");
                     }
                     
-                    // Reload investigations to reflect any changes made in workspace
-                    if let Some(ref main_db) = self.main_db {
-                        let rt = tokio::runtime::Runtime::new().unwrap();
-                        match rt.block_on(Investigation::load_all(main_db)) {
-                            Ok(investigations) => {
-                                self.investigations = investigations;
-                                println!("Reloaded {} investigations when returning to home", self.investigations.len());
-                            }
-                            Err(e) => {
-                                eprintln!("ERROR: Failed to reload investigations: {}", e);
+                    if ui.button("Clone to new investigation").on_hover_text("Copy the widgets and hosts into a fresh investigation, without recorded data").clicked() {
+                        ui.close_menu();
+                        self.clone_to_new_investigation();
+                    }
+                    
+                    if ui.button("Save as template…").on_hover_text("Save the current widget setup for reuse in other investigations").clicked() {
+                        ui.close_menu();
+                        self.template_name_prompt = self.current_investigation.as_ref().map(|inv| inv.name.clone());
+                    }
+                    
+                    ui.separator();
+                    if ui.button("Redaction rules…").clicked() {
+                        ui.close_menu();
+                        self.open_redaction_editor();
+                    }
+                    if ui.button("Data retention…").clicked() {
+                        ui.close_menu();
+                        self.open_retention_editor();
+                    }
+                    
+                    if let Some(extension) = export_extension {
+                        ui.close_menu();
+                        if let Some(ref investigation) = self.current_investigation {
+                            if let Err(e) = investigation.export_database_file(extension) {
+                                eprintln!("ERROR: Failed to copy database file: {}", e);
                             }
                         }
                     }
-                    
-                    self.mode = AppMode::Home;
-                    self.home_quote_index = 0; // Reset to trigger new quote selection
-                }
+                });
                 
                 ui.menu_button("View", |ui| {
                     if ui.button("Clear All Widgets").clicked() {
@@ -41,6 +99,44 @@ impl Skop {
                         }
                         self.widgets.clear();
                     }
+                    
+                    // Deleted rows leave free pages behind; VACUUM rewrites the file without them
+                    let file_size = self.current_investigation.as_ref()
+                        .and_then(|investigation| std::fs::metadata(&investigation.file_path).ok())
+                        .map(|metadata| crate::widgets::command_widget::format_bytes(metadata.len() as f64));
+                    if let Some(file_size) = file_size {
+                        let status = self.current_investigation.as_ref().and_then(|investigation| {
+                            COMPACT_STATUS.lock().unwrap().clone()
+                                .filter(|(path, _)| *path == investigation.file_path)
+                                .map(|(_, status)| status)
+                        });
+                        let running = matches!(status, Some(CompactStatus::Running));
+                        let label = if running { "Compacting…".to_string() } else { format!("Compact database ({})", file_size) };
+                        if ui.add_enabled(!running, egui::Button::new(label))
+                            .on_hover_text("Reclaim disk space left by pruned output and removed widgets")
+                            .clicked() {
+                            self.compact_investigation();
+                        }
+                        match status {
+                            Some(CompactStatus::Done(reclaimed)) => {
+                                ui.label(egui::RichText::new(format!("Reclaimed {}", crate::widgets::command_widget::format_bytes(reclaimed as f64)))
+                                    .size(11.0)
+                                    .color(ui.style().visuals.weak_text_color()));
+                            }
+                            Some(CompactStatus::Failed(e)) => {
+                                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("Compacting failed: {}", e));
+                            }
+                            _ => {}
+                        }
+                    }
+                    
+                    let replaying = self.replay.is_some();
+                    if ui.add_enabled(!replaying, egui::Button::new("Replay…"))
+                        .on_hover_text("Scrub back through recorded output without running anything")
+                        .clicked() {
+                        ui.close_menu();
+                        self.start_replay(ui.ctx());
+                    }
                 });
                 
                 ui.menu_button("Help", |ui| {
@@ -51,9 +147,15 @@ impl Skop {
             });
         });
         
+        self.render_redaction_editor(ctx);
+        self.render_retention_editor(ctx);
+        self.render_template_prompt(ctx);
+        self.render_config_inspector(ctx);
+        self.render_replay_bar(ctx);
+        
         // Extract data needed for UI to avoid borrowing conflicts
         let investigation_data = self.current_investigation.as_ref().map(|inv| {
-            (inv.name.clone(), inv.description.clone(), inv.color.clone())
+            (inv.name.clone(), inv.description.clone(), inv.color)
         });
         
         // Track if we need to update investigation after UI
@@ -61,6 +163,7 @@ impl Skop {
         let mut new_name = String::new();
         let mut new_description = String::new();
         let mut new_color = [0.0, 0.0, 0.0];
+        let mut should_rename_file = false;
         
         // Left Sidebar - Widget Producer Menu
         egui::SidePanel::left("widget_menu")
@@ -155,11 +258,30 @@ impl Skop {
                             ui.painter().rect_filled(rect, 2.0, color_preview);
                         });
                         
+                        ui.add_space(5.0);
+                        
+                        let mut rename_file = ui.ctx().data_mut(|d| d.get_temp::<bool>(egui::Id::new("edit_inv_rename_file"))).unwrap_or(true);
+                        if ui.checkbox(&mut rename_file, "Rename the file to match").changed() {
+                            ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("edit_inv_rename_file"), rename_file));
+                        }
+                        let rename_check = match (&self.current_investigation, rename_file) {
+                            (Some(investigation), true) => check_file_rename(&edit_name, investigation, &self.investigations),
+                            _ => Ok(()),
+                        };
+                        if let Err(e) = &rename_check {
+                            ui.colored_label(egui::Color32::RED, e);
+                        }
+                        // Why the last save couldn't move the file
+                        if let Some(e) = ui.ctx().data_mut(|d| d.get_temp::<String>(egui::Id::new("edit_inv_rename_error"))) {
+                            ui.colored_label(egui::Color32::RED, e);
+                        }
+                        
                         ui.add_space(10.0);
                         
                         ui.horizontal(|ui| {
-                            if ui.button("Save Changes").clicked() {
+                            if ui.add_enabled(rename_check.is_ok(), egui::Button::new("Save Changes")).clicked() {
                                 should_update_investigation = true;
+                                should_rename_file = rename_file;
                                 new_name = edit_name.clone();
                                 new_description = edit_desc.clone();
                                 new_color = edit_color;
@@ -170,6 +292,8 @@ impl Skop {
                                     d.remove::<String>(egui::Id::new("edit_inv_desc"));
                                     d.remove::<[f32; 3]>(egui::Id::new("edit_inv_color"));
                                     d.remove::<String>(egui::Id::new("edit_inv_color_name"));
+                                    d.remove::<bool>(egui::Id::new("edit_inv_rename_file"));
+                                    d.remove::<String>(egui::Id::new("edit_inv_rename_error"));
                                 });
                             }
                             
@@ -180,6 +304,8 @@ impl Skop {
                                     d.remove::<String>(egui::Id::new("edit_inv_desc"));
                                     d.remove::<[f32; 3]>(egui::Id::new("edit_inv_color"));
                                     d.remove::<String>(egui::Id::new("edit_inv_color_name"));
+                                    d.remove::<bool>(egui::Id::new("edit_inv_rename_file"));
+                                    d.remove::<String>(egui::Id::new("edit_inv_rename_error"));
                                 });
                             }
                         });
@@ -204,8 +330,23 @@ impl Skop {
                                         ui.label("🖥️");
                                     }
                                     ui.label(&host.name);
+                                    if !host.group.is_empty() {
+                                        ui.weak(format!("👥 {}", host.group));
+                                    }
+                                    if !host.is_localhost {
+                                        ui.label(format!("({})", crate::redaction::mask_secrets(&host.ssh_alias)));
+                                    }
+                                    if !host.command_prefix.is_empty() {
+                                        ui.monospace(format!("{} …", host.command_prefix));
+                                    }
                                     if !host.is_localhost {
-                                        ui.label(format!("({})", host.ssh_alias));
+                                        let checking = crate::host_check::status(host) == Some(crate::host_check::ConnectionStatus::Checking);
+                                        if ui.add_enabled(!checking, egui::Button::new("Test").small())
+                                            .on_hover_text("Connect with ssh and run `true`, without prompting for a password")
+                                            .clicked() {
+                                            crate::host_check::start_check(host);
+                                        }
+                                        crate::host_check::render_status(ui, host);
                                     }
                                 });
                             }
@@ -224,6 +365,21 @@ impl Skop {
                         let mut new_host_description = ui.ctx().data_mut(|d| 
                             d.get_temp::<String>(egui::Id::new("new_host_description"))
                         ).unwrap_or_default();
+                        let mut new_command_prefix = ui.ctx().data_mut(|d| 
+                            d.get_temp::<String>(egui::Id::new("new_command_prefix"))
+                        ).unwrap_or_default();
+                        let mut new_ssh_port = ui.ctx().data_mut(|d| 
+                            d.get_temp::<String>(egui::Id::new("new_ssh_port"))
+                        ).unwrap_or_default();
+                        let mut new_ssh_username = ui.ctx().data_mut(|d| 
+                            d.get_temp::<String>(egui::Id::new("new_ssh_username"))
+                        ).unwrap_or_default();
+                        let mut new_identity_file = ui.ctx().data_mut(|d| 
+                            d.get_temp::<String>(egui::Id::new("new_identity_file"))
+                        ).unwrap_or_default();
+                        let mut new_host_group = ui.ctx().data_mut(|d| 
+                            d.get_temp::<String>(egui::Id::new("new_host_group"))
+                        ).unwrap_or_default();
                         
                         ui.label("Display Name:");
                         if ui.text_edit_singleline(&mut new_host_name).changed() {
@@ -235,6 +391,46 @@ impl Skop {
                         if ui.text_edit_singleline(&mut new_ssh_alias).changed() {
                             ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_ssh_alias"), new_ssh_alias.clone()));
                         }
+                        let alias_check = Host::validate_ssh_alias(new_ssh_alias.trim());
+                        if let (Err(e), false) = (&alias_check, new_ssh_alias.is_empty()) {
+                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+                        }
+                        if crate::redaction::contains_secret(&new_ssh_alias) {
+                            ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "⚠ This alias appears to contain a password")
+                                .on_hover_text("Hosts are saved in the investigation file; prefer an entry in ~/.ssh/config with key authentication");
+                        }
+                        
+                        ui.label("Command Prefix (optional):");
+                        ui.small("Put in front of every command, e.g. 'docker exec web' or 'kubectl exec -n prod api --'");
+                        if ui.text_edit_singleline(&mut new_command_prefix).changed() {
+                            ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_command_prefix"), new_command_prefix.clone()));
+                        }
+                        
+                        ui.label("Group (optional):");
+                        ui.small("Hosts with the same group can run a command together, e.g. 'web'");
+                        if ui.text_edit_singleline(&mut new_host_group).changed() {
+                            ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_host_group"), new_host_group.clone()));
+                        }
+                        
+                        ui.collapsing("Connection Options (optional)", |ui| {
+                            ui.small("Leave empty to use ~/.ssh/config");
+                            ui.label("Port:");
+                            if ui.text_edit_singleline(&mut new_ssh_port).changed() {
+                                ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_ssh_port"), new_ssh_port.clone()));
+                            }
+                            ui.label("Username:");
+                            if ui.text_edit_singleline(&mut new_ssh_username).changed() {
+                                ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_ssh_username"), new_ssh_username.clone()));
+                            }
+                            ui.label("Identity File:");
+                            if ui.text_edit_singleline(&mut new_identity_file).changed() {
+                                ui.ctx().data_mut(|d| d.insert_temp(egui::Id::new("new_identity_file"), new_identity_file.clone()));
+                            }
+                        });
+                        let port_check = Host::parse_port(&new_ssh_port);
+                        if let Err(ref e) = port_check {
+                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+                        }
                         
                         ui.label("Description:");
                         if ui.text_edit_multiline(&mut new_host_description).changed() {
@@ -242,42 +438,52 @@ impl Skop {
                         }
                         
                         ui.horizontal(|ui| {
-                            if ui.button("Add Host").clicked() {
-                                if !new_host_name.trim().is_empty() && !new_ssh_alias.trim().is_empty() {
-                                    // Add host to database
-                                    if let Some(ref current_investigation) = self.current_investigation {
-                                        let rt = tokio::runtime::Runtime::new().unwrap();
-                                        match rt.block_on(async {
-                                            let db = current_investigation.open().await?;
-                                            db.add_host(&new_host_name, &new_ssh_alias, &new_host_description).await
-                                        }) {
-                                            Ok(host_id) => {
-                                                println!("Added host '{}' with ID {}", new_host_name, host_id);
-                                                
-                                                // Add to local list
-                                                self.hosts.push(Host {
-                                                    id: Some(host_id),
-                                                    name: new_host_name.clone(),
-                                                    ssh_alias: new_ssh_alias.clone(),
-                                                    description: new_host_description.clone(),
-                                                    is_localhost: new_ssh_alias == "localhost" || new_ssh_alias == "127.0.0.1",
-                                                });
-                                                
-                                                // Update all existing widgets with the new host list
-                                                for widget in &mut self.widgets {
-                                                    widget.set_available_hosts(self.hosts.clone());
-                                                }
-                                                
-                                                // Clear form
-                                                ui.ctx().data_mut(|d| {
-                                                    d.remove::<String>(egui::Id::new("new_host_name"));
-                                                    d.remove::<String>(egui::Id::new("new_ssh_alias"));
-                                                    d.remove::<String>(egui::Id::new("new_host_description"));
-                                                });
-                                            }
-                                            Err(e) => {
-                                                eprintln!("ERROR: Failed to add host: {}", e);
+                            if ui.button("Add Host").clicked() && !new_host_name.trim().is_empty() && alias_check.is_ok() {
+                                // Add host to database
+                                if let (Some(ref current_investigation), Ok(port)) = (&self.current_investigation, port_check) {
+                                    let mut host = Host {
+                                        id: None,
+                                        name: new_host_name.clone(),
+                                        ssh_alias: new_ssh_alias.trim().to_string(),
+                                        description: new_host_description.clone(),
+                                        command_prefix: new_command_prefix.trim().to_string(),
+                                        port,
+                                        username: Host::optional_field(&new_ssh_username),
+                                        identity_file: Host::optional_field(&new_identity_file),
+                                        group: new_host_group.trim().to_string(),
+                                        is_localhost: Host::is_plain_localhost(new_ssh_alias.trim(), &new_command_prefix),
+                                    };
+                                    let rt = crate::runtime::runtime();
+                                    match rt.block_on(async {
+                                        let db = current_investigation.open().await?;
+                                        db.add_host(&host).await
+                                    }) {
+                                        Ok(host_id) => {
+                                            println!("Added host '{}' with ID {}", new_host_name, host_id);
+                                            
+                                            // Add to local list
+                                            host.id = Some(host_id);
+                                            self.hosts.push(host);
+                                            
+                                            // Update all existing widgets with the new host list
+                                            for widget in &mut self.widgets {
+                                                widget.set_available_hosts(self.hosts.clone());
                                             }
+                                            
+                                            // Clear form
+                                            ui.ctx().data_mut(|d| {
+                                                d.remove::<String>(egui::Id::new("new_host_name"));
+                                                d.remove::<String>(egui::Id::new("new_ssh_alias"));
+                                                d.remove::<String>(egui::Id::new("new_host_description"));
+                                                d.remove::<String>(egui::Id::new("new_command_prefix"));
+                                                d.remove::<String>(egui::Id::new("new_ssh_port"));
+                                                d.remove::<String>(egui::Id::new("new_ssh_username"));
+                                                d.remove::<String>(egui::Id::new("new_identity_file"));
+                                                d.remove::<String>(egui::Id::new("new_host_group"));
+                                            });
+                                        }
+                                        Err(e) => {
+                                            eprintln!("ERROR: Failed to add host: {}", e);
                                         }
                                     }
                                 }
@@ -288,6 +494,11 @@ impl Skop {
                                     d.remove::<String>(egui::Id::new("new_host_name"));
                                     d.remove::<String>(egui::Id::new("new_ssh_alias"));
                                     d.remove::<String>(egui::Id::new("new_host_description"));
+                                    d.remove::<String>(egui::Id::new("new_command_prefix"));
+                                    d.remove::<String>(egui::Id::new("new_ssh_port"));
+                                    d.remove::<String>(egui::Id::new("new_ssh_username"));
+                                    d.remove::<String>(egui::Id::new("new_identity_file"));
+                                    d.remove::<String>(egui::Id::new("new_host_group"));
                                 });
                             }
                         });
@@ -298,18 +509,42 @@ impl Skop {
                 
                 ui.label("System Monitoring:");
                 ui.vertical(|ui| {
+                    if ui.button("System Summary").clicked() {
+                        self.add_widget(WidgetType::new_summary(self.next_widget_id));
+                    }
                     if ui.button("CPU Monitor").clicked() {
                         self.add_widget(WidgetType::new_cpu_monitor(self.next_widget_id));
                     }
                     if ui.button("Process Monitor").clicked() {
                         self.add_widget(WidgetType::new_process_monitor(self.next_widget_id));
                     }
+                    if ui.button("Memory Monitor").clicked() {
+                        self.add_widget(WidgetType::new_memory_monitor(self.next_widget_id));
+                    }
+                    if ui.button("Disk Monitor").clicked() {
+                        self.add_widget(WidgetType::new_disk_monitor(self.next_widget_id));
+                    }
                     if ui.button("Network Monitor").clicked() {
                         self.add_widget(WidgetType::new_network_monitor(self.next_widget_id));
                     }
                     if ui.button("System Info").clicked() {
                         self.add_widget(WidgetType::new_system_info(self.next_widget_id));
                     }
+                    if ui.button("System Log").clicked() {
+                        self.add_widget(WidgetType::new_journal(self.next_widget_id));
+                    }
+                });
+                
+                ui.separator();
+                
+                ui.label("Containers:");
+                ui.vertical(|ui| {
+                    if ui.button("Docker").clicked() {
+                        self.add_widget(WidgetType::new_docker(self.next_widget_id));
+                    }
+                    if ui.button("Kubernetes").clicked() {
+                        self.add_widget(WidgetType::new_kube(self.next_widget_id));
+                    }
                 });
                 
                 ui.separator();
@@ -319,6 +554,18 @@ impl Skop {
                     if ui.button("Command").clicked() {
                         self.add_widget(WidgetType::new_raw_command(self.next_widget_id));
                     }
+                    if ui.button("Chart").on_hover_text("Plot a number picked out of a command's output").clicked() {
+                        self.add_widget(WidgetType::new_chart(self.next_widget_id));
+                    }
+                });
+                
+                ui.separator();
+                
+                ui.label("Analysis:");
+                ui.vertical(|ui| {
+                    if ui.button("SQL Query").clicked() {
+                        self.add_widget(WidgetType::new_sql_query(self.next_widget_id));
+                    }
                 });
                 
                 ui.separator();
@@ -333,6 +580,38 @@ impl Skop {
                 ui.separator();
                 
                 ui.label(format!("Active Widgets: {}", self.widgets.len()));
+                
+                // What each widget's polling costs, to spot an overly aggressive monitor
+                ui.collapsing("Widget stats", |ui| {
+                    let mut any = false;
+                    for widget in &self.widgets {
+                        let Some(usage) = widget.resource_usage() else {
+                            continue;
+                        };
+                        any = true;
+                        ui.label(egui::RichText::new(format!("{} #{}", widget.widget_type_name(), widget.widget_id())).strong());
+                        ui.small(format!("{} runs, avg {:.2}s, {:.1}s total", usage.runs, usage.average_run().as_secs_f64(), usage.busy.as_secs_f64()));
+                        ui.small(format!("{} lines, {} received, {} in memory",
+                            usage.lines,
+                            crate::widgets::command_widget::format_bytes(usage.bytes as f64),
+                            crate::widgets::command_widget::format_bytes(usage.buffered_bytes as f64)));
+                    }
+                    if !any {
+                        ui.small("No command widgets open");
+                    }
+                });
+                
+                // The exact JSON each widget is saved as, for bug reports and hand edits
+                ui.collapsing("Inspect config", |ui| {
+                    if self.widgets.is_empty() {
+                        ui.small("No widgets open");
+                    }
+                    for widget in &self.widgets {
+                        if ui.button(format!("{} #{}", widget.widget_type_name(), widget.widget_id())).clicked() {
+                            self.inspected_widget = Some(widget.widget_id());
+                        }
+                    }
+                });
             });
         
         // Handle investigation updates after UI to avoid borrowing conflicts
@@ -343,7 +622,7 @@ impl Skop {
                 investigation.color = new_color;
                 
                 // Update investigation metadata in database
-                let rt = tokio::runtime::Runtime::new().unwrap();
+                let rt = crate::runtime::runtime();
                 match rt.block_on(investigation.update_metadata()) {
                     Ok(()) => {
                         println!("Successfully updated investigation metadata");
@@ -368,6 +647,27 @@ impl Skop {
                     }
                 }
             }
+            
+            if should_rename_file {
+                if let Err(e) = self.rename_investigation_file() {
+                    eprintln!("ERROR: Failed to rename investigation file: {}", e);
+                    ctx.data_mut(|d| d.insert_temp(egui::Id::new("edit_inv_rename_error"), format!("The file wasn't renamed: {}", e)));
+                }
+            }
+        }
+        
+        // Saved layouts go to egui on the first frame after loading
+        if std::mem::take(&mut self.restore_layouts) {
+            for (widget_id, layout) in &self.widget_layouts {
+                crate::widgets::restore_layout(ctx, *widget_id, *layout);
+            }
+        }
+        
+        // Replayed widgets stand in for the live ones, which keep running unseen
+        if self.replay.is_some() {
+            self.render_replay_widgets(ctx);
+            self.render_background(ctx);
+            return;
         }
         
         // Render all widgets
@@ -385,30 +685,498 @@ impl Skop {
             }
         }
         
+        // Save moved or resized windows once the drag is over
+        if !ctx.input(|i| i.pointer.any_down()) {
+            self.save_changed_layouts(ctx);
+        }
+        
         // Remove closed widgets
         for idx in widgets_to_remove.iter().rev() {
-            let widget = &self.widgets[*idx];
-            
-            // Stop widget activities before removal
-            widget.stop();
-            
-            // Archive widget in database if we have an active investigation
-            if let Some(ref current_investigation) = self.current_investigation {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                if let Err(e) = rt.block_on(async {
-                    let db = current_investigation.open().await?;
-                    db.archive_widget_instance(widget).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
-                }) {
-                    eprintln!("Failed to archive widget in database: {}", e);
-                }
+            self.close_widget(*idx);
+        }
+        
+        self.render_background(ctx);
+    }
+    
+    // Stop a widget and archive it, as when its window is closed
+    pub fn close_widget(&mut self, idx: usize) {
+        let widget = &self.widgets[idx];
+        
+        // Stop widget activities before removal
+        widget.stop();
+        
+        // Archive widget in database if we have an active investigation
+        if let Some(ref current_investigation) = self.current_investigation {
+            let rt = crate::runtime::runtime();
+            if let Err(e) = rt.block_on(async {
+                let db = current_investigation.open().await?;
+                db.archive_widget_instance(widget).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+            }) {
+                eprintln!("Failed to archive widget in database: {}", e);
             }
-            
-            self.widgets.remove(*idx);
         }
         
+        self.widgets.remove(idx);
+    }
+    
+    fn render_background(&self, ctx: &egui::Context) {
         // Central panel (background)
-        egui::CentralPanel::default().show(ctx, |_ui| {
+        egui::CentralPanel::default().show(ctx, |ui| {
             // Empty central panel - widgets float on top
+            let empty_hint = match self.replay {
+                Some(ref replay) if replay.widgets.is_empty() => Some("No widgets were open at this moment"),
+                Some(_) => None,
+                None if self.widgets.is_empty() => Some("← Add a widget from the sidebar to start collecting data"),
+                None => None,
+            };
+            if let Some(hint) = empty_hint {
+                ui.centered_and_justified(|ui| {
+                    ui.label(egui::RichText::new(hint)
+                        .size(18.0)
+                        .color(ui.style().visuals.weak_text_color()));
+                });
+            }
+        });
+    }
+    
+    // Moves the open investigation's file to match its name. Its widgets are closed and their
+    // recorders flushed first so nothing writes to the old path, then they're reloaded from
+    // wherever the file ends up
+    fn rename_investigation_file(&mut self) -> Result<(), String> {
+        let Some(mut investigation) = self.current_investigation.clone() else {
+            return Ok(());
+        };
+        if self.main_db.is_none() || investigation.renamed_file_path().is_none() {
+            return Ok(());
+        }
+        
+        let investigation_db = self.investigation_db.clone();
+        self.close_workspace_widgets();
+        
+        let rt = crate::runtime::runtime();
+        let result = rt.block_on(async {
+            if let Some(db) = investigation_db {
+                crate::database::investigation_db::InvestigationDB::release(db).await?;
+            }
+            match self.main_db {
+                Some(ref main_db) => investigation.rename_file(main_db).await.map_err(|e| e.to_string()),
+                None => Ok(()),
+            }
+        });
+        if result.is_ok() {
+            println!("Renamed investigation file to {}", investigation.file_path.display());
+        }
+        
+        if let Err(e) = rt.block_on(self.load_widgets_from_db(&investigation)) {
+            eprintln!("Failed to load widgets: {}", e);
+        }
+        
+        if let Some(listed) = self.investigations.iter_mut().find(|listed| listed.id == investigation.id) {
+            *listed = investigation.clone();
+        }
+        self.current_investigation = Some(investigation);
+        result
+    }
+    
+    // Fork the open workspace into a new investigation and switch to it
+    fn clone_to_new_investigation(&mut self) {
+        let (Some(source), Some(main_db)) = (self.current_investigation.clone(), self.main_db.as_ref()) else {
+            return;
+        };
+        
+        let rt = crate::runtime::runtime();
+        let fork = match rt.block_on(source.fork(&self.widgets, main_db)) {
+            Ok(fork) => fork,
+            Err(e) => {
+                eprintln!("ERROR: Failed to clone investigation: {}", e);
+                return;
+            }
+        };
+        println!("Cloned '{}' into new investigation '{}'", source.name, fork.name);
+        
+        self.close_workspace_widgets();
+        if let Err(e) = rt.block_on(self.load_widgets_from_db(&fork)) {
+            eprintln!("Failed to load widgets: {}", e);
+        }
+        
+        self.investigations.push(fork.clone());
+        self.current_investigation = Some(fork);
+    }
+    
+    fn render_template_prompt(&mut self, ctx: &egui::Context) {
+        let Some(ref mut name) = self.template_name_prompt else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        
+        egui::Window::new("Save as Template")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Saves the configuration of {} widgets, without recorded data.", self.widgets.len()));
+                ui.label("Template name:");
+                let response = ui.text_edit_singleline(name);
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.small("Saving under an existing name replaces that template");
+                
+                ui.horizontal(|ui| {
+                    let valid = !name.trim().is_empty();
+                    if ui.add_enabled(valid, egui::Button::new("Save")).clicked() || (valid && submitted) {
+                        save = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if save {
+            if let Some(ref main_db) = self.main_db {
+                let rt = crate::runtime::runtime();
+                match rt.block_on(crate::template::Template::save(name.trim(), &self.widgets, main_db)) {
+                    Ok(()) => {
+                        println!("Saved template '{}'", name.trim());
+                        // Refresh the list shown in settings
+                        ctx.data_mut(|d| d.remove::<Vec<crate::template::Template>>(egui::Id::new("template_list")));
+                    }
+                    Err(e) => eprintln!("ERROR: Failed to save template: {}", e),
+                }
+            }
+        }
+        
+        if save || cancel || !open {
+            self.template_name_prompt = None;
+        }
+    }
+    
+    fn save_changed_layouts(&mut self, ctx: &egui::Context) {
+        let changed: Vec<(usize, crate::widgets::WidgetLayout)> = self.widgets.iter()
+            .filter_map(|widget| {
+                let layout = crate::widgets::current_layout(ctx, widget.widget_id())?;
+                (self.widget_layouts.get(&widget.widget_id()) != Some(&layout)).then_some((widget.widget_id(), layout))
+            })
+            .collect();
+        // Written through the pool the widgets already share, off the UI thread
+        let Some(db) = self.investigation_db.clone() else {
+            return;
+        };
+        if changed.is_empty() {
+            return;
+        }
+        
+        // Marked saved even if the write fails, so a broken file isn't retried every frame
+        self.widget_layouts.extend(changed.iter().copied());
+        crate::runtime::runtime().spawn(async move {
+            for (widget_id, layout) in changed {
+                if let Err(e) = db.save_widget_layout(widget_id as i32, layout).await {
+                    eprintln!("Failed to save layout of widget {}: {}", widget_id, e);
+                }
+            }
+        });
+    }
+    
+    fn render_config_inspector(&mut self, ctx: &egui::Context) {
+        let Some(widget_id) = self.inspected_widget else {
+            return;
+        };
+        let Some(widget) = self.widgets.iter().find(|w| w.widget_id() == widget_id) else {
+            // Closed while being inspected
+            self.inspected_widget = None;
+            return;
+        };
+        
+        // Same serialization as the config_json column; skipped runtime state isn't in it
+        let json = match serde_json::to_string(widget) {
+            Ok(json) => json,
+            Err(e) => format!("Failed to serialize widget config: {}", e),
+        };
+        let pretty_id = egui::Id::new("inspect_config_pretty");
+        let mut pretty = ctx.data(|d| d.get_temp::<bool>(pretty_id)).unwrap_or(true);
+        let mut open = true;
+        
+        egui::Window::new(format!("Config: {} #{}", widget.widget_type_name(), widget_id))
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                let shown = if pretty {
+                    serde_json::from_str::<serde_json::Value>(&json)
+                        .and_then(|value| serde_json::to_string_pretty(&value))
+                        .unwrap_or_else(|_| json.clone())
+                } else {
+                    json.clone()
+                };
+                
+                ui.horizontal(|ui| {
+                    ui.label(format!("Version {} · {} bytes saved", widget.widget_version(), json.len()));
+                    ui.checkbox(&mut pretty, "Pretty");
+                    if ui.button("Copy").on_hover_text("Copy the text as shown").clicked() {
+                        ctx.copy_text(shown.clone());
+                    }
+                });
+                ui.separator();
+                
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(&mut shown.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY));
+                    });
+            });
+        
+        ctx.data_mut(|d| d.insert_temp(pretty_id, pretty));
+        if !open {
+            self.inspected_widget = None;
+        }
+    }
+    
+    fn open_redaction_editor(&mut self) {
+        let Some(ref investigation) = self.current_investigation else {
+            return;
+        };
+        let rt = crate::runtime::runtime();
+        match rt.block_on(async {
+            let db = investigation.open().await?;
+            crate::redaction::RedactionSettings::load(&db).await
+        }) {
+            Ok(settings) => self.redaction_editor = Some(settings),
+            Err(e) => eprintln!("ERROR: Failed to load redaction rules: {}", e),
+        }
+    }
+    
+    fn render_redaction_editor(&mut self, ctx: &egui::Context) {
+        let Some(ref mut settings) = self.redaction_editor else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        let mut any_invalid = false;
+        
+        egui::Window::new("Redaction Rules")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label("Matching text is replaced before output is recorded to this investigation.");
+                ui.add_space(5.0);
+                
+                ui.label(egui::RichText::new("Built-in patterns").strong());
+                for builtin in crate::redaction::BuiltinPattern::ALL {
+                    let mut enabled = settings.builtins.contains(builtin);
+                    if ui.checkbox(&mut enabled, builtin.label()).changed() {
+                        if enabled {
+                            settings.builtins.push(*builtin);
+                        } else {
+                            settings.builtins.retain(|b| b != builtin);
+                        }
+                    }
+                }
+                
+                ui.add_space(5.0);
+                ui.label(egui::RichText::new("Custom rules").strong());
+                
+                let mut rule_to_remove = None;
+                egui::Grid::new("redaction_rules")
+                    .num_columns(3)
+                    .spacing([10.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Regex");
+                        ui.label("Replacement");
+                        ui.end_row();
+                        
+                        for (i, rule) in settings.rules.iter_mut().enumerate() {
+                            ui.add(egui::TextEdit::singleline(&mut rule.pattern)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(240.0));
+                            ui.add(egui::TextEdit::singleline(&mut rule.replacement)
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(140.0));
+                            if ui.button("🗑").on_hover_text("Remove rule").clicked() {
+                                rule_to_remove = Some(i);
+                            }
+                            ui.end_row();
+                            
+                            if let Err(e) = crate::redaction::validate_pattern(&rule.pattern) {
+                                any_invalid = true;
+                                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+                                ui.end_row();
+                            }
+                        }
+                    });
+                
+                if let Some(i) = rule_to_remove {
+                    settings.rules.remove(i);
+                }
+                
+                if ui.button("➕ Add rule").clicked() {
+                    settings.rules.push(crate::redaction::RedactionRule {
+                        pattern: String::new(),
+                        replacement: "<redacted>".to_string(),
+                    });
+                }
+                
+                ui.add_space(5.0);
+                ui.checkbox(&mut settings.redact_live_view, "Also redact output shown in widgets")
+                    .on_hover_text("When off, widgets show the raw output and only recorded data is redacted");
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!any_invalid, egui::Button::new("Save")).clicked() {
+                        save = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if save {
+            if let Some(ref investigation) = self.current_investigation {
+                let rt = crate::runtime::runtime();
+                match rt.block_on(async {
+                    let db = investigation.open().await?;
+                    settings.save(&db).await
+                }) {
+                    Ok(()) => crate::redaction::set_settings(&investigation.file_path, settings),
+                    Err(e) => eprintln!("ERROR: Failed to save redaction rules: {}", e),
+                }
+            }
+        }
+        
+        if save || cancel || !open {
+            self.redaction_editor = None;
+        }
+    }
+    
+    // VACUUM can take a while on a big file, so it runs in the background on the widgets' pool
+    fn compact_investigation(&mut self) {
+        let Some(investigation) = self.current_investigation.clone() else {
+            return;
+        };
+        let shared_db = self.investigation_db.clone();
+        *COMPACT_STATUS.lock().unwrap() = Some((investigation.file_path.clone(), CompactStatus::Running));
+        
+        crate::runtime::runtime().spawn(async move {
+            let result = match shared_db {
+                Some(db) => db.compact().await,
+                None => match investigation.open().await {
+                    Ok(db) => db.compact().await,
+                    Err(e) => Err(e),
+                },
+            };
+            let status = match result {
+                Ok(reclaimed) => CompactStatus::Done(reclaimed),
+                Err(e) => {
+                    eprintln!("ERROR: Failed to compact investigation: {}", e);
+                    CompactStatus::Failed(e.to_string())
+                }
+            };
+            *COMPACT_STATUS.lock().unwrap() = Some((investigation.file_path, status));
+            crate::runtime::request_repaint();
         });
     }
-}
\ No newline at end of file
+    
+    fn open_retention_editor(&mut self) {
+        let Some(ref investigation) = self.current_investigation else {
+            return;
+        };
+        let rt = crate::runtime::runtime();
+        match rt.block_on(async {
+            let db = investigation.open().await?;
+            crate::retention::RetentionPolicy::load(&db).await
+        }) {
+            Ok(policy) => self.retention_editor = Some(policy),
+            Err(e) => eprintln!("ERROR: Failed to load retention policy: {}", e),
+        }
+    }
+    
+    fn render_retention_editor(&mut self, ctx: &egui::Context) {
+        let Some(ref mut policy) = self.retention_editor else {
+            return;
+        };
+        
+        let result_id = egui::Id::new("retention_prune_result");
+        let mut open = true;
+        let mut save = false;
+        let mut prune = false;
+        let mut cancel = false;
+        
+        egui::Window::new("Data Retention")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Recorded output outside these limits is deleted when the investigation is opened.");
+                ui.add_space(5.0);
+                
+                let mut limit_age = policy.max_age_days.is_some();
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut limit_age, "Delete output older than");
+                    let mut days = policy.max_age_days.unwrap_or(30);
+                    ui.add_enabled(limit_age, egui::DragValue::new(&mut days).range(1..=3650).suffix(" days"));
+                    policy.max_age_days = limit_age.then_some(days);
+                });
+                
+                let mut limit_lines = policy.max_lines_per_widget.is_some();
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut limit_lines, "Keep at most");
+                    let mut lines = policy.max_lines_per_widget.unwrap_or(100_000);
+                    ui.add_enabled(limit_lines, egui::DragValue::new(&mut lines).range(100..=u32::MAX).speed(100).suffix(" lines per widget"));
+                    policy.max_lines_per_widget = limit_lines.then_some(lines);
+                });
+                
+                if policy.is_unlimited() {
+                    ui.small("No limits set: everything is kept");
+                }
+                if let Some(result) = ui.ctx().data(|d| d.get_temp::<String>(result_id)) {
+                    ui.small(result);
+                }
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        save = true;
+                    }
+                    if ui.add_enabled(!policy.is_unlimited(), egui::Button::new("Prune now"))
+                        .on_hover_text("Save these limits and delete what falls outside them")
+                        .clicked() {
+                        prune = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if save || prune {
+            if let Some(ref investigation) = self.current_investigation {
+                let rt = crate::runtime::runtime();
+                match rt.block_on(async {
+                    let db = investigation.open().await?;
+                    policy.save(&db).await?;
+                    if prune {
+                        policy.apply(&db).await
+                    } else {
+                        Ok(0)
+                    }
+                }) {
+                    Ok(removed) if prune => ctx.data_mut(|d| d.insert_temp(result_id, format!("Deleted {} recorded lines", removed))),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("ERROR: Failed to apply retention policy: {}", e),
+                }
+            }
+        }
+        
+        if save || cancel || !open {
+            self.retention_editor = None;
+            ctx.data_mut(|d| d.remove::<String>(result_id));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use eframe::egui;
+use crate::widgets::WidgetWindow;
 use serde::{Serialize, Deserialize};
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -28,14 +29,10 @@ impl crate::widgets::Widget for AboutWidget {
         // About widget doesn't capture data
     }
     
-    fn restore_widget_data(&mut self, _data: Vec<String>) {
+    fn restore_widget_data(&mut self, _data: Vec<crate::widgets::command_widget::TimedLine>) {
         // About widget doesn't have data to restore
     }
     
-    fn set_available_hosts(&mut self, _hosts: Vec<crate::database::investigation_db::Host>) {
-        // About widget doesn't use hosts
-    }
-    
     fn needs_restart(&self) -> bool {
         // About widget doesn't execute commands, never needs restart
         false
@@ -50,7 +47,7 @@ impl crate::widgets::Widget for AboutWidget {
             .default_pos([400.0 + (idx as f32 * 30.0), 200.0 + (idx as f32 * 30.0)])
             .default_size([600.0, 500.0])
             .resizable(true)
-            .show(ctx, |ui| {
+            .show_for_widget(ctx, self.id, |ui| {
                 ui.heading("Skop - System Knowledge Operations Platform");
                 ui.separator();
                 
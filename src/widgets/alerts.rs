@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+use eframe::egui;
+use serde::{Serialize, Deserialize};
+
+// Notify when a value a widget already computes stays above a limit; off until enabled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdAlert {
+    pub enabled: bool,
+    pub above: f64,
+    pub for_secs: u64,  // How long the value has to stay above the limit
+    pub cooldown_secs: u64,  // Quiet time after a notification, however long the condition lasts
+}
+
+impl ThresholdAlert {
+    pub fn new(above: f64, for_secs: u64) -> Self {
+        Self { enabled: false, above, for_secs, cooldown_secs: 300 }
+    }
+}
+
+// An alert and where it stands, fed by the parser that sees the samples and edited from the widget's settings
+#[derive(Debug, Clone)]
+pub struct AlertWatch {
+    pub alert: ThresholdAlert,
+    above_since: Option<Instant>,
+    last_notified: Option<Instant>,
+}
+
+impl AlertWatch {
+    pub fn new(alert: ThresholdAlert) -> Self {
+        Self { alert, above_since: None, last_notified: None }
+    }
+    
+    // New settings start the count again
+    pub fn set_alert(&mut self, alert: ThresholdAlert) {
+        if self.alert != alert {
+            self.alert = alert;
+            self.above_since = None;
+        }
+    }
+    
+    // True when this sample should be notified about
+    pub fn observe(&mut self, value: f64, now: Instant) -> bool {
+        if !self.alert.enabled || value <= self.alert.above {
+            self.above_since = None;
+            return false;
+        }
+        let since = *self.above_since.get_or_insert(now);
+        let sustained = now.duration_since(since) >= Duration::from_secs(self.alert.for_secs);
+        let quiet = self.last_notified.is_none_or(|last| now.duration_since(last) >= Duration::from_secs(self.alert.cooldown_secs));
+        if sustained && quiet {
+            self.last_notified = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+impl Default for AlertWatch {
+    fn default() -> Self {
+        Self::new(ThresholdAlert::new(0.0, 0))
+    }
+}
+
+// Desktop notification through the OS's own tool; where there's none, it's only logged
+pub fn notify(title: &str, body: &str) {
+    println!("{}: {}", title, body);
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut command = std::process::Command::new("osascript");
+        command.arg("-e").arg(format!("display notification \"{}\" with title \"{}\"", quote(body), quote(title)));
+        command
+    } else if cfg!(unix) {
+        let mut command = std::process::Command::new("notify-send");
+        command.arg("--app-name=skop").arg(title).arg(body);
+        command
+    } else {
+        return;
+    };
+    crate::runtime::runtime().spawn_blocking(move || {
+        if let Err(e) = command.status() {
+            eprintln!("Failed to show notification: {}", e);
+        }
+    });
+}
+
+// "Notify when <what> is above [x] <unit> for [n]s" with its cooldown; true when changed
+pub fn render_alert_settings(ui: &mut egui::Ui, alert: &mut ThresholdAlert, what: &str, unit: &str, max: f64) -> bool {
+    let before = alert.clone();
+    ui.checkbox(&mut alert.enabled, format!("Notify when {} is above", what));
+    ui.add_enabled_ui(alert.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut alert.above).range(0.0..=max).suffix(unit));
+            ui.label("for");
+            ui.add(egui::DragValue::new(&mut alert.for_secs).range(0..=3600).suffix("s"));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Then wait");
+            ui.add(egui::DragValue::new(&mut alert.cooldown_secs).range(10..=86_400).suffix("s"));
+            ui.label("before notifying again");
+        });
+    });
+    *alert != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn alerts_fire_once_sustained_and_then_wait_out_the_cooldown() {
+        let alert = ThresholdAlert { enabled: true, above: 90.0, for_secs: 10, cooldown_secs: 60 };
+        let mut watch = AlertWatch::new(alert);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        
+        assert!(!watch.observe(95.0, at(0)));
+        assert!(!watch.observe(95.0, at(5)));
+        assert!(watch.observe(95.0, at(10)));
+        // Still above, but within the cooldown
+        assert!(!watch.observe(95.0, at(30)));
+        // Dropping below starts the sustain time again
+        assert!(!watch.observe(50.0, at(40)));
+        assert!(!watch.observe(95.0, at(75)));
+        assert!(watch.observe(95.0, at(85)));
+        
+        watch.set_alert(ThresholdAlert { enabled: false, ..watch.alert.clone() });
+        assert!(!watch.observe(95.0, at(500)));
+    }
+}
@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use eframe::egui::Color32;
+
+// What SGR codes ("\x1b[1;31m") set on the text after them
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnsiStyle {
+    pub foreground: Option<Color32>,
+    pub background: Option<Color32>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+// The 16 basic colors, normal then bright
+const PALETTE: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 49, 49),
+    Color32::from_rgb(13, 188, 121),
+    Color32::from_rgb(229, 229, 16),
+    Color32::from_rgb(36, 114, 200),
+    Color32::from_rgb(188, 63, 188),
+    Color32::from_rgb(17, 168, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(102, 102, 102),
+    Color32::from_rgb(241, 76, 76),
+    Color32::from_rgb(35, 209, 139),
+    Color32::from_rgb(245, 245, 67),
+    Color32::from_rgb(59, 142, 234),
+    Color32::from_rgb(214, 112, 214),
+    Color32::from_rgb(41, 184, 219),
+    Color32::from_rgb(255, 255, 255),
+];
+
+// xterm's 256 colors: the basic 16, a 6x6x6 cube, then 24 grays
+fn color_256(index: u8) -> Color32 {
+    match index {
+        0..=15 => PALETTE[index as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let cube = index - 16;
+            Color32::from_rgb(level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Color32::from_rgb(gray, gray, gray)
+        }
+    }
+}
+
+// "38;5;n" or "38;2;r;g;b" after the 38/48 itself; None leaves the rest of the codes unread
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<Color32> {
+    match params.next()? {
+        5 => Some(color_256(params.next()?.min(255) as u8)),
+        2 => {
+            let mut channel = || params.next().map(|value| value.min(255) as u8);
+            Some(Color32::from_rgb(channel()?, channel()?, channel()?))
+        }
+        _ => None,
+    }
+}
+
+impl AnsiStyle {
+    fn apply_sgr(&mut self, params: &str) {
+        // A bare "\x1b[m" is a reset too
+        let mut codes = params.split([';', ':']).map(|code| code.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = AnsiStyle::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(PALETTE[(code - 30) as usize]),
+                90..=97 => self.foreground = Some(PALETTE[(code - 90 + 8) as usize]),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(PALETTE[(code - 40) as usize]),
+                100..=107 => self.background = Some(PALETTE[(code - 100 + 8) as usize]),
+                49 => self.background = None,
+                38 => match extended_color(&mut codes) {
+                    Some(color) => self.foreground = Some(color),
+                    None => return,
+                },
+                48 => match extended_color(&mut codes) {
+                    Some(color) => self.background = Some(color),
+                    None => return,
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+// The line without escape sequences, and the styled runs of it by byte range. Only SGR codes
+// affect the text; cursor movement, screen clearing and window titles are dropped
+pub fn parse(line: &str) -> (String, Vec<(Range<usize>, AnsiStyle)>) {
+    let mut text = String::with_capacity(line.len());
+    let mut runs: Vec<(Range<usize>, AnsiStyle)> = Vec::new();
+    let mut style = AnsiStyle::default();
+    let mut chars = line.chars().peekable();
+    
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters, then a final byte from @ to ~
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            if c == 'm' {
+                                style.apply_sgr(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // OSC: up to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Anything else is ESC and one more character
+                _ => {}
+            },
+            '\x07' => {}
+            c => {
+                let start = text.len();
+                text.push(c);
+                if style == AnsiStyle::default() {
+                    continue;
+                }
+                match runs.last_mut() {
+                    Some((range, last)) if range.end == start && *last == style => range.end = text.len(),
+                    _ => runs.push((start..text.len(), style)),
+                }
+            }
+        }
+    }
+    (text, runs)
+}
+
+// For filtering and searching what's shown rather than the bytes behind it
+pub fn strip(line: &str) -> Cow<'_, str> {
+    if line.contains('\x1b') {
+        Cow::Owned(parse(line).0)
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+// Escape characters spelled out, for looking at the sequences themselves
+pub fn show_escapes(line: &str) -> Cow<'_, str> {
+    if line.contains('\x1b') {
+        Cow::Owned(line.replace('\x1b', "\\x1b"))
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sgr_codes_become_styled_runs_and_other_sequences_are_dropped() {
+        let (text, runs) = parse("\x1b[2K\x1b[1;31merror\x1b[0m: \x1b[38;5;46mok\x1b[39m \x1b[48;2;10;20;30mbg\x1b[m\x1b]0;title\x07!");
+        assert_eq!(text, "error: ok bg!");
+        
+        let red = AnsiStyle { foreground: Some(PALETTE[1]), bold: true, ..Default::default() };
+        let green = AnsiStyle { foreground: Some(Color32::from_rgb(0, 255, 0)), ..Default::default() };
+        let background = AnsiStyle { background: Some(Color32::from_rgb(10, 20, 30)), ..Default::default() };
+        assert_eq!(runs, vec![(0..5, red), (7..9, green), (10..12, background)]);
+        
+        assert_eq!(strip("plain"), "plain");
+        assert_eq!(show_escapes("\x1b[32mok"), "\\x1b[32mok");
+    }
+}
@@ -0,0 +1,26 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use kira::{AudioManager, AudioManagerSettings};
+use kira::backend::cpal::{CpalBackend, CpalBackendSettings};
+
+// Output devices of the default audio host, by name; Err when the host can't list them
+pub fn output_device_names() -> Result<Vec<String>, String> {
+    let devices = cpal::default_host().output_devices().map_err(|e| e.to_string())?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+// An audio manager playing to the named device, or the system default for None.
+// A device that's gone is an error rather than a quiet switch to the default
+pub fn open_audio_manager(device_name: Option<&str>) -> Result<AudioManager<CpalBackend>, String> {
+    let device = match device_name {
+        Some(name) => {
+            let mut devices = cpal::default_host().output_devices().map_err(|e| e.to_string())?;
+            let device = devices.find(|device| device.name().is_ok_and(|device_name| device_name == name));
+            Some(device.ok_or_else(|| format!("Output device \"{}\" isn't available", name))?)
+        }
+        None => None,
+    };
+    AudioManager::<CpalBackend>::new(AudioManagerSettings {
+        backend_settings: CpalBackendSettings { device, ..Default::default() },
+        ..Default::default()
+    }).map_err(|e| e.to_string())
+}
@@ -0,0 +1,415 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use eframe::egui;
+use regex::Regex;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget, SampleParser};
+
+// Points held for drawing; everything is still in raw_data
+const MAX_POINTS: usize = 10_000;
+
+// (µs since the epoch, value)
+type Points = Arc<Mutex<Vec<(i64, f64)>>>;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChartWidget {
+    pub id: usize,
+    pub version: i32,
+    pub command: String,
+    pub pattern: String,  // Regex whose first capture group is the number to plot
+    pub refresh_interval_secs: u64,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+    #[serde(skip, default)]
+    pub points: Points,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+fn now_micros() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64
+}
+
+pub fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+    if regex.captures_len() < 2 {
+        return Err("Put a capture group around the number, e.g. time=([\\d.]+)".to_string());
+    }
+    Ok(regex)
+}
+
+pub fn extract_value(regex: &Regex, line: &str) -> Option<f64> {
+    regex.captures(line)?.get(1)?.as_str().trim().parse().ok()
+}
+
+fn push_point(points: &mut Vec<(i64, f64)>, point: (i64, f64)) {
+    points.push(point);
+    if points.len() > MAX_POINTS {
+        let excess = points.len() - MAX_POINTS;
+        points.drain(0..excess);
+    }
+}
+
+// Plots each value as its line arrives and records it as the "value" sample
+struct ChartSampleParser {
+    regex: Regex,
+    points: Points,
+}
+
+impl SampleParser for ChartSampleParser {
+    fn parse(&mut self, line: &str) -> Vec<(String, f64)> {
+        let Some(value) = extract_value(&self.regex, line) else {
+            return vec![];
+        };
+        push_point(&mut self.points.lock().unwrap(), (now_micros(), value));
+        vec![("value".to_string(), value)]
+    }
+}
+
+impl crate::widgets::Widget for ChartWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "chart"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    // The pattern is compiled on every start, so an edited one takes effect with the restart
+    fn start(&self) {
+        match compile_pattern(&self.pattern) {
+            Ok(regex) => {
+                *self.executor.sample_parser.lock().unwrap() = Some(Box::new(ChartSampleParser {
+                    regex,
+                    points: self.points.clone(),
+                }));
+                self.start_command();
+            }
+            Err(e) => eprintln!("Chart {} not started: {}", self.id, e),
+        }
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("Chart")
+            .id(egui::Id::new(format!("chart_{}", self.id)))
+            .open(&mut open)
+            .default_pos([200.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
+            .default_size([560.0, 300.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
+                });
+                
+                ui.horizontal(|ui| {
+                    if self.render_config(ui) {
+                        self.config_unsaved = true;
+                        self.handle_config_change();
+                        self.save_config();
+                    }
+                });
+                
+                ui.separator();
+                self.render_output(ui);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop();
+        self.start();
+    }
+    
+    // Points are extracted again from the recorded lines, with the times they were recorded
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        if let Ok(regex) = compile_pattern(&self.pattern) {
+            let mut points = self.points.lock().unwrap();
+            for (timestamp, line, _) in &data {
+                if let Some(value) = extract_value(&regex, line) {
+                    push_point(&mut points, (*timestamp, value));
+                }
+            }
+        }
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for ChartWidget {
+    fn build_command(&self) -> CommandSpec {
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(self.command.trim())
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
+    }
+}
+
+impl CommandOutputRenderer for ChartWidget {
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn render_output(&self, ui: &mut egui::Ui) {
+        let points = self.points.lock().unwrap().clone();
+        if points.is_empty() {
+            ui.label("No values yet; each line the pattern matches adds a point");
+            return;
+        }
+        render_line_chart(ui, &points);
+    }
+}
+
+impl CommandControlBar for ChartWidget {}
+
+impl RefreshableWidget for ChartWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl ChartWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            command: "ping -c 1 127.0.0.1".to_string(),
+            pattern: r"time=([\d.]+)".to_string(),
+            refresh_interval_secs: 1,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+            config_unsaved: false,
+            points: Points::default(),
+        }
+    }
+    
+    // Command and pattern, applied once editing is done; returns true if either changed
+    fn render_config(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        
+        ui.label("Command:");
+        let id = egui::Id::new(format!("chart_command_{}", self.id));
+        let mut command = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| self.command.clone());
+        let response = ui.add(egui::TextEdit::singleline(&mut command)
+            .code_editor()
+            .desired_width(200.0));
+        if response.lost_focus() && command.trim() != self.command {
+            self.command = command.trim().to_string();
+            changed = true;
+        }
+        ui.data_mut(|d| d.insert_temp(id, command));
+        
+        ui.label("Value:");
+        let id = egui::Id::new(format!("chart_pattern_{}", self.id));
+        let mut pattern = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| self.pattern.clone());
+        let response = ui.add(egui::TextEdit::singleline(&mut pattern)
+            .code_editor()
+            .hint_text(r"regex, e.g. time=([\d.]+)")
+            .desired_width(140.0))
+            .on_hover_text("Each output line is matched against this; the first capture group is parsed as the value");
+        if response.lost_focus() && pattern != self.pattern {
+            self.pattern = pattern.clone();
+            // Values picked out by the old pattern don't belong on the new chart
+            self.points.lock().unwrap().clear();
+            changed = true;
+        }
+        if let Err(e) = compile_pattern(&pattern) {
+            let summary = e.lines().last().unwrap_or_default().trim().to_string();
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("⚠ {}", summary))
+                .on_hover_text(egui::RichText::new(e).monospace());
+        }
+        ui.data_mut(|d| d.insert_temp(id, pattern));
+        
+        changed
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.abs() >= 1000.0 || value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
+// Time runs left to right over the points held; hovering shows the nearest point
+fn render_line_chart(ui: &mut egui::Ui, points: &[(i64, f64)]) {
+    let (response, painter) = ui.allocate_painter(ui.available_size().max(egui::vec2(200.0, 100.0)), egui::Sense::hover());
+    let plot = egui::Rect::from_min_max(
+        response.rect.min + egui::vec2(64.0, 8.0),
+        response.rect.max - egui::vec2(8.0, 20.0),
+    );
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    let grid = visuals.widgets.noninteractive.bg_stroke;
+    let font = egui::FontId::monospace(11.0);
+    
+    let (start, end) = (points[0].0, points[points.len() - 1].0.max(points[0].0 + 1));
+    let (mut low, mut high) = points.iter().fold((f64::MAX, f64::MIN), |(low, high), &(_, value)| (low.min(value), high.max(value)));
+    let padding = if high > low { (high - low) * 0.05 } else { low.abs().max(1.0) * 0.1 };
+    low -= padding;
+    high += padding;
+    
+    let to_screen = |timestamp: i64, value: f64| egui::pos2(
+        egui::lerp(plot.x_range(), (timestamp - start) as f32 / (end - start) as f32),
+        egui::lerp(plot.bottom()..=plot.top(), ((value - low) / (high - low)) as f32),
+    );
+    
+    painter.rect_stroke(plot, 0.0, grid, egui::StrokeKind::Inside);
+    for step in 0..=4 {
+        let value = low + (high - low) * step as f64 / 4.0;
+        let y = to_screen(start, value).y;
+        painter.hline(plot.x_range(), y, egui::Stroke::new(0.5_f32, grid.color));
+        painter.text(egui::pos2(plot.left() - 6.0, y), egui::Align2::RIGHT_CENTER, format_value(value), font.clone(), text_color);
+    }
+    let time = |timestamp: i64| TimestampDisplay::Absolute.format(timestamp, start).unwrap_or_default();
+    painter.text(egui::pos2(plot.left(), plot.bottom() + 4.0), egui::Align2::LEFT_TOP, time(start), font.clone(), text_color);
+    painter.text(egui::pos2(plot.right(), plot.bottom() + 4.0), egui::Align2::RIGHT_TOP, time(end), font.clone(), text_color);
+    
+    let line: Vec<egui::Pos2> = points.iter().map(|&(timestamp, value)| to_screen(timestamp, value)).collect();
+    let color = ui.visuals().selection.bg_fill;
+    if line.len() == 1 {
+        painter.circle_filled(line[0], 3.0, color);
+    } else {
+        painter.add(egui::Shape::line(line, egui::Stroke::new(1.5_f32, color)));
+    }
+    
+    if let Some(pointer) = response.hover_pos().filter(|pos| plot.contains(*pos)) {
+        let nearest = points.iter()
+            .min_by_key(|&&(timestamp, value)| (to_screen(timestamp, value).x - pointer.x).abs() as i64)
+            .copied();
+        if let Some((timestamp, value)) = nearest {
+            let pos = to_screen(timestamp, value);
+            painter.vline(pos.x, plot.y_range(), egui::Stroke::new(0.5_f32, text_color));
+            painter.circle_filled(pos, 4.0, color);
+            painter.text(pos + egui::vec2(6.0, -6.0), egui::Align2::LEFT_BOTTOM, format!("{}  {}", format_value(value), time(timestamp)), font, text_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn values_come_from_the_first_capture_group() {
+        let regex = compile_pattern(r"time=([\d.]+)").unwrap();
+        assert_eq!(extract_value(&regex, "64 bytes from 127.0.0.1: icmp_seq=1 ttl=64 time=0.042 ms"), Some(0.042));
+        assert_eq!(extract_value(&regex, "PING 127.0.0.1 (127.0.0.1): 56 data bytes"), None);
+        
+        // The group has to hold a number
+        let regex = compile_pattern(r"status=(\w+)").unwrap();
+        assert_eq!(extract_value(&regex, "status=ok"), None);
+        
+        assert!(compile_pattern(r"time=[\d.]+").is_err());
+        assert!(compile_pattern(r"time=(").is_err());
+    }
+    
+    #[test]
+    fn reloaded_lines_are_plotted_at_their_recorded_times() {
+        use crate::widgets::command_widget::OutputStream;
+        let mut chart = ChartWidget::new(1);
+        crate::widgets::Widget::restore_widget_data(&mut chart, vec![
+            (1_000, "64 bytes: time=1.5 ms".to_string(), OutputStream::Stdout),
+            (2_000, "--- ping statistics ---".to_string(), OutputStream::Stdout),
+            (3_000, "64 bytes: time=2 ms".to_string(), OutputStream::Stdout),
+        ]);
+        assert_eq!(*chart.points.lock().unwrap(), vec![(1_000, 1.5), (3_000, 2.0)]);
+    }
+}
@@ -0,0 +1,189 @@
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, AsyncWrite};
+use super::command_widget::CommandSpec;
+
+pub type OutputPipe = Box<dyn AsyncRead + Send + Unpin>;
+pub type InputPipe = Box<dyn AsyncWrite + Send + Unpin>;
+
+// A started command: its pipes plus a handle to wait for or kill it
+pub struct SpawnedCommand {
+    pub stdout: OutputPipe,
+    pub stderr: OutputPipe,
+    pub stdin: Option<InputPipe>,
+    pub process: Box<dyn CommandProcess>,
+}
+
+pub trait CommandProcess: Send {
+    // Exit code once the process has ended on its own, None if killed by a signal
+    fn wait(&mut self) -> BoxFuture<'_, Option<i32>>;
+    // Ask the process to exit (SIGTERM), without waiting for it
+    fn terminate(&mut self);
+    fn kill(&mut self) -> BoxFuture<'_, ()>;
+}
+
+// How the executor starts commands, so tests can swap in canned output
+pub trait CommandRunner: Send + Sync {
+    fn spawn(&self, spec: &CommandSpec, with_stdin: bool) -> std::io::Result<SpawnedCommand>;
+}
+
+// Runs commands as real child processes
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn spawn(&self, spec: &CommandSpec, with_stdin: bool) -> std::io::Result<SpawnedCommand> {
+        let mut cmd = tokio::process::Command::new(&spec.program);
+        cmd.args(&spec.args)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        if with_stdin {
+            cmd.stdin(std::process::Stdio::piped());
+        }
+        
+        let mut child = cmd.spawn()?;
+        Ok(SpawnedCommand {
+            stdout: Box::new(child.stdout.take().unwrap()),
+            stderr: Box::new(child.stderr.take().unwrap()),
+            stdin: child.stdin.take().map(|stdin| Box::new(stdin) as InputPipe),
+            process: Box::new(child),
+        })
+    }
+}
+
+impl CommandProcess for tokio::process::Child {
+    fn wait(&mut self) -> BoxFuture<'_, Option<i32>> {
+        Box::pin(async move {
+            tokio::process::Child::wait(self).await.ok().and_then(|status| status.code())
+        })
+    }
+    
+    #[cfg(unix)]
+    fn terminate(&mut self) {
+        if let Some(pid) = self.id() {
+            // Safe: signalling our own child, which hasn't been reaped while we hold the handle
+            unsafe {
+                libc::kill(pid as libc::pid_t, libc::SIGTERM);
+            }
+        }
+    }
+    
+    // No SIGTERM elsewhere, so the grace period is skipped
+    #[cfg(not(unix))]
+    fn terminate(&mut self) {
+        let _ = self.start_kill();
+    }
+    
+    fn kill(&mut self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let _ = tokio::process::Child::kill(self).await;
+        })
+    }
+}
+
+// Returns the same canned output for every command and remembers what was asked for
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct FakeRunner {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub run_time: std::time::Duration,  // How long each fake process takes to exit after its output
+    pub holds_open: bool,  // Keep stdout open after the canned lines, like `tail -f`
+    pub terminated: std::sync::Arc<std::sync::Mutex<usize>>,  // SIGTERMs sent to fake processes
+    pub spawned: std::sync::Arc<std::sync::Mutex<Vec<CommandSpec>>>,
+    pub spawn_times: std::sync::Arc<std::sync::Mutex<Vec<tokio::time::Instant>>>,  // On tokio's clock, which tests can pause
+}
+
+#[cfg(test)]
+impl FakeRunner {
+    pub fn new(stdout: &[&str]) -> Self {
+        Self {
+            stdout: stdout.iter().map(|line| line.to_string()).collect(),
+            exit_code: Some(0),
+            ..Self::default()
+        }
+    }
+    
+    pub fn with_stderr(mut self, stderr: &[&str]) -> Self {
+        self.stderr = stderr.iter().map(|line| line.to_string()).collect();
+        self
+    }
+    
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+    
+    pub fn with_run_time(mut self, run_time: std::time::Duration) -> Self {
+        self.run_time = run_time;
+        self
+    }
+    
+    pub fn holding_output_open(mut self) -> Self {
+        self.holds_open = true;
+        self
+    }
+    
+    pub fn spawn_count(&self) -> usize {
+        self.spawned.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for FakeRunner {
+    fn spawn(&self, spec: &CommandSpec, with_stdin: bool) -> std::io::Result<SpawnedCommand> {
+        self.spawned.lock().unwrap().push(spec.clone());
+        self.spawn_times.lock().unwrap().push(tokio::time::Instant::now());
+        let pipe = |lines: &[String]| -> OutputPipe {
+            Box::new(std::io::Cursor::new(lines.iter().map(|line| format!("{}\n", line)).collect::<String>().into_bytes()))
+        };
+        let stdout = if self.holds_open {
+            Box::new(tokio::io::AsyncReadExt::chain(pipe(&self.stdout), NeverEnds))
+        } else {
+            pipe(&self.stdout)
+        };
+        Ok(SpawnedCommand {
+            stdout,
+            stderr: pipe(&self.stderr),
+            stdin: with_stdin.then(|| Box::new(tokio::io::sink()) as InputPipe),
+            process: Box::new(FakeProcess { exit_code: self.exit_code, run_time: self.run_time, terminated: self.terminated.clone() }),
+        })
+    }
+}
+
+// A pipe that never produces anything more
+#[cfg(test)]
+struct NeverEnds;
+
+#[cfg(test)]
+impl AsyncRead for NeverEnds {
+    fn poll_read(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>, _buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<std::io::Result<()>> {
+        std::task::Poll::Pending
+    }
+}
+
+#[cfg(test)]
+struct FakeProcess {
+    exit_code: Option<i32>,
+    run_time: std::time::Duration,
+    terminated: std::sync::Arc<std::sync::Mutex<usize>>,
+}
+
+#[cfg(test)]
+impl CommandProcess for FakeProcess {
+    fn wait(&mut self) -> BoxFuture<'_, Option<i32>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.run_time).await;
+            self.exit_code
+        })
+    }
+    
+    fn terminate(&mut self) {
+        *self.terminated.lock().unwrap() += 1;
+        self.exit_code = None;
+    }
+    
+    fn kill(&mut self) -> BoxFuture<'_, ()> {
+        self.exit_code = None;
+        Box::pin(std::future::ready(()))
+    }
+}
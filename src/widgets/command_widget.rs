@@ -1,9 +1,10 @@
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use tokio::runtime::Runtime;
-use tokio::process::Command;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use serde::{Serialize, Deserialize};
+use super::command_runner::{CommandRunner, InputPipe, OutputPipe, ProcessRunner};
+use super::ansi;
+use crate::database::raw_data_writer::{RawDataWriter, RecordedLine};
 
 // Core execution modes
 #[derive(Debug, Clone)]
@@ -18,6 +19,8 @@ pub enum ExecutionMode {
 pub struct CommandSpec {
     pub program: String,
     pub args: Vec<String>,
+    pub timeout: Option<Duration>,  // Kill one-shot and periodic runs that take longer; ignored when continuous
+    pub fan_out: Vec<(String, CommandSpec)>,  // Set for a host group: one command per host, run side by side under its host's name
 }
 
 impl CommandSpec {
@@ -25,6 +28,16 @@ impl CommandSpec {
         Self {
             program: program.into(),
             args: Vec::new(),
+            timeout: None,
+            fan_out: Vec::new(),
+        }
+    }
+    
+    // Run each (host name, command) at once as a single run, with their output merged
+    pub fn fan_out(targets: Vec<(String, CommandSpec)>) -> Self {
+        Self {
+            fan_out: targets,
+            ..Self::new("")
         }
     }
     
@@ -37,32 +50,302 @@ impl CommandSpec {
         self.args = args;
         self
     }
+    
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+// Prompt passed to `sudo -p` so it can be spotted on stderr
+const SUDO_PROMPT: &str = "skop-sudo-password:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SudoStatus {
+    #[default]
+    Ok,
+    PasswordRequired,
+    IncorrectPassword,
+}
+
+// Per-widget sudo settings; the password only lives in memory
+#[derive(Debug, Clone, Default)]
+pub struct SudoState {
+    pub enabled: bool,
+    pub password: Option<String>,
+    pub status: SudoStatus,
+}
+
+// Which output streams to show or record
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum StreamSelection {
+    #[default]
+    Both,
+    StdoutOnly,
+    StderrOnly,
+}
+
+impl StreamSelection {
+    pub const ALL: &'static [StreamSelection] = &[
+        StreamSelection::Both,
+        StreamSelection::StdoutOnly,
+        StreamSelection::StderrOnly,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            StreamSelection::Both => "Both",
+            StreamSelection::StdoutOnly => "stdout only",
+            StreamSelection::StderrOnly => "stderr only",
+        }
+    }
+    
+    pub fn includes(&self, stream: OutputStream) -> bool {
+        match self {
+            StreamSelection::Both => true,
+            StreamSelection::StdoutOnly => stream == OutputStream::Stdout,
+            StreamSelection::StderrOnly => stream == OutputStream::Stderr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    // As stored in raw_data.stream
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+    
+    // Back from raw_data.stream; anything unrecognised counts as stdout
+    pub fn from_recorded(stream: &str) -> Self {
+        match stream {
+            "stderr" => OutputStream::Stderr,
+            _ => OutputStream::Stdout,
+        }
+    }
+}
+
+// A recorded line as restored: when it arrived, its text and the stream it came from
+pub type TimedLine = (i64, String, OutputStream);
+
+// How each line's arrival time is put in front of it; display only
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TimestampDisplay {
+    #[default]
+    Off,
+    Relative,  // Since the first line in the buffer
+    Absolute,  // Local time of day
+}
+
+impl TimestampDisplay {
+    pub const ALL: &'static [TimestampDisplay] = &[
+        TimestampDisplay::Off,
+        TimestampDisplay::Relative,
+        TimestampDisplay::Absolute,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimestampDisplay::Off => "Off",
+            TimestampDisplay::Relative => "Relative",
+            TimestampDisplay::Absolute => "Time of day",
+        }
+    }
+    
+    // `timestamp` and `first` are microseconds since the epoch
+    pub fn format(&self, timestamp: i64, first: i64) -> Option<String> {
+        match self {
+            TimestampDisplay::Off => None,
+            TimestampDisplay::Relative => {
+                let elapsed = (timestamp - first).max(0);
+                let millis = (elapsed / 1000) % 1000;
+                let secs = elapsed / 1_000_000;
+                Some(format!("+{}:{:02}.{:03}", secs / 60, secs % 60, millis))
+            }
+            TimestampDisplay::Absolute => Some(format_time_of_day(timestamp + local_utc_offset(timestamp / 1_000_000) * 1_000_000)),
+        }
+    }
+}
+
+// "HH:MM:SS.mmm" of a microsecond timestamp already shifted to the wanted time zone
+fn format_time_of_day(timestamp: i64) -> String {
+    let micros_of_day = timestamp.rem_euclid(86_400 * 1_000_000);
+    let secs = micros_of_day / 1_000_000;
+    format!("{:02}:{:02}:{:02}.{:03}", secs / 3600, (secs % 3600) / 60, secs % 60, (micros_of_day / 1000) % 1000)
+}
+
+// Seconds the local time zone is ahead of UTC at `secs` since the epoch
+#[cfg(unix)]
+fn local_utc_offset(secs: i64) -> i64 {
+    let time = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return 0;
+    }
+    tm.tm_gmtoff as i64
+}
+
+// No libc elsewhere; times are shown in UTC
+#[cfg(not(unix))]
+fn local_utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+fn now_micros() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct StreamSettings {
+    pub display: StreamSelection,
+    pub record: StreamSelection,
+}
+
+// Output volume received by the current run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransferStats {
+    pub bytes: u64,
+    pub lines: u64,
+    pub started: Option<Instant>,
+    pub ended: Option<Instant>,
+}
+
+impl TransferStats {
+    // Bytes per second since the run started
+    pub fn rate(&self) -> f64 {
+        let Some(started) = self.started else {
+            return 0.0;
+        };
+        let elapsed = self.ended.unwrap_or_else(Instant::now).duration_since(started).as_secs_f64();
+        if elapsed > 0.0 {
+            self.bytes as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+}
+
+// What a widget's command has cost since it was created, across restarts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WidgetUsage {
+    pub runs: u64,
+    pub busy: Duration,  // Wall time spent with a command process running
+    pub bytes: u64,
+    pub lines: u64,
+    pub buffered_bytes: usize,  // Output currently held in memory
+}
+
+impl WidgetUsage {
+    pub fn average_run(&self) -> Duration {
+        if self.runs == 0 {
+            Duration::ZERO
+        } else {
+            self.busy / self.runs as u32
+        }
+    }
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// Turns output lines into named numeric samples, recorded alongside the raw text
+pub trait SampleParser: Send {
+    fn parse(&mut self, line: &str) -> Vec<(String, f64)>;
 }
 
 // Core command executor that all widgets will use  
 #[derive(Clone)]
 pub struct CommandExecutor {
     pub output: Arc<Mutex<Vec<String>>>,
+    pub previous_output: Arc<Mutex<Vec<String>>>,  // Output of the last completed periodic run
+    pub output_streams: Arc<Mutex<Vec<OutputStream>>>,  // Stream of each line in `output`, kept in step with it
+    pub output_times: Arc<Mutex<Vec<i64>>>,  // When each line in `output` arrived (µs since the epoch), kept in step with it
+    pub output_revision: Arc<Mutex<u64>>,  // Bumped whenever `output` changes, so renderers can cache what they derive from it
     pub is_running: Arc<Mutex<bool>>,
+    pub stop_signal: Arc<tokio::sync::Notify>,  // Wakes a run blocked waiting for output when it's stopped
+    pub run_generation: Arc<Mutex<u64>>,  // Bumped on every start so stale runs exit after a restart
+    pub current_run: Arc<Mutex<i64>>,  // When the command producing output now was started, recorded with each line
+    pub stats: Arc<Mutex<TransferStats>>,  // Bytes/lines received since start
+    pub reconnect_attempt: Arc<Mutex<Option<u32>>>,  // Set while a dropped SSH session is being re-established
+    pub command_queued: Arc<Mutex<bool>>,  // Waiting for a free command slot; shown in the control bar, not recorded
+    pub ssh_queued: Arc<Mutex<bool>>,  // Waiting for a free SSH connect slot
+    pub sudo: Arc<Mutex<SudoState>>,  // Run with sudo, answering its password prompt
+    pub streams: Arc<Mutex<StreamSettings>>,  // Which of stdout/stderr to display and record
+    pub collapse_repeats: Arc<Mutex<bool>>,  // Show runs of identical lines once, with a count; display only
+    pub raw_escapes: Arc<Mutex<bool>>,  // Show ANSI escape codes as text instead of styling with them; display only
+    pub timestamps: Arc<Mutex<TimestampDisplay>>,  // Put each line's arrival time in front of it
+    pub usage: Arc<Mutex<WidgetUsage>>,  // Cumulative runs, run time and output volume
+    pub sample_parser: Arc<Mutex<Option<Box<dyn SampleParser>>>>,  // Extracts numeric samples from recorded lines
+    pub samples_only: bool,  // Record the parsed samples but not the lines, for a helper command beside the widget's own
     pub database: Option<Arc<crate::database::investigation_db::InvestigationDB>>,
+    pub recorder: Option<RawDataWriter>,  // Batches recorded lines into the database for this widget version
     pub widget_id: Option<i32>,
     pub widget_version: Option<i32>,
-    pub max_lines: usize,  // Limit output buffer size
+    pub max_lines: Arc<Mutex<usize>>,  // Limit output buffer size; shared with running commands so changes apply at once
     pub selected_host: Arc<Mutex<String>>,  // Selected host for execution
+    pub command_prefix: Arc<Mutex<String>>,  // The selected host's command prefix
+    pub selected_group: Arc<Mutex<Option<String>>>,  // Host group to fan out to; overrides the selected host when set
     pub available_hosts: Arc<Mutex<Vec<crate::database::investigation_db::Host>>>,  // Available hosts
+    pub runner: Arc<dyn CommandRunner>,  // Starts the processes; swapped for a fake in tests
 }
 
 impl Default for CommandExecutor {
     fn default() -> Self {
         Self {
             output: Arc::new(Mutex::new(Vec::new())),
+            previous_output: Arc::new(Mutex::new(Vec::new())),
+            output_streams: Arc::new(Mutex::new(Vec::new())),
+            output_times: Arc::new(Mutex::new(Vec::new())),
+            output_revision: Arc::new(Mutex::new(0)),
             is_running: Arc::new(Mutex::new(false)),
+            stop_signal: Arc::new(tokio::sync::Notify::new()),
+            run_generation: Arc::new(Mutex::new(0)),
+            current_run: Arc::new(Mutex::new(0)),
+            stats: Arc::new(Mutex::new(TransferStats::default())),
+            reconnect_attempt: Arc::new(Mutex::new(None)),
+            command_queued: Arc::new(Mutex::new(false)),
+            ssh_queued: Arc::new(Mutex::new(false)),
+            sudo: Arc::new(Mutex::new(SudoState::default())),
+            streams: Arc::new(Mutex::new(StreamSettings::default())),
+            collapse_repeats: Arc::new(Mutex::new(false)),
+            raw_escapes: Arc::new(Mutex::new(false)),
+            timestamps: Arc::new(Mutex::new(TimestampDisplay::default())),
+            usage: Arc::new(Mutex::new(WidgetUsage::default())),
+            sample_parser: Arc::new(Mutex::new(None)),
+            samples_only: false,
             database: None,
+            recorder: None,
             widget_id: None,
             widget_version: None,
-            max_lines: 1000,
+            max_lines: Arc::new(Mutex::new(1000)),
             selected_host: Arc::new(Mutex::new("localhost".to_string())),
+            command_prefix: Arc::new(Mutex::new(String::new())),
+            selected_group: Arc::new(Mutex::new(None)),
             available_hosts: Arc::new(Mutex::new(vec![])),
+            runner: Arc::new(ProcessRunner),
         }
     }
 }
@@ -72,6 +355,21 @@ impl CommandExecutor {
         Self::default()
     }
     
+    pub fn with_sample_parser(self, parser: impl SampleParser + 'static) -> Self {
+        *self.sample_parser.lock().unwrap() = Some(Box::new(parser));
+        self
+    }
+    
+    pub fn with_runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.runner = Arc::new(runner);
+        self
+    }
+    
+    pub fn recording_samples_only(mut self) -> Self {
+        self.samples_only = true;
+        self
+    }
+    
     pub fn get_selected_host(&self) -> String {
         self.selected_host.lock().unwrap().clone()
     }
@@ -80,20 +378,121 @@ impl CommandExecutor {
         *self.selected_host.lock().unwrap() = host;
     }
     
-    pub fn set_available_hosts(&self, hosts: Vec<crate::database::investigation_db::Host>) {
+    pub fn command_prefix(&self) -> String {
+        self.command_prefix.lock().unwrap().clone()
+    }
+    
+    pub fn set_command_prefix(&self, prefix: String) {
+        *self.command_prefix.lock().unwrap() = prefix;
+    }
+    
+    pub fn selected_group(&self) -> Option<String> {
+        self.selected_group.lock().unwrap().clone()
+    }
+    
+    pub fn set_selected_group(&self, group: Option<String>) {
+        *self.selected_group.lock().unwrap() = group;
+    }
+    
+    pub fn sudo_enabled(&self) -> bool {
+        self.sudo.lock().unwrap().enabled
+    }
+    
+    pub fn set_sudo_enabled(&self, enabled: bool) {
+        let mut sudo = self.sudo.lock().unwrap();
+        sudo.enabled = enabled;
+        sudo.status = SudoStatus::Ok;
+    }
+    
+    pub fn sudo_status(&self) -> SudoStatus {
+        self.sudo.lock().unwrap().status
+    }
+    
+    pub fn set_sudo_password(&self, password: String) {
+        let mut sudo = self.sudo.lock().unwrap();
+        sudo.password = Some(password);
+        sudo.status = SudoStatus::Ok;
+    }
+    
+    // Returns true when the selected host (or every host of the selected group) is no longer in the list
+    // and the executor fell back to localhost
+    pub fn set_available_hosts(&self, hosts: Vec<crate::database::investigation_db::Host>) -> bool {
+        let host = self.get_selected_host();
+        let prefix = self.command_prefix();
+        let still_listed = hosts.iter().any(|h| h.ssh_alias == host && h.command_prefix == prefix);
+        let group = self.selected_group();
+        let group_listed = group.as_ref().is_some_and(|group| hosts.iter().any(|h| &h.group == group));
         *self.available_hosts.lock().unwrap() = hosts;
+        
+        if group.is_some() {
+            if group_listed {
+                return false;
+            }
+            self.set_selected_group(None);
+        }
+        if still_listed || crate::database::investigation_db::Host::is_plain_localhost(&host, &prefix) {
+            return false;
+        }
+        self.set_selected_host("localhost".to_string());
+        self.set_command_prefix(String::new());
+        true
+    }
+    
+    // Move the selection from an edited host to its new alias and prefix; true when the executor was on it
+    pub fn retarget_host(&self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host) -> bool {
+        if self.selected_group().is_some() || self.get_selected_host() != from.ssh_alias || self.command_prefix() != from.command_prefix {
+            return false;
+        }
+        self.set_selected_host(to.ssh_alias.clone());
+        self.set_command_prefix(to.command_prefix.clone());
+        true
+    }
+    
+    // Hosts in a group, in list order
+    pub fn group_members(&self, group: &str) -> Vec<crate::database::investigation_db::Host> {
+        self.available_hosts.lock().unwrap().iter()
+            .filter(|h| h.group == group)
+            .cloned()
+            .collect()
     }
     
     pub fn get_available_hosts(&self) -> Vec<crate::database::investigation_db::Host> {
         self.available_hosts.lock().unwrap().clone()
     }
     
-    pub fn with_max_lines(mut self, max: usize) -> Self {
-        self.max_lines = max;
+    // The configured host behind the current selection, for its connection options
+    pub fn selected_host_entry(&self) -> Option<crate::database::investigation_db::Host> {
+        let host = self.get_selected_host();
+        let prefix = self.command_prefix();
+        self.available_hosts.lock().unwrap().iter()
+            .find(|h| h.ssh_alias == host && h.command_prefix == prefix)
+            .cloned()
+    }
+    
+    pub fn with_max_lines(self, max: usize) -> Self {
+        *self.max_lines.lock().unwrap() = max;
         self
     }
     
+    pub fn max_lines(&self) -> usize {
+        *self.max_lines.lock().unwrap()
+    }
+    
+    // A lower limit drops the oldest buffered lines now; a higher one lets the buffer grow from here
+    pub fn set_max_lines(&self, max: usize) {
+        *self.max_lines.lock().unwrap() = max;
+        let mut output = self.output.lock().unwrap();
+        if output.len() > max {
+            trim_to(&mut output, max);
+            trim_to(&mut self.output_streams.lock().unwrap(), max);
+            trim_to(&mut self.output_times.lock().unwrap(), max);
+            drop(output);
+            self.output_changed();
+        }
+    }
+    
     pub fn set_database(&mut self, database: Option<Arc<crate::database::investigation_db::InvestigationDB>>, widget_id: i32, widget_version: i32) {
+        self.recorder = database.as_ref().map(|db| RawDataWriter::spawn(db.clone(), widget_id, widget_version));
         self.database = database;
         self.widget_id = Some(widget_id);
         self.widget_version = Some(widget_version);
@@ -101,50 +500,200 @@ impl CommandExecutor {
     
     pub fn clear_output(&self) {
         self.output.lock().unwrap().clear();
+        self.output_streams.lock().unwrap().clear();
+        self.output_times.lock().unwrap().clear();
+        self.previous_output.lock().unwrap().clear();
+        self.output_changed();
     }
     
-    pub fn load_historical_output(&self, lines: Vec<String>) {
-        let mut output = self.output.lock().unwrap();
-        output.clear(); // Clear any existing output
-        output.extend(lines); // Add all historical lines
-        
+    pub fn output_revision(&self) -> u64 {
+        *self.output_revision.lock().unwrap()
+    }
+    
+    fn output_changed(&self) {
+        *self.output_revision.lock().unwrap() += 1;
+        crate::runtime::request_repaint();
+    }
+    
+    // Recorded lines, oldest first
+    pub fn load_historical_output(&self, lines: Vec<TimedLine>) {
         // Respect max_lines limit
-        if output.len() > self.max_lines {
-            let excess = output.len() - self.max_lines;
-            output.drain(0..excess);
+        let skip = lines.len().saturating_sub(self.max_lines());
+        let mut times = Vec::with_capacity(lines.len() - skip);
+        let mut streams = Vec::with_capacity(lines.len() - skip);
+        let mut text = Vec::with_capacity(lines.len() - skip);
+        for (time, line, stream) in lines.into_iter().skip(skip) {
+            times.push(time);
+            text.push(line);
+            streams.push(stream);
         }
+        
+        let mut output = self.output.lock().unwrap();
+        *self.output_streams.lock().unwrap() = streams;
+        *self.output_times.lock().unwrap() = times;
+        *output = text;
+        self.output_changed();
     }
     
-    pub fn add_output(&self, line: String, line_number: i32) {
-        // Add to output buffer for UI
+    // Which stream each buffered line came from; lines are only ever appended, so align from the end
+    pub fn line_streams(&self, line_count: usize) -> Vec<OutputStream> {
+        let streams = self.output_streams.lock().unwrap();
+        let known = &streams[streams.len().saturating_sub(line_count)..];
+        let mut aligned = vec![OutputStream::Stdout; line_count - known.len()];
+        aligned.extend_from_slice(known);
+        aligned
+    }
+    
+    // When each buffered line arrived, aligned from the end like `line_streams`; None where unknown
+    pub fn line_times(&self, line_count: usize) -> Vec<Option<i64>> {
+        let times = self.output_times.lock().unwrap();
+        let known = &times[times.len().saturating_sub(line_count)..];
+        let mut aligned = vec![None; line_count - known.len()];
+        aligned.extend(known.iter().copied().map(Some));
+        aligned
+    }
+    
+    pub fn timestamp_display(&self) -> TimestampDisplay {
+        *self.timestamps.lock().unwrap()
+    }
+    
+    pub fn set_timestamp_display(&self, display: TimestampDisplay) {
+        *self.timestamps.lock().unwrap() = display;
+    }
+    
+    pub fn stream_settings(&self) -> StreamSettings {
+        *self.streams.lock().unwrap()
+    }
+    
+    pub fn set_stream_settings(&self, settings: StreamSettings) {
+        *self.streams.lock().unwrap() = settings;
+    }
+    
+    pub fn collapses_repeats(&self) -> bool {
+        *self.collapse_repeats.lock().unwrap()
+    }
+    
+    pub fn set_collapse_repeats(&self, collapse: bool) {
+        *self.collapse_repeats.lock().unwrap() = collapse;
+    }
+    
+    pub fn shows_raw_escapes(&self) -> bool {
+        *self.raw_escapes.lock().unwrap()
+    }
+    
+    pub fn set_show_raw_escapes(&self, raw: bool) {
+        *self.raw_escapes.lock().unwrap() = raw;
+    }
+    
+    // Status and error lines are always shown and recorded
+    pub async fn add_output(&self, line: String, line_number: i32) {
+        self.add_line(OutputStream::Stdout, line, line_number, true, true, None).await;
+    }
+    
+    // A line from the command itself, filtered by the stream settings. `host` names the group member it came from
+    async fn add_stream_line(&self, stream: OutputStream, line: String, line_number: i32, host: Option<&str>) {
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.bytes += line.len() as u64 + 1;
+            stats.lines += 1;
+        }
         {
+            let mut usage = self.usage.lock().unwrap();
+            usage.bytes += line.len() as u64 + 1;
+            usage.lines += 1;
+        }
+        self.reconnect_attempt.lock().unwrap().take();
+        
+        let streams = self.stream_settings();
+        self.add_line(stream, line, line_number, streams.display.includes(stream), streams.record.includes(stream), host).await;
+    }
+    
+    // Recorded lines go through the widget's batching writer, whose queue is bounded: a slow database slows reading.
+    // Lines from a group member are shown with its name in front and recorded with it in the host column
+    async fn add_line(&self, stream: OutputStream, line: String, line_number: i32, display: bool, record: bool, host: Option<&str>) {
+        if !display && !record {
+            return;
+        }
+        
+        let timestamp = now_micros();
+        
+        // Add to output buffer for UI
+        if display {
             let mut output = self.output.lock().unwrap();
-            output.push(line.clone());
-            // Keep buffer size limited
-            if output.len() > self.max_lines {
-                let excess = output.len() - self.max_lines;
-                output.drain(0..excess);
+            let mut streams = self.output_streams.lock().unwrap();
+            let mut times = self.output_times.lock().unwrap();
+            match host {
+                Some(host) => output.push(format!("[{}] {}", host, line)),
+                None => output.push(line.clone()),
             }
+            streams.push(stream);
+            times.push(timestamp);
+            // Keep buffer size limited
+            let max_lines = self.max_lines();
+            trim_to(&mut output, max_lines);
+            trim_to(&mut streams, output.len());
+            trim_to(&mut times, output.len());
+            self.output_changed();
         }
         
+        // Parsed whether or not there's a database, since a parser may also feed the widget's display
+        let samples = match record {
+            true => self.sample_parser.lock().unwrap().as_mut().map(|parser| parser.parse(&line)).unwrap_or_default(),
+            false => vec![],
+        };
+        
         // Log to database if available
-        if let (Some(db), Some(widget_id), Some(widget_version)) = 
-            (&self.database, &self.widget_id, &self.widget_version) {
-            let db_clone = db.clone();
-            let widget_id = *widget_id;
-            let widget_version = *widget_version;
-            let line_clone = line.clone();
-            
-            tokio::spawn(async move {
-                if let Err(e) = db_clone.record_raw_data(widget_id, widget_version, &line_clone, line_number).await {
-                    eprintln!("Failed to record raw data: {}", e);
-                }
-            });
+        if self.samples_only && samples.is_empty() {
+            return;
+        }
+        if let (true, Some(recorder)) = (record, &self.recorder) {
+            let run = *self.current_run.lock().unwrap();
+            recorder.record(RecordedLine {
+                timestamp,
+                line_content: self.redactor().redact(&line).into_owned(),
+                line_number,
+                stream: stream.as_str(),
+                host: host.map(str::to_string),
+                run: Some(run),
+                samples,
+                samples_only: self.samples_only,
+            }).await;
         }
     }
     
     pub fn stop(&self) {
         *self.is_running.lock().unwrap() = false;
+        self.stats.lock().unwrap().ended.get_or_insert_with(Instant::now);
+        self.stop_signal.notify_waiters();
+    }
+    
+    pub fn transfer_stats(&self) -> TransferStats {
+        *self.stats.lock().unwrap()
+    }
+    
+    pub fn usage(&self) -> WidgetUsage {
+        let buffered_bytes = self.output.lock().unwrap().iter().map(|line| line.len()).sum();
+        WidgetUsage {
+            buffered_bytes,
+            ..*self.usage.lock().unwrap()
+        }
+    }
+    
+    // Redaction rules of the investigation this widget records into
+    pub fn redactor(&self) -> Arc<crate::redaction::Redactor> {
+        self.database.as_ref().map(|db| db.redactor()).unwrap_or_default()
+    }
+    
+    // Lines to export: the in-memory buffer, or everything recorded for this widget
+    pub fn export_lines(&self, full_history: bool) -> Result<Vec<String>, sqlx::Error> {
+        if full_history {
+            if let (Some(db), Some(widget_id)) = (&self.database, self.widget_id) {
+                return crate::runtime::runtime().block_on(db.get_all_widget_data(widget_id));
+            }
+        }
+        // The buffer holds raw output, so apply the same redaction as recorded data
+        let redactor = self.redactor();
+        Ok(self.output.lock().unwrap().iter().map(|line| redactor.redact(line).into_owned()).collect())
     }
     
     pub fn is_running(&self) -> bool {
@@ -158,13 +707,11 @@ impl CommandExecutor {
         }
         
         let executor = self.clone();
-        *self.is_running.lock().unwrap() = true;
+        let generation = self.begin_run();
         
-        thread::spawn(move || {
-            let rt = Runtime::new().unwrap();
-            rt.block_on(async {
-                executor.execute_command(spec, false).await;
-            });
+        crate::runtime::runtime().spawn(async move {
+            executor.execute_command(spec, false, generation).await;
+            executor.finish_run(generation);
         });
     }
     
@@ -175,13 +722,35 @@ impl CommandExecutor {
         }
         
         let executor = self.clone();
-        *self.is_running.lock().unwrap() = true;
+        let generation = self.begin_run();
         
-        thread::spawn(move || {
-            let rt = Runtime::new().unwrap();
-            rt.block_on(async {
-                executor.execute_command(spec, true).await;
-            });
+        crate::runtime::runtime().spawn(async move {
+            let mut attempt = 0u32;
+            loop {
+                let lines_before = executor.transfer_stats().lines;
+                let exit_code = executor.execute_command(spec.clone(), true, generation).await;
+                
+                // Only dropped SSH sessions are re-established, a command that exits on its own stays stopped
+                if spec.program != "ssh" || exit_code != Some(SSH_CONNECTION_ERROR) || !executor.is_current_run(generation) {
+                    break;
+                }
+                
+                // Back off further while reconnects keep failing without output
+                if executor.transfer_stats().lines > lines_before {
+                    attempt = 0;
+                }
+                attempt += 1;
+                let delay = reconnect_delay(attempt);
+                *executor.reconnect_attempt.lock().unwrap() = Some(attempt);
+                executor.add_output(format!("--- SSH connection lost, reconnecting in {}s (attempt {}) ---", delay.as_secs(), attempt), 0).await;
+                
+                if !executor.sleep_while_current(delay, generation).await {
+                    break;
+                }
+            }
+            
+            *executor.reconnect_attempt.lock().unwrap() = None;
+            executor.finish_run(generation);
         });
     }
     
@@ -192,68 +761,489 @@ impl CommandExecutor {
         }
         
         let executor = self.clone();
+        let generation = self.begin_run();
+        crate::runtime::runtime().spawn(executor.run_on_schedule(spec, interval, generation));
+    }
+    
+    // Runs start on a fixed schedule, however long each takes; ticks missed by an overrunning run are skipped
+    async fn run_on_schedule(self, spec: CommandSpec, interval: Duration, generation: u64) {
+        let mut schedule = tokio::time::interval(interval);
+        schedule.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        
+        loop {
+            schedule.tick().await;
+            if !self.is_current_run(generation) {
+                break;
+            }
+            
+            // Keep the last run around for comparison, then clear output for this run
+            let last_run = std::mem::take(&mut *self.output.lock().unwrap());
+            self.output_streams.lock().unwrap().clear();
+            self.output_times.lock().unwrap().clear();
+            *self.previous_output.lock().unwrap() = last_run;
+            self.output_changed();
+            
+            self.execute_command(spec.clone(), false, generation).await;
+        }
+    }
+    
+    // Sleep in short steps so a stop or restart isn't held up; false if the run ended meanwhile
+    async fn sleep_while_current(&self, duration: Duration, generation: u64) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if !self.is_current_run(generation) {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+        self.is_current_run(generation)
+    }
+    
+    pub fn reconnect_attempt(&self) -> Option<u32> {
+        *self.reconnect_attempt.lock().unwrap()
+    }
+    
+    pub fn is_command_queued(&self) -> bool {
+        *self.command_queued.lock().unwrap()
+    }
+    
+    pub fn is_ssh_queued(&self) -> bool {
+        *self.ssh_queued.lock().unwrap()
+    }
+    
+    // Mark a new run as started and return its generation
+    fn begin_run(&self) -> u64 {
+        let mut generation = self.run_generation.lock().unwrap();
+        *generation += 1;
         *self.is_running.lock().unwrap() = true;
+        *self.stats.lock().unwrap() = TransferStats {
+            started: Some(Instant::now()),
+            ..TransferStats::default()
+        };
+        *generation
+    }
+    
+    // False once stopped or superseded by a restart
+    fn is_current_run(&self, generation: u64) -> bool {
+        self.is_running() && *self.run_generation.lock().unwrap() == generation
+    }
+    
+    fn finish_run(&self, generation: u64) {
+        if *self.run_generation.lock().unwrap() == generation {
+            *self.is_running.lock().unwrap() = false;
+            self.stats.lock().unwrap().ended.get_or_insert_with(Instant::now);
+            crate::runtime::request_repaint();
+        }
+    }
+    
+    // stderr lines are numbered separately from stdout
+    async fn capture_stderr(&self, stderr: OutputPipe, host: Option<&str>) {
+        let mut reader = BufReader::new(stderr).lines();
+        let mut line_number = 1i32;
+        while let Ok(Some(line)) = reader.next_line().await {
+            self.add_stream_line(OutputStream::Stderr, line, line_number, host).await;
+            line_number += 1;
+        }
+    }
+    
+    // Watch sudo's stderr, answer its password prompt once, and surface anything else as output
+    async fn answer_sudo_prompt(&self, mut stderr: OutputPipe, mut stdin: Option<InputPipe>, host: Option<&str>) {
+        let mut pending = String::new();
+        let mut buf = [0u8; 1024];
+        let mut sent_password = false;
         
-        thread::spawn(move || {
-            let rt = Runtime::new().unwrap();
-            rt.block_on(async {
-                while executor.is_running() {
-                    // Clear output for each periodic run
-                    executor.clear_output();
-                    
-                    executor.execute_command(spec.clone(), false).await;
-                    
-                    // Wait for interval
-                    tokio::time::sleep(interval).await;
+        while let Ok(n) = stderr.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+            pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+            
+            // Handle prompts and complete lines in order; the prompt has no trailing newline
+            loop {
+                let prompt = pending.find(SUDO_PROMPT);
+                let newline = pending.find('\n');
+                match (prompt, newline) {
+                    (Some(pos), newline) if newline.is_none_or(|newline| pos < newline) => {
+                        pending.replace_range(pos..pos + SUDO_PROMPT.len(), "");
+                        
+                        // Answer the first prompt only; closing stdin makes sudo give up on a retry
+                        let Some(mut input) = stdin.take() else {
+                            continue;
+                        };
+                        let password = {
+                            let mut sudo = self.sudo.lock().unwrap();
+                            if sudo.password.is_none() {
+                                sudo.status = SudoStatus::PasswordRequired;
+                                crate::runtime::request_repaint();
+                            }
+                            sudo.password.clone()
+                        };
+                        if let Some(password) = password {
+                            let _ = input.write_all(format!("{}\n", password).as_bytes()).await;
+                            let _ = input.flush().await;
+                            sent_password = true;
+                        }
+                    }
+                    (_, Some(pos)) => {
+                        let line: String = pending.drain(..=pos).collect();
+                        let line = line.trim_end();
+                        if sent_password && (line.contains("Sorry, try again") || line.contains("incorrect password")) {
+                            let mut sudo = self.sudo.lock().unwrap();
+                            sudo.password = None;
+                            sudo.status = SudoStatus::IncorrectPassword;
+                            crate::runtime::request_repaint();
+                        } else if !line.is_empty() {
+                            self.add_stream_line(OutputStream::Stderr, line.to_string(), 0, host).await;
+                        }
+                    }
+                    _ => break,
                 }
-            });
+            }
+        }
+    }
+    
+    // Returns the exit code when the process ended on its own
+    async fn execute_command(&self, spec: CommandSpec, continuous: bool, generation: u64) -> Option<i32> {
+        // Periodic polls share a generation, so each execution is told apart by when it started
+        *self.current_run.lock().unwrap() = now_micros();
+        if spec.fan_out.is_empty() {
+            return self.execute_on_host(spec, continuous, generation, None).await;
+        }
+        
+        // Every host runs at once (each still takes its own command and SSH slots); the run ends when they all have
+        let runs = spec.fan_out.into_iter().map(|(host, spec)| {
+            let executor = self.clone();
+            async move {
+                executor.execute_on_host(spec, continuous, generation, Some(&host)).await
+            }
         });
+        let exit_codes = futures::future::join_all(runs).await;
+        if !continuous && self.is_current_run(generation) {
+            self.add_output("Command completed".to_string(), 0).await;
+        }
+        
+        // Success only if every host succeeded, otherwise the first failure
+        exit_codes.into_iter().try_fold(0, |first_failure, exit_code| {
+            exit_code.map(|code| if first_failure != 0 { first_failure } else { code })
+        })
     }
     
-    async fn execute_command(&self, spec: CommandSpec, continuous: bool) {
-        let mut cmd = Command::new(&spec.program);
-        for arg in &spec.args {
-            cmd.arg(arg);
+    // One process; `host` is set when it's one member of a group run, which reports its own completion
+    async fn execute_on_host(&self, spec: CommandSpec, continuous: bool, generation: u64, host: Option<&str>) -> Option<i32> {
+        // Queue behind other widgets once the shared command slots are used up
+        let slots = crate::runtime::command_slots();
+        let _slot = match slots.try_acquire() {
+            Ok(slot) => slot,
+            Err(_) => {
+                *self.command_queued.lock().unwrap() = true;
+                crate::runtime::request_repaint();
+                let slot = slots.acquire().await;
+                *self.command_queued.lock().unwrap() = false;
+                crate::runtime::request_repaint();
+                slot.ok()?
+            }
+        };
+        if !self.is_current_run(generation) {
+            return None;
         }
         
-        match cmd
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn() {
+        // SSH also waits for a connect slot, held until the session produces output, ends or the grace period passes
+        let ssh_slot = if spec.program == "ssh" {
+            let slots = crate::runtime::ssh_connect_slots();
+            let slot = match slots.try_acquire() {
+                Ok(slot) => slot,
+                Err(_) => {
+                    *self.ssh_queued.lock().unwrap() = true;
+                    crate::runtime::request_repaint();
+                    let slot = slots.acquire().await;
+                    *self.ssh_queued.lock().unwrap() = false;
+                    crate::runtime::request_repaint();
+                    slot.ok()?
+                }
+            };
+            if !self.is_current_run(generation) {
+                return None;
+            }
+            let slot = Arc::new(Mutex::new(Some(slot)));
+            let grace_slot = slot.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(SSH_CONNECT_GRACE).await;
+                grace_slot.lock().unwrap().take();
+            });
+            Some(slot)
+        } else {
+            None
+        };
+        
+        let sudo = self.sudo_enabled();
+        let exit_code = match self.runner.spawn(&spec, sudo) {
             Ok(mut child) => {
-                let stdout = child.stdout.take().unwrap();
-                let mut reader = BufReader::new(stdout).lines();
+                let started = Instant::now();
+                let executor = self.clone();
+                let stderr = child.stderr;
+                let stderr_host = host.map(str::to_string);
+                if sudo {
+                    let stdin = child.stdin.take();
+                    tokio::spawn(async move {
+                        executor.answer_sudo_prompt(stderr, stdin, stderr_host.as_deref()).await;
+                    });
+                } else {
+                    tokio::spawn(async move {
+                        executor.capture_stderr(stderr, stderr_host.as_deref()).await;
+                    });
+                }
+                
+                let mut reader = BufReader::new(child.stdout).lines();
                 let mut line_number = 1i32;
+                let mut ended = false;
+                
+                let mut timed_out = false;
+                
+                // Listen before the first check so a stop in between isn't missed
+                let stopped = self.stop_signal.notified();
+                tokio::pin!(stopped);
+                stopped.as_mut().enable();
+                let deadline = async {
+                    match spec.timeout.filter(|_| !continuous) {
+                        Some(timeout) => tokio::time::sleep(timeout).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::pin!(deadline);
                 
-                while self.is_running() {
-                    match reader.next_line().await {
+                while self.is_current_run(generation) {
+                    let next_line = tokio::select! {
+                        next_line = reader.next_line() => next_line,
+                        _ = &mut stopped => break,
+                        _ = &mut deadline => {
+                            timed_out = true;
+                            break;
+                        }
+                    };
+                    match next_line {
                         Ok(Some(line)) => {
-                            self.add_output(line, line_number);
+                            if let Some(ref slot) = ssh_slot {
+                                slot.lock().unwrap().take();
+                            }
+                            self.add_stream_line(OutputStream::Stdout, line, line_number, host).await;
                             line_number += 1;
                         }
                         Ok(None) => {
                             // Process ended
-                            if !continuous {
-                                self.add_output("Command completed".to_string(), line_number);
+                            if !continuous && host.is_none() {
+                                self.add_output("Command completed".to_string(), line_number).await;
                             }
+                            ended = true;
                             break;
                         }
                         Err(e) => {
-                            self.add_output(format!("Error reading output: {}", e), line_number);
+                            self.add_line(OutputStream::Stdout, format!("Error reading output: {}", e), line_number, true, true, host).await;
                             break;
                         }
                     }
                 }
                 
-                let _ = child.kill().await;
-            }
-            Err(e) => {
-                self.add_output(format!("Failed to execute command: {}", e), 0);
-            }
-        }
+                let exit_code = if ended {
+                    child.process.wait().await
+                } else {
+                    // Give the command a chance to exit cleanly before killing it
+                    child.process.terminate();
+                    if tokio::time::timeout(TERMINATE_GRACE, child.process.wait()).await.is_err() {
+                        child.process.kill().await;
+                    }
+                    if let (true, Some(timeout)) = (timed_out, spec.timeout) {
+                        self.add_line(OutputStream::Stdout, format!("Command timed out after {}s", timeout.as_secs_f32()), line_number, true, true, host).await;
+                    }
+                    None
+                };
+                
+                {
+                    let mut usage = self.usage.lock().unwrap();
+                    usage.runs += 1;
+                    usage.busy += started.elapsed();
+                }
+                
+                exit_code
+            }
+            Err(e) => {
+                self.add_line(OutputStream::Stdout, format!("Failed to execute command: {}", e), 0, true, true, host).await;
+                None
+            }
+        };
         
-        *self.is_running.lock().unwrap() = false;
+        // The grace timer may still hold a handle on the slot
+        if let Some(slot) = ssh_slot {
+            slot.lock().unwrap().take();
+        }
+        exit_code
+    }
+}
+
+// ssh exits with 255 when the connection itself fails or drops
+const SSH_CONNECTION_ERROR: i32 = 255;
+
+// Longest a quiet SSH session keeps its connect slot
+const SSH_CONNECT_GRACE: Duration = Duration::from_secs(10);
+
+// How long a stopped command gets to exit after SIGTERM before it's killed
+const TERMINATE_GRACE: Duration = Duration::from_secs(2);
+
+// Quiet time after an inline config edit before the widget restarts with it
+const CONFIG_SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+// 1s, 2s, 4s... capped at 30s
+fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_secs((1u64 << attempt.saturating_sub(1).min(5)).min(30))
+}
+
+// The command as run on one host: its prefix, then sudo, then SSH unless it's the local machine.
+// `entry` is the configured host, for its connection options
+fn wrap_for(spec: CommandSpec, mode: &ExecutionMode, host: &str, prefix: &str, entry: Option<&crate::database::investigation_db::Host>, sudo: bool) -> CommandSpec {
+    let timeout = spec.timeout;
+    let mut spec = with_command_prefix(spec, prefix);
+    
+    // Wrap with sudo before SSH so it applies on the remote side
+    if sudo {
+        let mut sudo = CommandSpec::new("sudo")
+            .arg("-S")
+            .arg("-p")
+            .arg(SUDO_PROMPT)
+            .arg(spec.program);
+        sudo.args.extend(spec.args);
+        spec = sudo;
+    }
+    
+    // Wrap with SSH if not localhost
+    if is_remote_host(host) {
+        // Convert local command to SSH command, quoted for the remote shell
+        let original_command = remote_command_line(&spec);
+        let mut ssh = CommandSpec::new("ssh");
+        if matches!(mode, ExecutionMode::Continuous) {
+            // Keepalives so a silently dropped connection exits instead of hanging
+            ssh = ssh.arg("-o").arg("ServerAliveInterval=15")
+                .arg("-o").arg("ServerAliveCountMax=3");
+        }
+        let mut destination = entry.map(|entry| entry.ssh_options()).unwrap_or_default();
+        destination.push(host.to_string());
+        ssh.args.extend(crate::ssh_multiplex::options_for(&destination));
+        ssh.args.extend(destination);
+        spec = ssh.arg(original_command);
+    }
+    
+    spec.timeout = timeout;
+    spec
+}
+
+// "docker exec web" + ps aux -> docker exec web ps aux. The prefix is split on whitespace, without quoting
+pub fn with_command_prefix(spec: CommandSpec, prefix: &str) -> CommandSpec {
+    let mut words = prefix.split_whitespace();
+    let Some(program) = words.next() else {
+        return spec;
+    };
+    let mut args: Vec<String> = words.map(String::from).collect();
+    args.push(spec.program);
+    args.extend(spec.args);
+    CommandSpec::new(program).args(args)
+}
+
+// Hosts other than the local machine are reached over SSH
+pub fn is_remote_host(host: &str) -> bool {
+    host != "localhost" && host != "127.0.0.1" && !host.is_empty()
+}
+
+// Quote an argument for a POSIX shell, leaving plain words untouched
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Single command line for a remote shell, with every argument quoted
+pub fn remote_command_line(spec: &CommandSpec) -> String {
+    std::iter::once(&spec.program)
+        .chain(&spec.args)
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Ask the user for a destination and write the lines as a plain text log
+pub fn save_log_file(default_name: &str, lines: &[String]) -> std::io::Result<()> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name(default_name)
+        .add_filter("Log file", &["log", "txt"])
+        .save_file() else {
+        return Ok(());
+    };
+    
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    std::fs::write(path, contents)
+}
+
+// A CSV field, quoted when it holds a comma, quote or line break
+pub fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+pub fn save_table_csv(default_name: &str, headers: &[&str], rows: &[Vec<String>]) -> std::io::Result<()> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name(default_name)
+        .add_filter("CSV", &["csv"])
+        .save_file() else {
+        return Ok(());
+    };
+    
+    let mut contents = headers.join(",");
+    contents.push('\n');
+    for row in rows {
+        let fields: Vec<_> = row.iter().map(|field| csv_field(field)).collect();
+        contents.push_str(&fields.join(","));
+        contents.push('\n');
+    }
+    std::fs::write(path, contents)
+}
+
+// Write recorded samples as timestamp,series,value rows, with timestamps in Unix seconds
+pub fn save_samples_csv(default_name: &str, samples: &[(i64, String, f64)]) -> std::io::Result<()> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name(default_name)
+        .add_filter("CSV", &["csv"])
+        .save_file() else {
+        return Ok(());
+    };
+    
+    let mut contents = String::from("timestamp,series,value\n");
+    for (timestamp, series, value) in samples {
+        contents.push_str(&format!("{}.{:06},{},{}\n", timestamp / 1_000_000, timestamp % 1_000_000, series, value));
+    }
+    std::fs::write(path, contents)
+}
+
+// Drop the oldest entries past `len`
+fn trim_to<T>(lines: &mut Vec<T>, len: usize) {
+    if lines.len() > len {
+        lines.drain(0..lines.len() - len);
+    }
+}
+
+// Consecutive identical lines as (line, count)
+pub fn collapse_repeats<'a>(lines: impl IntoIterator<Item = &'a String>) -> Vec<(&'a str, usize)> {
+    let mut collapsed: Vec<(&str, usize)> = Vec::new();
+    for line in lines {
+        match collapsed.last_mut() {
+            Some((last, count)) if *last == line.as_str() => *count += 1,
+            _ => collapsed.push((line, 1)),
+        }
     }
+    collapsed
 }
 
 // Main trait that command widgets implement
@@ -280,19 +1270,68 @@ pub trait CommandWidget: crate::widgets::Widget {
         self.executor().set_selected_host(host);
     }
     
+    // Optional: the output line limit saved with the widget's config; None keeps the executor's default
+    fn saved_max_lines(&self) -> Option<usize> {
+        None
+    }
+    
+    fn set_saved_max_lines(&mut self, _max: usize) {}
+    
+    // Optional: the streams to show and record saved with the widget's config; None keeps both
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        None
+    }
+    
+    fn set_saved_stream_settings(&mut self, _settings: StreamSettings) {}
+    
+    // Optional: the timestamp column saved with the widget's config; None keeps it off
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        None
+    }
+    
+    fn set_saved_timestamp_display(&mut self, _display: TimestampDisplay) {}
+    
+    // Provided: hand the output options saved with the widget's config to its executor
+    fn apply_saved_settings(&self) {
+        if let Some(max) = self.saved_max_lines() {
+            self.executor().set_max_lines(max);
+        }
+        if let Some(streams) = self.saved_stream_settings() {
+            self.executor().set_stream_settings(streams);
+        }
+        if let Some(display) = self.saved_timestamp_display() {
+            self.executor().set_timestamp_display(display);
+        }
+    }
+    
+    // Provided: put recorded output back under the saved settings
+    fn restore_output(&self, data: Vec<TimedLine>) {
+        self.apply_saved_settings();
+        self.executor().load_historical_output(data);
+    }
+    
+    // Hand over a new host list; a widget running on a host that was removed moves to localhost and restarts there
+    fn update_available_hosts(&self, hosts: Vec<crate::database::investigation_db::Host>) {
+        if self.executor().set_available_hosts(hosts) && self.executor().is_running() {
+            self.stop_command();
+            self.start_command();
+        }
+    }
+    
+    // Provided: follow an edited host, restarting on it so new connection options apply
+    fn follow_host_edit(&self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        let moved = self.executor().retarget_host(from, to);
+        let fell_back = self.executor().set_available_hosts(hosts);
+        if (moved || fell_back) && self.executor().is_running() {
+            self.stop_command();
+            self.start_command();
+        }
+    }
+    
     // Provided: standard start implementation
     fn start_command(&self) {
-        let mut spec = self.build_command();
-        
-        // Wrap with SSH if not localhost
-        let host = self.selected_host();
-        if host != "localhost" && host != "127.0.0.1" && !host.is_empty() {
-            // Convert local command to SSH command
-            let original_command = format!("{} {}", spec.program, spec.args.join(" "));
-            spec = CommandSpec::new("ssh")
-                .arg(&host)
-                .arg(original_command);
-        }
+        self.apply_saved_settings();
+        let spec = self.wrap_for_host(self.build_command(), &self.execution_mode());
         
         match self.execution_mode() {
             ExecutionMode::OneShot => {
@@ -307,15 +1346,47 @@ pub trait CommandWidget: crate::widgets::Widget {
         }
     }
     
+    // Provided: apply the selected host's prefix, sudo and SSH to a command, for widgets running extra commands too
+    fn wrap_for_host(&self, spec: CommandSpec, mode: &ExecutionMode) -> CommandSpec {
+        let sudo = self.executor().sudo_enabled();
+        
+        // A selected group runs the command on each of its hosts, labelled with the host's name
+        if let Some(group) = self.executor().selected_group().filter(|_| self.supports_host_groups()) {
+            let targets = self.executor().group_members(&group).iter()
+                .map(|member| (member.name.clone(), wrap_for(spec.clone(), mode, &member.ssh_alias, &member.command_prefix, Some(member), sudo)))
+                .collect();
+            return CommandSpec::fan_out(targets);
+        }
+        
+        let entry = self.executor().selected_host_entry();
+        wrap_for(spec, mode, &self.selected_host(), &self.executor().command_prefix(), entry.as_ref(), sudo)
+    }
+    
+    // Optional: whether the widget's output still makes sense with lines from several hosts interleaved
+    fn supports_host_groups(&self) -> bool {
+        false
+    }
+    
     // Provided: standard stop
     fn stop_command(&self) {
         self.executor().stop();
     }
     
     
+    // Provided: save the widget config to the investigation it records into
+    fn save_config(&self) where Self: Clone + Into<crate::widgets::WidgetType> {
+        if let Some(ref db) = self.executor().database {
+            let widget: crate::widgets::WidgetType = self.clone().into();
+            if let Err(e) = crate::runtime::runtime().block_on(db.save_widget_instance(&widget)) {
+                eprintln!("Failed to save {} config change: {}", self.widget_type_name(), e);
+            }
+        }
+    }
+    
     // Provided: handle config changes with immediate versioning and optional restart
-    // Note: Database save will be handled by the specific widget implementation
-    fn handle_config_change(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+    // Note: callers save the new config with save_config afterwards
+    fn handle_config_change(&mut self) {
+        let database = self.executor().database.clone();
         let needs_restart = self.needs_restart();
         
         // Stop if restart needed
@@ -346,16 +1417,191 @@ pub trait RefreshableWidget {
     fn set_refresh_interval(&mut self, interval: Duration);
 }
 
+// How a widget's filter text is matched against lines
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum FilterMode {
+    #[default]
+    Substring,  // Case-insensitive
+    Regex,
+}
+
+// A styled stretch of a displayed line, by byte range
+type AnsiRun = (std::ops::Range<usize>, ansi::AnsiStyle);
+
+// A pattern and what compiling it gave
+type CompiledFilter = (FilterMode, String, Result<regex::Regex, String>);
+
+// The filter's regex, compiled when the pattern changes rather than every frame
+#[derive(Clone, Default)]
+pub struct FilterCache(Arc<Mutex<Option<CompiledFilter>>>);
+
+impl FilterCache {
+    // Substring patterns compile to an escaped, case-insensitive regex so matches can be located for highlighting
+    pub fn regex(&self, mode: FilterMode, pattern: &str) -> Result<regex::Regex, String> {
+        let mut cached = self.0.lock().unwrap();
+        match cached.as_ref() {
+            Some((cached_mode, cached_pattern, compiled)) if *cached_mode == mode && cached_pattern == pattern => compiled.clone(),
+            _ => {
+                let compiled = match mode {
+                    FilterMode::Substring => regex::RegexBuilder::new(&regex::escape(pattern)).case_insensitive(true).build(),
+                    FilterMode::Regex => regex::Regex::new(pattern),
+                }.map_err(|e| e.to_string());
+                *cached = Some((mode, pattern.to_string(), compiled.clone()));
+                compiled
+            }
+        }
+    }
+}
+
 // Trait for widgets that filter output
 pub trait FilterableOutput {
     fn filter_pattern(&self) -> &str;
-    fn set_filter_pattern(&mut self, pattern: String);
+    fn filter_mode(&self) -> FilterMode;
+    fn filter_cache(&self) -> &FilterCache;
+    
+    // Why the pattern can't be used; lines aren't filtered meanwhile
+    fn filter_error(&self) -> Option<String> {
+        match self.filter_mode() {
+            FilterMode::Regex if !self.filter_pattern().is_empty() => self.filter_cache().regex(FilterMode::Regex, self.filter_pattern()).err(),
+            _ => None,
+        }
+    }
+    
     fn matches_filter(&self, line: &str) -> bool {
         if self.filter_pattern().is_empty() {
-            true
+            return true;
+        }
+        match self.filter_mode() {
+            FilterMode::Substring => line.to_lowercase().contains(&self.filter_pattern().to_lowercase()),
+            FilterMode::Regex => self.filter_cache().regex(FilterMode::Regex, self.filter_pattern()).map_or(true, |regex| regex.is_match(line)),
+        }
+    }
+    
+    // Byte ranges of every match in `text`, for highlighting
+    fn filter_matches(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        if self.filter_pattern().is_empty() {
+            return vec![];
+        }
+        match self.filter_cache().regex(self.filter_mode(), self.filter_pattern()) {
+            Ok(regex) => regex.find_iter(text)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+}
+
+// Filter box with a substring/regex toggle; returns true when the text was committed or the mode changed
+pub fn render_filter_box(ui: &mut eframe::egui::Ui, text: &mut String, mode: &mut FilterMode, error: Option<String>) -> bool {
+    use eframe::egui;
+    
+    ui.label("Filter:");
+    let committed = ui.text_edit_singleline(text).lost_focus();
+    let toggled = ui.selectable_label(*mode == FilterMode::Regex, ".*")
+        .on_hover_text("Match the filter as a regular expression instead of plain text; add (?i) to ignore case")
+        .clicked();
+    if toggled {
+        *mode = match mode {
+            FilterMode::Substring => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Substring,
+        };
+    }
+    if let Some(error) = error {
+        // regex errors span several lines with a caret under the problem; the last line says what it is
+        let summary = error.lines().last().unwrap_or_default().trim().to_string();
+        ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("⚠ {}", summary))
+            .on_hover_text(egui::RichText::new(error).monospace());
+    }
+    committed || toggled
+}
+
+// Ctrl+F (Cmd+F on macOS) over a widget's output opens its search bar
+const SEARCH_SHORTCUT: eframe::egui::KeyboardShortcut = eframe::egui::KeyboardShortcut::new(eframe::egui::Modifiers::COMMAND, eframe::egui::Key::F);
+
+// Find-in-output state, kept in egui memory per output area
+#[derive(Clone, Default)]
+struct OutputSearch {
+    open: bool,
+    query: String,
+    current: usize,  // Index of the selected occurrence among all matches
+    scroll_pending: bool,
+    cache: FilterCache,
+}
+
+impl OutputSearch {
+    // Case-insensitive occurrences of the query in one displayed line
+    fn occurrences(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        if !self.open || self.query.is_empty() {
+            return vec![];
+        }
+        match self.cache.regex(FilterMode::Substring, &self.query) {
+            Ok(regex) => regex.find_iter(text).map(|found| found.range()).collect(),
+            Err(_) => vec![],
+        }
+    }
+    
+    fn step(&mut self, total: usize, forward: bool) {
+        if total == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % total
         } else {
-            line.to_lowercase().contains(&self.filter_pattern().to_lowercase())
+            (self.current + total - 1) % total
+        };
+        self.scroll_pending = true;
+    }
+    
+    fn render_bar(&mut self, ui: &mut eframe::egui::Ui, total: usize) {
+        use eframe::egui;
+        
+        let hovered = ui.ctx().pointer_hover_pos()
+            .is_some_and(|pos| ui.ctx().layer_id_at(pos) == Some(ui.layer_id()));
+        let opened = hovered && ui.input_mut(|i| i.consume_shortcut(&SEARCH_SHORTCUT));
+        if opened {
+            self.open = true;
+        }
+        if !self.open {
+            return;
         }
+        
+        ui.horizontal(|ui| {
+            ui.label("Find:");
+            let response = ui.text_edit_singleline(&mut self.query);
+            if opened {
+                response.request_focus();
+            }
+            if response.changed() {
+                self.current = 0;
+                self.scroll_pending = true;
+            }
+            // Enter and Shift+Enter move between matches without leaving the box
+            if response.lost_focus() {
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let forward = !ui.input(|i| i.modifiers.shift);
+                    self.step(total, forward);
+                    response.request_focus();
+                } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    self.open = false;
+                }
+            }
+            
+            if total == 0 && !self.query.is_empty() {
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), "No matches");
+            } else if total > 0 {
+                ui.monospace(format!("{}/{}", self.current + 1, total));
+            }
+            if ui.add_enabled(total > 0, egui::Button::new("⏶")).on_hover_text("Previous match (Shift+Enter)").clicked() {
+                self.step(total, false);
+            }
+            if ui.add_enabled(total > 0, egui::Button::new("⏷")).on_hover_text("Next match (Enter)").clicked() {
+                self.step(total, true);
+            }
+            if ui.small_button("✖").on_hover_text("Close search (Esc)").clicked() {
+                self.open = false;
+            }
+        });
     }
 }
 
@@ -363,29 +1609,202 @@ pub trait FilterableOutput {
 pub trait CommandOutputRenderer {
     fn executor(&self) -> &CommandExecutor;
     
+    // Lines hidden by the widget's filter are left out of the default rendering
+    fn shows_line(&self, _line: &str) -> bool {
+        true
+    }
+    
+    // Spans of a displayed line to highlight, such as what the filter matched
+    fn highlights(&self, _text: &str) -> Vec<std::ops::Range<usize>> {
+        vec![]
+    }
+    
+    // Keep the newest line in view as output arrives
+    fn follows_tail(&self) -> bool {
+        true
+    }
+    
     fn render_output(&self, ui: &mut eframe::egui::Ui) {
         use eframe::egui;
         
+        let redactor = self.executor().redactor();
+        let output = self.executor().output.lock().unwrap();
+        let streams = self.executor().line_streams(output.len());
+        let times = self.executor().line_times(output.len());
+        let timestamps = self.executor().timestamp_display();
+        let first = times.iter().flatten().next().copied().unwrap_or_default();
+        
+        // What's on screen: each displayed line with the index of its first output line. Filters see
+        // the text as it's shown, so escape codes only count when they're shown as text too
+        let raw_escapes = self.executor().shows_raw_escapes();
+        let visible: Vec<usize> = (0..output.len())
+            .filter(|&index| if raw_escapes {
+                self.shows_line(&ansi::show_escapes(&output[index]))
+            } else {
+                self.shows_line(&ansi::strip(&output[index]))
+            })
+            .collect();
+        let rows: Vec<(String, usize)> = if self.executor().collapses_repeats() {
+            let mut position = 0;
+            collapse_repeats(visible.iter().map(|&index| &output[index]))
+                .into_iter()
+                .map(|(line, count)| {
+                    let text = if count > 1 {
+                        format!("{} (×{})", redactor.for_display(line), count)
+                    } else {
+                        redactor.for_display(line).into_owned()
+                    };
+                    let row = (text, visible[position]);
+                    position += count;
+                    row
+                })
+                .collect()
+        } else {
+            visible.iter().map(|&index| (redactor.for_display(&output[index]).into_owned(), index)).collect()
+        };
+        drop(output);
+        let rows: Vec<(String, Vec<AnsiRun>, usize)> = rows.into_iter()
+            .map(|(text, index)| if raw_escapes {
+                (ansi::show_escapes(&text).into_owned(), vec![], index)
+            } else {
+                let (text, runs) = ansi::parse(&text);
+                (text, runs, index)
+            })
+            .collect();
+        
+        // Search runs over the displayed lines, so it only finds what the filter lets through
+        let search_id = ui.id().with("output_search");
+        let mut search = ui.data_mut(|d| d.get_temp::<OutputSearch>(search_id)).unwrap_or_default();
+        let found: Vec<Vec<std::ops::Range<usize>>> = rows.iter().map(|(text, _, _)| search.occurrences(text)).collect();
+        let total = found.iter().map(Vec::len).sum();
+        search.render_bar(ui, total);
+        search.current = search.current.min(total.saturating_sub(1));
+        
+        let text_color = match ui.visuals().override_text_color {
+            Some(color) => color,
+            None => ui.visuals().text_color(),
+        };
+        let strong_color = ui.visuals().strong_text_color();
+        let styled = |text: &str, runs: &[AnsiRun], index: usize, highlights: Vec<(std::ops::Range<usize>, egui::Color32)>| -> egui::WidgetText {
+            let prefix = match times[index].and_then(|time| timestamps.format(time, first)) {
+                Some(time) => format!("{} ", time),
+                None => String::new(),
+            };
+            let color = match streams[index] {
+                OutputStream::Stderr => egui::Color32::from_rgb(220, 80, 80),
+                OutputStream::Stdout => text_color,
+            };
+            if highlights.is_empty() && runs.is_empty() {
+                return egui::RichText::new(prefix + text).monospace().size(12.0).color(color).into();
+            }
+            
+            let plain = egui::TextFormat::simple(egui::FontId::monospace(12.0), color);
+            let mut job = egui::text::LayoutJob::default();
+            job.append(&prefix, 0.0, plain.clone());
+            // Cut the text wherever a styled run or a highlight starts or ends; highlights win over colors
+            let mut cuts: Vec<usize> = runs.iter().map(|(range, _)| range)
+                .chain(highlights.iter().map(|(range, _)| range))
+                .flat_map(|range| [range.start, range.end])
+                .chain([0, text.len()])
+                .collect();
+            cuts.sort_unstable();
+            cuts.dedup();
+            for piece in cuts.windows(2) {
+                let (start, end) = (piece[0], piece[1]);
+                let mut format = plain.clone();
+                if let Some((_, style)) = runs.iter().find(|(range, _)| range.contains(&start)) {
+                    format.color = style.foreground.unwrap_or(if style.bold { strong_color } else { color });
+                    if style.dim {
+                        format.color = format.color.gamma_multiply(0.6);
+                    }
+                    format.background = style.background.unwrap_or_default();
+                    format.italics = style.italic;
+                    if style.underline {
+                        format.underline = egui::Stroke::new(1.0_f32, format.color);
+                    }
+                }
+                if let Some((_, background)) = highlights.iter().find(|(range, _)| range.contains(&start)) {
+                    format.background = *background;
+                    format.color = egui::Color32::BLACK;
+                }
+                job.append(&text[start..end], 0.0, format);
+            }
+            job.into()
+        };
+        
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
-            .stick_to_bottom(true)
+            .stick_to_bottom(self.follows_tail())
             .show(ui, |ui| {
-                let output = self.executor().output.lock().unwrap();
-                for line in output.iter() {
-                    ui.label(egui::RichText::new(line).monospace().size(12.0));
+                let mut occurrence = 0;
+                for ((text, runs, index), found) in rows.iter().zip(found) {
+                    let count = found.len();
+                    let selected = (occurrence..occurrence + count).contains(&search.current);
+                    // While searching, occurrences are marked instead of filter matches
+                    let highlights = if search.open && !search.query.is_empty() {
+                        found.into_iter()
+                            .enumerate()
+                            .map(|(nth, range)| {
+                                let background = if occurrence + nth == search.current {
+                                    egui::Color32::from_rgb(250, 150, 50)
+                                } else {
+                                    egui::Color32::from_rgb(250, 220, 80)
+                                };
+                                (range, background)
+                            })
+                            .collect()
+                    } else {
+                        self.highlights(text).into_iter()
+                            .map(|range| (range, egui::Color32::from_rgb(250, 220, 80)))
+                            .collect()
+                    };
+                    occurrence += count;
+                    let response = ui.label(styled(text, runs, *index, highlights));
+                    if selected && search.scroll_pending {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                        search.scroll_pending = false;
+                    }
                 }
             });
+        
+        ui.data_mut(|d| d.insert_temp(search_id, search));
     }
 }
 
 pub trait CommandControlBar: CommandWidget {
-    fn render_controls(&mut self, ui: &mut eframe::egui::Ui) -> bool {
+    // Column names and the rows currently shown, for widgets that display a table; offered as CSV under "Save output"
+    fn table_export(&self) -> Option<(Vec<&'static str>, Vec<Vec<String>>)> {
+        None
+    }
+    
+    fn render_controls(&mut self, ui: &mut eframe::egui::Ui) -> bool
+    where Self: Clone + Into<crate::widgets::WidgetType> {
         use eframe::egui;
         
         let mut refresh_clicked = false;
         let is_running = self.executor().is_running();
         
-        if is_running {
+        if let (true, Some(attempt)) = (is_running, self.executor().reconnect_attempt()) {
+            ui.spinner();
+            ui.colored_label(egui::Color32::from_rgb(230, 160, 60), format!("Reconnecting... (attempt {})", attempt));
+            if ui.button("Stop").clicked() {
+                self.stop_command();
+            }
+        } else if is_running && self.executor().is_command_queued() {
+            ui.spinner();
+            ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "Queued")
+                .on_hover_text(format!("Waiting for a free command slot; at most {} commands run at once", crate::runtime::MAX_RUNNING_COMMANDS));
+            if ui.button("Stop").clicked() {
+                self.stop_command();
+            }
+        } else if is_running && self.executor().is_ssh_queued() {
+            ui.spinner();
+            ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "Queued")
+                .on_hover_text("Waiting for another SSH connection to finish setting up; the limit is in Settings");
+            if ui.button("Stop").clicked() {
+                self.stop_command();
+            }
+        } else if is_running {
             ui.spinner();
             ui.label("Running...");
             if ui.button("Stop").clicked() {
@@ -403,6 +1822,116 @@ pub trait CommandControlBar: CommandWidget {
             self.executor().clear_output();
         }
         
+        ui.menu_button("Save output", |ui| {
+            let mut full_history = None;
+            if ui.button("Visible lines").clicked() {
+                full_history = Some(false);
+            }
+            let has_history = self.executor().database.is_some();
+            if ui.add_enabled(has_history, egui::Button::new("Full recorded history")).clicked() {
+                full_history = Some(true);
+            }
+            if let Some((headers, rows)) = self.table_export() {
+                ui.separator();
+                if ui.add_enabled(!rows.is_empty(), egui::Button::new("Table as CSV"))
+                    .on_hover_text("The rows as shown, with the current sort and filters")
+                    .clicked() {
+                    ui.close_menu();
+                    let default_name = format!("{}_{}.csv", self.widget_type_name(), self.widget_id());
+                    if let Err(e) = save_table_csv(&default_name, &headers, &rows) {
+                        eprintln!("Failed to save table: {}", e);
+                    }
+                }
+            }
+            
+            if let Some(full_history) = full_history {
+                ui.close_menu();
+                let default_name = format!("{}_{}.log", self.widget_type_name(), self.widget_id());
+                match self.executor().export_lines(full_history) {
+                    Ok(lines) => {
+                        if let Err(e) = save_log_file(&default_name, &lines) {
+                            eprintln!("Failed to save output: {}", e);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to load recorded output: {}", e),
+                }
+            }
+        });
+        
+        ui.menu_button("Streams", |ui| {
+            let mut streams = self.executor().stream_settings();
+            let before = streams;
+            
+            ui.label("Show:");
+            for selection in StreamSelection::ALL {
+                ui.radio_value(&mut streams.display, *selection, selection.label());
+            }
+            ui.separator();
+            ui.label("Record:");
+            for selection in StreamSelection::ALL {
+                ui.radio_value(&mut streams.record, *selection, selection.label());
+            }
+            
+            if before != streams {
+                self.executor().set_stream_settings(streams);
+                self.set_saved_stream_settings(streams);
+                self.save_config();
+            }
+            
+            ui.separator();
+            let mut collapse = self.executor().collapses_repeats();
+            if ui.checkbox(&mut collapse, "Collapse repeated lines")
+                .on_hover_text("Show identical consecutive lines once with a count; everything is still recorded")
+                .changed() {
+                self.executor().set_collapse_repeats(collapse);
+            }
+            let mut raw_escapes = self.executor().shows_raw_escapes();
+            if ui.checkbox(&mut raw_escapes, "Show escape codes")
+                .on_hover_text("Show ANSI color and cursor codes as text instead of coloring the output with them")
+                .changed() {
+                self.executor().set_show_raw_escapes(raw_escapes);
+            }
+            
+            ui.separator();
+            ui.label("Timestamps:");
+            let mut timestamps = self.executor().timestamp_display();
+            for display in TimestampDisplay::ALL {
+                if ui.radio_value(&mut timestamps, *display, display.label()).changed() {
+                    self.executor().set_timestamp_display(timestamps);
+                    self.set_saved_timestamp_display(timestamps);
+                    self.save_config();
+                }
+            }
+            
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Keep");
+                let mut max_lines = self.executor().max_lines();
+                let response = ui.add(egui::DragValue::new(&mut max_lines).range(100..=1_000_000).speed(10.0).suffix(" lines"))
+                    .on_hover_text("Output lines kept in memory; lowering it drops the oldest now. Recording is unaffected");
+                if response.changed() {
+                    self.executor().set_max_lines(max_lines);
+                    self.set_saved_max_lines(max_lines);
+                }
+                if response.drag_stopped() || response.lost_focus() {
+                    self.save_config();
+                }
+            });
+        });
+        
+        // Received volume and rate, so long or remote runs don't look hung
+        let stats = self.executor().transfer_stats();
+        if stats.started.is_some() {
+            let host = self.selected_host();
+            let source = match self.executor().selected_group().filter(|_| self.supports_host_groups()) {
+                Some(group) => format!("⇣ 👥 {} ", group),
+                None if is_remote_host(&host) => format!("⇣ {} ", host),
+                None => String::new(),
+            };
+            ui.label(format!("{}{} lines · {} · {}/s", source, stats.lines, format_bytes(stats.bytes as f64), format_bytes(stats.rate())))
+                .on_hover_text("Output received since the command was started");
+        }
+        
         ui.separator();
         
         // Host selection
@@ -421,12 +1950,41 @@ pub trait CommandControlBar: CommandWidget {
                 name: "localhost".to_string(),
                 ssh_alias: "localhost".to_string(),
                 description: "Local machine".to_string(),
+                command_prefix: String::new(),
+                port: None,
+                username: None,
+                identity_file: None,
+                group: String::new(),
                 is_localhost: true,
             });
         }
         
+        // Hosts can share an alias and differ only by prefix (e.g. two containers), so both identify the selection
+        let current_prefix = self.executor().command_prefix();
+        let mut selected_prefix = current_prefix.clone();
+        let current_group = self.executor().selected_group().filter(|_| self.supports_host_groups());
+        let mut selected_group = current_group.clone();
+        let selected_text = match current_group {
+            Some(ref group) => format!("👥 {}", group),
+            None => available_hosts.iter()
+                .find(|h| h.ssh_alias == selected_host && h.command_prefix == selected_prefix && !h.command_prefix.is_empty())
+                .map(|h| h.name.clone())
+                .unwrap_or_else(|| crate::redaction::mask_secrets(&selected_host).into_owned()),
+        };
+        
+        // Groups in order of first appearance, with their sizes
+        let mut groups: Vec<(String, usize)> = Vec::new();
+        if self.supports_host_groups() {
+            for host in available_hosts.iter().filter(|h| !h.group.is_empty()) {
+                match groups.iter_mut().find(|(group, _)| *group == host.group) {
+                    Some((_, count)) => *count += 1,
+                    None => groups.push((host.group.clone(), 1)),
+                }
+            }
+        }
+        
         egui::ComboBox::from_id_salt(format!("host_selector_{}", self.widget_id()))
-            .selected_text(&selected_host)
+            .selected_text(selected_text)
             .width(150.0)
             .show_ui(ui, |ui| {
                 for host in &available_hosts {
@@ -435,12 +1993,30 @@ pub trait CommandControlBar: CommandWidget {
                     } else {
                         format!("🖥️ {}", host.name)
                     };
-                    ui.selectable_value(&mut selected_host, host.ssh_alias.clone(), label);
+                    let selected = selected_group.is_none() && host.ssh_alias == selected_host && host.command_prefix == selected_prefix;
+                    if ui.selectable_label(selected, label).clicked() {
+                        selected_host = host.ssh_alias.clone();
+                        selected_prefix = host.command_prefix.clone();
+                        selected_group = None;
+                    }
+                }
+                if !groups.is_empty() {
+                    ui.separator();
+                }
+                for (group, count) in &groups {
+                    let selected = selected_group.as_ref() == Some(group);
+                    if ui.selectable_label(selected, format!("👥 {} ({} hosts)", group, count))
+                        .on_hover_text("Run on every host in the group at once")
+                        .clicked() {
+                        selected_group = Some(group.clone());
+                    }
                 }
             });
         
-        if selected_host != current_host {
+        if selected_host != current_host || selected_prefix != current_prefix || selected_group != current_group {
+            self.executor().set_command_prefix(selected_prefix);
             self.set_selected_host(selected_host);
+            self.executor().set_selected_group(selected_group);
             // Restart if running with new host
             if is_running {
                 self.stop_command();
@@ -449,6 +2025,501 @@ pub trait CommandControlBar: CommandWidget {
             }
         }
         
+        if self.render_sudo_controls(ui) {
+            refresh_clicked = true;
+        }
+        
         refresh_clicked
     }
-}
\ No newline at end of file
+    
+    // Sudo toggle plus a masked password field when sudo asks for one; returns true if restarted
+    fn render_sudo_controls(&mut self, ui: &mut eframe::egui::Ui) -> bool {
+        use eframe::egui;
+        
+        let mut restart = false;
+        
+        let mut use_sudo = self.executor().sudo_enabled();
+        if ui.checkbox(&mut use_sudo, "sudo").on_hover_text("Run the command with sudo (on the selected host)").changed() {
+            self.executor().set_sudo_enabled(use_sudo);
+            restart = self.executor().is_running();
+        }
+        
+        let status = self.executor().sudo_status();
+        if use_sudo && status != SudoStatus::Ok {
+            let message = match status {
+                SudoStatus::IncorrectPassword => "Incorrect password:",
+                _ => "Password:",
+            };
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), message);
+            
+            let id = egui::Id::new(format!("sudo_password_{}", self.widget_id()));
+            let mut password = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_default();
+            let response = ui.add(egui::TextEdit::singleline(&mut password)
+                .password(true)
+                .desired_width(120.0));
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            
+            if (ui.button("Run").clicked() || submitted) && !password.is_empty() {
+                self.executor().set_sudo_password(std::mem::take(&mut password));
+                restart = true;
+            }
+            ui.data_mut(|d| d.insert_temp(id, password));
+        }
+        
+        if restart {
+            self.stop_command();
+            self.start_command();
+        }
+        
+        restart
+    }
+    
+    // Polling interval for monitors; bumps the version, restarts and saves on change
+    fn render_interval_control(&mut self, ui: &mut eframe::egui::Ui)
+    where Self: RefreshableWidget + Clone + Into<crate::widgets::WidgetType> {
+        use eframe::egui;
+        
+        ui.label("Interval:");
+        let mut secs = self.refresh_interval().as_secs();
+        let changed = ui.add(egui::DragValue::new(&mut secs).range(1..=60).suffix("s")).changed();
+        if changed {
+            self.set_refresh_interval(Duration::from_secs(secs));
+        }
+        if self.config_settled(ui, changed) {
+            self.handle_config_change();
+            self.save_config();
+        }
+    }
+    
+    // True once, when inline config edits have stopped for CONFIG_SETTLE_DELAY, so dragging
+    // a value restarts the command once instead of every frame. Call every frame.
+    fn config_settled(&self, ui: &eframe::egui::Ui, changed: bool) -> bool {
+        use eframe::egui;
+        
+        let id = egui::Id::new(format!("config_settle_{}_{}", self.widget_type_name(), self.widget_id()));
+        let now = ui.input(|i| i.time);
+        if changed {
+            ui.data_mut(|d| d.insert_temp(id, now + CONFIG_SETTLE_DELAY.as_secs_f64()));
+            ui.ctx().request_repaint_after(CONFIG_SETTLE_DELAY);
+        }
+        
+        match ui.data(|d| d.get_temp::<f64>(id)) {
+            Some(deadline) if now >= deadline => {
+                ui.data_mut(|d| d.remove::<f64>(id));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::command_runner::FakeRunner;
+    
+    // Poll until the background run gets there, failing after a few seconds
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out waiting for the executor");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+    
+    fn output(executor: &CommandExecutor) -> Vec<String> {
+        executor.output.lock().unwrap().clone()
+    }
+    
+    #[test]
+    fn one_shot_output_is_buffered_in_order() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&["first", "second"]));
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running());
+        
+        assert_eq!(output(&executor), vec!["first", "second", "Command completed"]);
+        assert_eq!(executor.transfer_stats().lines, 2);
+        assert_eq!(executor.usage().runs, 1);
+    }
+    
+    #[test]
+    fn buffer_keeps_only_the_newest_lines() {
+        let executor = CommandExecutor::new()
+            .with_max_lines(3)
+            .with_runner(FakeRunner::new(&["a", "b", "c", "d", "e"]));
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running());
+        
+        assert_eq!(output(&executor), vec!["d", "e", "Command completed"]);
+        // Trimming only affects the display buffer, not the counters
+        assert_eq!(executor.transfer_stats().lines, 5);
+    }
+    
+    #[test]
+    fn output_revision_moves_with_the_buffer() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&["a"]));
+        let start = executor.output_revision();
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running());
+        let after_run = executor.output_revision();
+        assert!(after_run > start);
+        
+        executor.clear_output();
+        assert!(executor.output_revision() > after_run);
+    }
+    
+    #[test]
+    fn hidden_stream_is_not_buffered() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&[]).with_stderr(&["oops"]));
+        executor.set_stream_settings(StreamSettings {
+            display: StreamSelection::StdoutOnly,
+            record: StreamSelection::Both,
+        });
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running());
+        
+        assert_eq!(output(&executor), vec!["Command completed"]);
+    }
+    
+    #[test]
+    fn stderr_lines_are_tagged_with_their_stream() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&["out"]).with_stderr(&["oops"]));
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running() && output(&executor).len() == 3);
+        
+        let lines = output(&executor);
+        let streams = executor.line_streams(lines.len());
+        for (line, stream) in lines.iter().zip(streams) {
+            let expected = if line == "oops" { OutputStream::Stderr } else { OutputStream::Stdout };
+            assert_eq!(stream, expected, "{}", line);
+        }
+    }
+    
+    #[test]
+    fn periodic_runs_start_from_a_clear_buffer() {
+        let runner = FakeRunner::new(&["x", "y"]);
+        let executor = CommandExecutor::new().with_runner(runner.clone());
+        executor.run_periodic(CommandSpec::new("fake"), Duration::from_millis(10));
+        wait_until(|| runner.spawn_count() >= 3);
+        executor.stop();
+        
+        // Each run replaces the buffer, the last complete one is kept for comparison
+        assert!(output(&executor).len() <= 3);
+        assert_eq!(*executor.previous_output.lock().unwrap(), vec!["x", "y", "Command completed"]);
+    }
+    
+    #[test]
+    fn periodic_schedule_does_not_drift_with_run_time() {
+        // On a paused clock, so the spacing is exact; sleeping the interval after each run would space them 70ms apart
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+        let runner = FakeRunner::new(&["x"]).with_run_time(Duration::from_millis(30));
+        let executor = CommandExecutor::new().with_runner(runner.clone());
+        rt.block_on(async {
+            let generation = executor.begin_run();
+            tokio::spawn(executor.clone().run_on_schedule(CommandSpec::new("fake"), Duration::from_millis(40), generation));
+            tokio::time::sleep(Duration::from_millis(390)).await;
+            executor.stop();
+        });
+        
+        let spawn_times = runner.spawn_times.lock().unwrap();
+        assert_eq!(spawn_times.len(), 10);
+        for pair in spawn_times.windows(2) {
+            assert_eq!(pair[1] - pair[0], Duration::from_millis(40));
+        }
+    }
+    
+    #[test]
+    fn exit_code_is_captured() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&["done"]).with_exit_code(Some(3)));
+        let generation = executor.begin_run();
+        let exit_code = crate::runtime::runtime().block_on(executor.execute_command(CommandSpec::new("fake"), false, generation));
+        
+        assert_eq!(exit_code, Some(3));
+    }
+    
+    #[test]
+    fn stopped_run_reports_no_exit_code() {
+        let executor = CommandExecutor::new().with_runner(FakeRunner::new(&["ignored"]));
+        let generation = executor.begin_run();
+        executor.stop();
+        let exit_code = crate::runtime::runtime().block_on(executor.execute_command(CommandSpec::new("fake"), false, generation));
+        
+        assert_eq!(exit_code, None);
+        assert!(output(&executor).is_empty());
+    }
+    
+    #[test]
+    fn stop_ends_a_run_that_is_waiting_for_output() {
+        let runner = FakeRunner::new(&["first"]).holding_output_open();
+        let executor = CommandExecutor::new().with_runner(runner.clone());
+        executor.run_continuous(CommandSpec::new("fake"));
+        wait_until(|| output(&executor) == vec!["first"]);
+        
+        executor.stop();
+        wait_until(|| *runner.terminated.lock().unwrap() == 1);
+    }
+    
+    #[test]
+    fn hung_command_is_killed_at_its_timeout() {
+        let runner = FakeRunner::new(&["started"]).holding_output_open();
+        let executor = CommandExecutor::new().with_runner(runner.clone());
+        executor.run_once(CommandSpec::new("fake").timeout(Duration::from_millis(50)));
+        wait_until(|| !executor.is_running());
+        
+        assert_eq!(output(&executor), vec!["started", "Command timed out after 0.05s"]);
+        assert_eq!(*runner.terminated.lock().unwrap(), 1);
+    }
+    
+    #[test]
+    fn only_consecutive_repeats_are_collapsed() {
+        let lines: Vec<String> = ["a", "err", "err", "err", "b", "err"].iter().map(|l| l.to_string()).collect();
+        assert_eq!(collapse_repeats(&lines), vec![("a", 1), ("err", 3), ("b", 1), ("err", 1)]);
+    }
+    
+    #[test]
+    fn recorded_lines_reach_the_database_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::runtime::runtime().block_on(async {
+            let db = crate::database::investigation_db::InvestigationDB::create(&dir.path().join("test.skop"), "Test", "", &[0.1, 0.2, 0.3]).await.unwrap();
+            db.save_widget_instance(&crate::widgets::WidgetType::new_raw_command(1)).await.unwrap();
+            Arc::new(db)
+        });
+        
+        // More than one batch's worth
+        let lines: Vec<String> = (1..=1200).map(|i| format!("line {}", i)).collect();
+        let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut executor = CommandExecutor::new().with_runner(FakeRunner::new(&line_refs));
+        executor.set_database(Some(db.clone()), 1, 0);
+        executor.run_once(CommandSpec::new("fake"));
+        wait_until(|| !executor.is_running());
+        
+        let mut expected = lines.clone();
+        expected.push("Command completed".to_string());
+        wait_until(|| crate::runtime::runtime().block_on(db.get_widget_data(1, 0)).unwrap().len() == expected.len());
+        assert_eq!(crate::runtime::runtime().block_on(db.get_widget_data(1, 0)).unwrap(), expected);
+    }
+    
+    struct Filter(&'static str, FilterMode, FilterCache);
+    
+    impl FilterableOutput for Filter {
+        fn filter_pattern(&self) -> &str {
+            self.0
+        }
+        
+        fn filter_mode(&self) -> FilterMode {
+            self.1
+        }
+        
+        fn filter_cache(&self) -> &FilterCache {
+            &self.2
+        }
+    }
+    
+    #[test]
+    fn filters_match_substrings_or_regexes() {
+        let substring = Filter("error", FilterMode::Substring, FilterCache::default());
+        assert!(substring.matches_filter("ERROR: disk full"));
+        assert!(!substring.matches_filter("all good"));
+        
+        let regex = Filter(r"^\w+ 5\d\d ", FilterMode::Regex, FilterCache::default());
+        assert!(regex.matches_filter("GET 503 /api"));
+        assert!(!regex.matches_filter("GET 200 /api"));
+        assert_eq!(regex.filter_error(), None);
+        
+        // A pattern that doesn't compile hides nothing and says why
+        let invalid = Filter("(unclosed", FilterMode::Regex, FilterCache::default());
+        assert!(invalid.matches_filter("anything"));
+        assert!(invalid.filter_error().is_some());
+    }
+    
+    #[test]
+    fn filter_matches_cover_every_occurrence() {
+        let substring = Filter("err", FilterMode::Substring, FilterCache::default());
+        assert_eq!(substring.filter_matches("Err: stderr (1+1)"), vec![0..3, 8..11]);
+        
+        // Substring patterns are literal even when they look like regexes
+        let literal = Filter("(1+1)", FilterMode::Substring, FilterCache::default());
+        assert_eq!(literal.filter_matches("Err: stderr (1+1)"), vec![12..17]);
+        
+        let regex = Filter(r"\d+", FilterMode::Regex, FilterCache::default());
+        assert_eq!(regex.filter_matches("GET 503 in 12ms"), vec![4..7, 11..13]);
+        
+        // Empty matches have nothing to highlight
+        let empty = Filter("x*", FilterMode::Regex, FilterCache::default());
+        assert!(empty.filter_matches("abc").is_empty());
+        
+        let invalid = Filter("(unclosed", FilterMode::Regex, FilterCache::default());
+        assert!(invalid.filter_matches("(unclosed").is_empty());
+    }
+    
+    #[test]
+    fn search_finds_every_occurrence_and_wraps_around() {
+        let mut search = OutputSearch { open: true, query: "Ab".to_string(), ..Default::default() };
+        assert_eq!(search.occurrences("ab xAB ba"), vec![0..2, 4..6]);
+        
+        search.step(3, false);
+        assert_eq!(search.current, 2);
+        search.step(3, true);
+        assert_eq!(search.current, 0);
+        assert!(search.scroll_pending);
+        
+        // A closed search bar marks nothing
+        search.open = false;
+        assert!(search.occurrences("ab").is_empty());
+    }
+    
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("nginx"), "nginx");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+    
+    #[test]
+    fn reloaded_lines_keep_their_recorded_times_and_streams() {
+        let executor = CommandExecutor::new().with_max_lines(2);
+        executor.load_historical_output(vec![
+            (1, "a".to_string(), OutputStream::Stdout),
+            (2, "b".to_string(), OutputStream::Stderr),
+            (3, "c".to_string(), OutputStream::Stdout),
+        ]);
+        assert_eq!(output(&executor), vec!["b", "c"]);
+        assert_eq!(executor.line_times(2), vec![Some(2), Some(3)]);
+        assert_eq!(executor.line_streams(2), vec![OutputStream::Stderr, OutputStream::Stdout]);
+        
+        executor.clear_output();
+        assert_eq!(executor.line_times(1), vec![None]);
+    }
+    
+    #[test]
+    fn lowering_the_line_limit_trims_the_buffer_at_once() {
+        let executor = CommandExecutor::new();
+        executor.load_historical_output((1..=5).map(|n| (n, n.to_string(), OutputStream::Stdout)).collect());
+        
+        executor.set_max_lines(3);
+        assert_eq!(output(&executor), vec!["3", "4", "5"]);
+        assert_eq!(executor.line_times(3), vec![Some(3), Some(4), Some(5)]);
+        
+        // Raising it keeps what's left and lets later lines build up again
+        executor.set_max_lines(10);
+        executor.load_historical_output((1..=5).map(|n| (n, n.to_string(), OutputStream::Stdout)).collect());
+        assert_eq!(output(&executor).len(), 5);
+    }
+    
+    #[test]
+    fn timestamps_format_relative_to_the_first_line_or_as_time_of_day() {
+        let first = 1_700_000_000_000_000;
+        assert_eq!(TimestampDisplay::Off.format(first, first), None);
+        assert_eq!(TimestampDisplay::Relative.format(first + 61_234_567, first).as_deref(), Some("+1:01.234"));
+        // 2023-11-14 22:13:20 UTC
+        assert_eq!(format_time_of_day(first + 5_000), "22:13:20.005");
+    }
+    
+    #[test]
+    fn command_prefix_goes_in_front_of_the_program() {
+        let spec = with_command_prefix(CommandSpec::new("ps").arg("aux"), " docker  exec web ");
+        assert_eq!(spec.program, "docker");
+        assert_eq!(spec.args, vec!["exec", "web", "ps", "aux"]);
+        
+        let unchanged = with_command_prefix(CommandSpec::new("ps").arg("aux"), "");
+        assert_eq!(unchanged.program, "ps");
+        assert_eq!(unchanged.args, vec!["aux"]);
+    }
+    
+    #[test]
+    fn plain_arguments_are_not_quoted() {
+        assert_eq!(shell_quote("vmstat"), "vmstat");
+        assert_eq!(shell_quote("-an"), "-an");
+    }
+    
+    #[test]
+    fn empty_and_spaced_arguments_are_quoted() {
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("uname -a && sw_vers"), "'uname -a && sw_vers'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+    
+    #[test]
+    fn remote_command_line_survives_a_shell_round_trip() {
+        // The remote side runs the line through a shell, simulate that locally
+        let spec = CommandSpec::new("printf")
+            .arg("%s|")
+            .arg("a b")
+            .arg("it's")
+            .arg("$HOME")
+            .arg("");
+        let line = remote_command_line(&spec);
+        let output = std::process::Command::new("sh").arg("-c").arg(&line).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a b|it's|$HOME||");
+    }
+    
+    #[test]
+    fn command_substitution_and_quotes_reach_the_remote_side_literally() {
+        let spec = CommandSpec::new("printf")
+            .arg("%s|")
+            .arg("$(touch /tmp/skop-injected)")
+            .arg("`id`")
+            .arg("\"foo bar\"")
+            .arg("a; b");
+        let line = remote_command_line(&spec);
+        assert_eq!(line, "printf '%s|' '$(touch /tmp/skop-injected)' '`id`' '\"foo bar\"' 'a; b'");
+        let output = std::process::Command::new("sh").arg("-c").arg(&line).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "$(touch /tmp/skop-injected)|`id`|\"foo bar\"|a; b|");
+    }
+    
+    #[test]
+    fn removing_the_selected_host_falls_back_to_localhost() {
+        let host = crate::database::investigation_db::Host::for_test;
+        let executor = CommandExecutor::new();
+        executor.set_selected_host("web-1".to_string());
+        executor.set_command_prefix("docker exec api".to_string());
+        
+        assert!(!executor.set_available_hosts(vec![host("web-1", "docker exec api"), host("web-2", "")]));
+        assert_eq!(executor.get_selected_host(), "web-1");
+        
+        // Same alias, but the container entry is gone
+        assert!(executor.set_available_hosts(vec![host("web-1", "")]));
+        assert_eq!(executor.get_selected_host(), "localhost");
+        assert_eq!(executor.command_prefix(), "");
+        assert!(!executor.set_available_hosts(vec![]));
+    }
+    
+    #[test]
+    fn editing_the_selected_host_moves_to_its_new_alias() {
+        let host = crate::database::investigation_db::Host::for_test;
+        let executor = CommandExecutor::new();
+        executor.set_available_hosts(vec![host("web-1", "docker exec api")]);
+        executor.set_selected_host("web-1".to_string());
+        executor.set_command_prefix("docker exec api".to_string());
+        
+        let renamed = host("web-01", "docker exec api-v2");
+        assert!(executor.retarget_host(&host("web-1", "docker exec api"), &renamed));
+        assert!(!executor.set_available_hosts(vec![renamed]));
+        assert_eq!(executor.get_selected_host(), "web-01");
+        assert_eq!(executor.command_prefix(), "docker exec api-v2");
+        
+        // Widgets on other hosts stay where they are
+        assert!(!executor.retarget_host(&host("db-1", ""), &host("db-2", "")));
+        assert_eq!(executor.get_selected_host(), "web-01");
+    }
+    
+    #[test]
+    fn group_runs_merge_every_hosts_output_under_its_name() {
+        let runner = FakeRunner::new(&["up 3 days"]).with_stderr(&["warning"]);
+        let executor = CommandExecutor::new().with_runner(runner.clone());
+        executor.run_once(CommandSpec::fan_out(vec![
+            ("web-1".to_string(), CommandSpec::new("ssh").arg("web-1").arg("uptime")),
+            ("web-2".to_string(), CommandSpec::new("ssh").arg("web-2").arg("uptime")),
+        ]));
+        wait_until(|| !executor.is_running() && output(&executor).len() == 5);
+        
+        // stderr is read alongside, so only stdout is ordered before the completion line
+        assert_eq!(runner.spawn_count(), 2);
+        let mut lines = output(&executor);
+        let completed = lines.iter().position(|line| line == "Command completed").unwrap();
+        assert!(lines[..completed].iter().filter(|line| line.ends_with("up 3 days")).count() == 2);
+        lines.remove(completed);
+        lines.sort();
+        assert_eq!(lines, vec!["[web-1] up 3 days", "[web-1] warning", "[web-2] up 3 days", "[web-2] warning"]);
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use kira::{AudioManager, Decibels, Frame, Tween};
+use kira::backend::cpal::CpalBackend;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+
+const SAMPLE_RATE: u32 = 44_100;
+const BASE_FREQUENCY: f32 = 200.0;
+const FREQUENCY_STEP: f32 = 50.0;
+const QUIET_DB: f32 = -40.0;
+const LOUD_DB: f32 = -10.0;
+
+// Each tone is a looping sound in the mixer, so big machines get their cores grouped into bands
+pub const DEFAULT_MAX_SOURCES: usize = 8;
+pub const MAX_SOURCES_LIMIT: usize = 32;
+
+// Shape of each tone
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Triangle,
+    Saw,
+}
+
+impl Waveform {
+    pub const ALL: &'static [Waveform] = &[
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Saw,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            Waveform::Sine => "Sine",
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Saw => "Saw",
+        }
+    }
+    
+    // Level at `phase` (0-1 through a cycle). Peaks stay at or under the sine's 0.2,
+    // with square turned down further since it's the loudest at the same peak
+    fn sample(&self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * std::f32::consts::TAU).sin() * 0.2,
+            Waveform::Square => if phase < 0.5 { 0.14 } else { -0.14 },
+            Waveform::Triangle => (1.0 - 4.0 * (phase - 0.5).abs()) * 0.2,
+            Waveform::Saw => (2.0 * phase - 1.0) * 0.17,
+        }
+    }
+}
+
+// Sonification of CPU load: one looping tone per source (200Hz + source * 50Hz), louder as usage rises.
+// A source is a core, or a band of neighbouring cores once there are more cores than allowed tones
+pub struct CpuAudio {
+    audio_manager: AudioManager<CpalBackend>,
+    pub cpu_sounds: Vec<StaticSoundHandle>,
+    waveform: Waveform,
+}
+
+impl CpuAudio {
+    // Plays to the named output device, or the system default for None
+    pub fn new(device_name: Option<&str>) -> Result<Self, String> {
+        let audio_manager = super::audio_output::open_audio_manager(device_name)?;
+        Ok(Self {
+            audio_manager,
+            cpu_sounds: vec![],
+            waveform: Waveform::default(),
+        })
+    }
+    
+    // Takes effect on the next update, which rebuilds the tones; the output stream stays open
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        if waveform != self.waveform {
+            self.waveform = waveform;
+            self.stop_all();
+        }
+    }
+    
+    // Replace the current tones with one silent tone per source, never more than MAX_SOURCES_LIMIT
+    pub fn setup_audio_for_cpus(&mut self, source_count: usize) -> Result<(), String> {
+        self.stop_all();
+        for source in 0..source_count.min(MAX_SOURCES_LIMIT) {
+            let frequency = BASE_FREQUENCY + source as f32 * FREQUENCY_STEP;
+            let handle = self.audio_manager.play(tone(frequency, self.waveform)).map_err(|e| e.to_string())?;
+            self.cpu_sounds.push(handle);
+        }
+        Ok(())
+    }
+    
+    // Map each source's usage (0-100%) onto its tone's volume, scaled by the master volume (0-1).
+    // Muted sources keep their tone, silent, so the others don't shift pitch
+    pub fn update_cpu_audio(&mut self, usages: &[f32], master_volume: f32, muted: &[usize]) -> Result<(), String> {
+        if usages.len().min(MAX_SOURCES_LIMIT) != self.cpu_sounds.len() {
+            self.setup_audio_for_cpus(usages.len())?;
+        }
+        
+        // Master volume as gain in dB; never louder than the top of the usage range
+        let master_volume = master_volume.clamp(0.0, 1.0);
+        let gain_db = if master_volume > 0.0 { 20.0 * master_volume.log10() } else { f32::NEG_INFINITY };
+        
+        for (source, (sound, usage)) in self.cpu_sounds.iter_mut().zip(usages).enumerate() {
+            if muted.contains(&source) {
+                sound.set_volume(Decibels::SILENCE, Tween::default());
+                continue;
+            }
+            let db = QUIET_DB + (LOUD_DB - QUIET_DB) * (usage / 100.0).clamp(0.0, 1.0);
+            let db = (db + gain_db).clamp(Decibels::SILENCE.0, LOUD_DB);
+            sound.set_volume(Decibels(db), Tween::default());
+        }
+        Ok(())
+    }
+    
+    pub fn stop_all(&mut self) {
+        for mut sound in self.cpu_sounds.drain(..) {
+            sound.stop(Tween::default());
+        }
+    }
+}
+
+impl Drop for CpuAudio {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+// The cores behind each source: at most max_sources bands of neighbouring cores,
+// e.g. 128 cores with 8 sources gives cores 0-15, 16-31, ... Fewer cores get a source each
+pub fn band_ranges(core_count: usize, max_sources: usize) -> Vec<std::ops::Range<usize>> {
+    let bands = max_sources.clamp(1, MAX_SOURCES_LIMIT).min(core_count);
+    (0..bands)
+        .map(|band| band * core_count / bands..(band + 1) * core_count / bands)
+        .collect()
+}
+
+// Average per-core usages over each band
+pub fn band_usages(usages: &[f32], max_sources: usize) -> Vec<f32> {
+    band_ranges(usages.len(), max_sources)
+        .into_iter()
+        .map(|cores| usages[cores.clone()].iter().sum::<f32>() / cores.len() as f32)
+        .collect()
+}
+
+// One second of the waveform; whole-Hz frequencies loop without a click
+fn tone(frequency: f32, waveform: Waveform) -> StaticSoundData {
+    let frames: Arc<[Frame]> = (0..SAMPLE_RATE)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            Frame::from_mono(waveform.sample((t * frequency).fract()))
+        })
+        .collect();
+    
+    StaticSoundData {
+        sample_rate: SAMPLE_RATE,
+        frames,
+        settings: StaticSoundSettings::new().loop_region(..).volume(Decibels::SILENCE),
+        slice: None,
+    }
+}
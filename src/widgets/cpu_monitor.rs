@@ -1,22 +1,462 @@
+use std::sync::{Arc, Mutex};
 use eframe::egui;
+use crate::widgets::WidgetWindow;
 use serde::{Serialize, Deserialize};
-use crate::widgets::command_widget::{CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
+use crate::widgets::cpu_audio::{self, CpuAudio};
+use crate::widgets::alerts::{self, AlertWatch, ThresholdAlert, render_alert_settings};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget, SampleParser};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ColorScalePreset {
+    TrafficLight,
+    Heatmap,
+    ColorblindSafe,
+    Custom,
+}
+
+impl ColorScalePreset {
+    pub const ALL: &'static [ColorScalePreset] = &[
+        ColorScalePreset::TrafficLight,
+        ColorScalePreset::Heatmap,
+        ColorScalePreset::ColorblindSafe,
+        ColorScalePreset::Custom,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorScalePreset::TrafficLight => "Traffic light",
+            ColorScalePreset::Heatmap => "Heatmap",
+            ColorScalePreset::ColorblindSafe => "Colorblind safe",
+            ColorScalePreset::Custom => "Custom",
+        }
+    }
+}
+
+// Maps a usage percentage to a color using low/medium/high bands
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpuColorScale {
+    pub preset: ColorScalePreset,
+    pub medium_threshold: f32,
+    pub high_threshold: f32,
+    pub low_color: [u8; 3],
+    pub medium_color: [u8; 3],
+    pub high_color: [u8; 3],
+}
+
+impl Default for CpuColorScale {
+    fn default() -> Self {
+        Self::from_preset(ColorScalePreset::TrafficLight)
+    }
+}
+
+impl CpuColorScale {
+    pub fn from_preset(preset: ColorScalePreset) -> Self {
+        let (low_color, medium_color, high_color) = match preset {
+            ColorScalePreset::TrafficLight | ColorScalePreset::Custom => ([60, 180, 75], [230, 200, 40], [220, 50, 50]),
+            ColorScalePreset::Heatmap => ([40, 60, 160], [240, 140, 30], [200, 20, 30]),
+            // Okabe-Ito blue / orange / vermillion
+            ColorScalePreset::ColorblindSafe => ([0, 114, 178], [230, 159, 0], [213, 94, 0]),
+        };
+        
+        Self {
+            preset,
+            medium_threshold: 50.0,
+            high_threshold: 80.0,
+            low_color,
+            medium_color,
+            high_color,
+        }
+    }
+    
+    pub fn color_for(&self, usage_percent: f32) -> egui::Color32 {
+        let [r, g, b] = if usage_percent >= self.high_threshold {
+            self.high_color
+        } else if usage_percent >= self.medium_threshold {
+            self.medium_color
+        } else {
+            self.low_color
+        };
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+// CPU time split parsed from one line of vmstat output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CpuSample {
+    pub user: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub wait: f32,
+}
+
+impl CpuSample {
+    pub fn total(&self) -> f32 {
+        (100.0 - self.idle).clamp(0.0, 100.0)
+    }
+}
+
+// Markers written by the per-core sampling script
+const CORE_SNAPSHOT_END: &str = "--";
+const NO_PER_CORE_DATA: &str = "no-per-core-data";
+
+// Per-core usage is recorded as cpu_core_0, cpu_core_1, ... in percent
+const CORE_SERIES_PREFIX: &str = "cpu_core_";
+
+// Busy percentage of each core between the last two complete /proc/stat snapshots
+pub fn per_core_usage(lines: &[String]) -> Option<Vec<f32>> {
+    // (busy, total) jiffies per core, one Vec per snapshot
+    let mut snapshots: Vec<Vec<(u64, u64)>> = Vec::new();
+    let mut current = Vec::new();
+    for line in lines {
+        if line == CORE_SNAPSHOT_END {
+            snapshots.push(std::mem::take(&mut current));
+            continue;
+        }
+        // cpuN user nice system idle iowait irq softirq steal ...
+        let fields: Vec<u64> = line.split_whitespace().skip(1).take(8).filter_map(|field| field.parse().ok()).collect();
+        if line.starts_with("cpu") && fields.len() >= 4 {
+            let total: u64 = fields.iter().sum();
+            let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+            current.push((total - idle, total));
+        }
+    }
+    
+    let [.., before, after] = snapshots.as_slice() else {
+        return None;
+    };
+    if before.len() != after.len() || after.is_empty() {
+        return None;
+    }
+    Some(before.iter().zip(after).map(|(&(busy_before, total_before), &(busy_after, total_after))| {
+        let total = total_after.saturating_sub(total_before);
+        if total == 0 {
+            0.0
+        } else {
+            (busy_after.saturating_sub(busy_before) as f32 * 100.0 / total as f32).clamp(0.0, 100.0)
+        }
+    }).collect())
+}
+
+// Rolling per-core usage, one entry per /proc/stat snapshot, oldest first
+#[derive(Default)]
+pub struct CoreHistory {
+    pub samples: std::collections::VecDeque<Vec<f32>>,
+    pub capacity: usize,
+}
+
+impl CoreHistory {
+    pub fn push(&mut self, usages: Vec<f32>) {
+        self.samples.push_back(usages);
+        self.truncate();
+    }
+    
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+    
+    fn truncate(&mut self) {
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+}
+
+// Adds a history entry each time a snapshot completes, so none are missed between frames, and
+// returns it as samples to record
+pub struct CoreHistoryParser {
+    history: Arc<Mutex<CoreHistory>>,
+    lines: Vec<String>,  // The last complete snapshot and the one being read
+    alert: Option<(Arc<Mutex<AlertWatch>>, String)>,  // Fed the busiest core of each snapshot, with the host it's named by
+}
+
+impl CoreHistoryParser {
+    pub fn new(history: Arc<Mutex<CoreHistory>>) -> Self {
+        Self { history, lines: Vec::new(), alert: None }
+    }
+    
+    pub fn with_alert(mut self, watch: Arc<Mutex<AlertWatch>>, host: String) -> Self {
+        self.alert = Some((watch, host));
+        self
+    }
+}
+
+impl SampleParser for CoreHistoryParser {
+    fn parse(&mut self, line: &str) -> Vec<(String, f64)> {
+        self.lines.push(line.to_string());
+        let mut samples = vec![];
+        if line == CORE_SNAPSHOT_END {
+            if let Some(usages) = per_core_usage(&self.lines) {
+                if let Some((ref watch, ref host)) = self.alert {
+                    let busiest = usages.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1));
+                    if let Some((core, &usage)) = busiest {
+                        let mut watch = watch.lock().unwrap();
+                        if watch.observe(usage as f64, std::time::Instant::now()) {
+                            alerts::notify("CPU Monitor", &format!("Core {} on {} at {:.0}% for over {}s", core, host, usage, watch.alert.for_secs));
+                        }
+                    }
+                }
+                samples = usages.iter().enumerate()
+                    .map(|(core, usage)| (format!("{}{}", CORE_SERIES_PREFIX, core), *usage as f64))
+                    .collect();
+                self.history.lock().unwrap().push(usages);
+            }
+            // Keep just the snapshot that ended, to diff the next one against
+            let start = self.lines[..self.lines.len() - 1].iter().rposition(|line| line == CORE_SNAPSHOT_END).map_or(0, |i| i + 1);
+            self.lines.drain(..start);
+        }
+        samples
+    }
+}
+
+// Find the most recent sample, using the latest header line to locate the cpu columns
+pub fn latest_cpu_sample(lines: &[String]) -> Option<CpuSample> {
+    let mut columns: Option<Vec<&str>> = None;
+    let mut latest = None;
+    
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.contains(&"us") && fields.contains(&"id") {
+            columns = Some(fields);
+            continue;
+        }
+        
+        let Some(ref header) = columns else { continue };
+        if fields.len() != header.len() {
+            continue;
+        }
+        
+        let value = |name: &str| -> Option<f32> {
+            let idx = header.iter().position(|column| *column == name)?;
+            fields.get(idx)?.parse().ok()
+        };
+        
+        if let (Some(user), Some(system), Some(idle)) = (value("us"), value("sy"), value("id")) {
+            latest = Some(CpuSample {
+                user,
+                system,
+                idle,
+                wait: value("wa").unwrap_or(0.0),
+            });
+        }
+    }
+    
+    latest
+}
+
+// Playback of recorded samples, driving the bars and tones in place of live output
+#[derive(Clone)]
+pub struct CpuReplay {
+    pub frames: Vec<(i64, CpuSample)>,
+    pub core_frames: Vec<(i64, Vec<f32>)>,  // Per-core usage, for recordings made where /proc/stat was readable
+    pub position: f64,  // Seconds since the first frame
+    pub playing: bool,
+    pub speed: f64,
+    last_tick: Option<std::time::Instant>,
+}
+
+impl CpuReplay {
+    pub const SPEEDS: &'static [f64] = &[1.0, 2.0, 4.0];
+    
+    // Rebuild one CpuSample per recorded reading from its cpu_* series, and the per-core usage of each snapshot
+    pub fn from_samples(samples: &[(i64, String, f64)]) -> Self {
+        let mut frames: Vec<(i64, CpuSample)> = Vec::new();
+        let mut core_frames: Vec<(i64, Vec<f32>)> = Vec::new();
+        let mut timestamp = None;
+        let mut values = std::collections::HashMap::new();
+        
+        for (i, (ts, series, value)) in samples.iter().enumerate() {
+            if let Some(core) = series.strip_prefix(CORE_SERIES_PREFIX).and_then(|core| core.parse::<usize>().ok()) {
+                if core_frames.last().is_none_or(|(last, _)| last != ts) {
+                    core_frames.push((*ts, Vec::new()));
+                }
+                if let Some((_, cores)) = core_frames.last_mut() {
+                    if cores.len() <= core {
+                        cores.resize(core + 1, 0.0);
+                    }
+                    cores[core] = *value as f32;
+                }
+                continue;
+            }
+            if timestamp != Some(*ts) {
+                values.clear();
+                timestamp = Some(*ts);
+            }
+            values.insert(series.as_str(), *value as f32);
+            
+            let reading_done = samples.get(i + 1).is_none_or(|(next, _, _)| next != ts);
+            if let (true, Some(&user), Some(&system), Some(&idle)) = (reading_done, values.get("cpu_user"), values.get("cpu_system"), values.get("cpu_idle")) {
+                let wait = values.get("cpu_wait").copied().unwrap_or(0.0);
+                frames.push((*ts, CpuSample { user, system, idle, wait }));
+            }
+        }
+        
+        Self {
+            frames,
+            core_frames,
+            position: 0.0,
+            playing: false,
+            speed: 1.0,
+            last_tick: None,
+        }
+    }
+    
+    pub fn duration(&self) -> f64 {
+        match (self.frames.first(), self.frames.last()) {
+            (Some((first, _)), Some((last, _))) => (last - first) as f64 / 1_000_000.0,
+            _ => 0.0,
+        }
+    }
+    
+    // Move the playhead by the wall time since the last frame, stopping at the end
+    pub fn advance(&mut self) {
+        let now = std::time::Instant::now();
+        if let (true, Some(last_tick)) = (self.playing, self.last_tick) {
+            self.position += now.duration_since(last_tick).as_secs_f64() * self.speed;
+            if self.position >= self.duration() {
+                self.position = self.duration();
+                self.playing = false;
+            }
+        }
+        self.last_tick = self.playing.then_some(now);
+    }
+    
+    // The latest reading at or before the playhead
+    pub fn current(&self) -> Option<&(i64, CpuSample)> {
+        let (start, _) = self.frames.first()?;
+        let playhead = start + (self.position * 1_000_000.0) as i64;
+        self.frames.iter().rev().find(|(ts, _)| *ts <= playhead)
+    }
+    
+    // Per-core usage of the latest snapshot at or before the playhead
+    pub fn current_cores(&self) -> Option<&Vec<f32>> {
+        let (start, _) = self.frames.first()?;
+        let playhead = start + (self.position * 1_000_000.0) as i64;
+        self.core_frames.iter().rev().find(|(ts, _)| *ts <= playhead).map(|(_, cores)| cores)
+    }
+}
+
+// vmstat columns recorded as samples: cpu_* in percent, mem_* and swap_* in KiB
+const VMSTAT_SERIES: &[(&str, &str)] = &[
+    ("us", "cpu_user"),
+    ("sy", "cpu_system"),
+    ("id", "cpu_idle"),
+    ("wa", "cpu_wait"),
+    ("free", "mem_free_kb"),
+    ("buff", "mem_buffers_kb"),
+    ("cache", "mem_cache_kb"),
+    ("swpd", "swap_used_kb"),
+];
+
+// Tracks the latest vmstat header so each reading can be split into named series
+#[derive(Default)]
+pub struct VmstatSampleParser {
+    header: Vec<String>,
+}
+
+impl SampleParser for VmstatSampleParser {
+    fn parse(&mut self, line: &str) -> Vec<(String, f64)> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.contains(&"us") && fields.contains(&"id") {
+            self.header = fields.iter().map(|field| field.to_string()).collect();
+            return vec![];
+        }
+        if self.header.is_empty() || fields.len() != self.header.len() {
+            return vec![];
+        }
+        
+        let mut samples: Vec<(String, f64)> = VMSTAT_SERIES.iter()
+            .filter_map(|(column, series)| {
+                let idx = self.header.iter().position(|name| name == column)?;
+                let value = fields[idx].parse().ok()?;
+                Some((series.to_string(), value))
+            })
+            .collect();
+        
+        if let Some(&(_, idle)) = samples.iter().find(|(series, _)| series == "cpu_idle") {
+            samples.insert(0, ("cpu_total".to_string(), (100.0 - idle).clamp(0.0, 100.0)));
+        }
+        samples
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CPUMonitorWidget {
     pub id: usize,
     pub version: i32,
+    #[serde(default = "default_interval_seconds")]
     pub interval_seconds: u64,
+    #[serde(default)]
+    pub color_scale: CpuColorScale,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
     #[serde(skip, default = "default_executor")]
     pub executor: CommandExecutor,
     #[serde(skip, default)]
     pub config_unsaved: bool,
+    #[serde(default)]
+    pub audio_enabled: bool,
+    #[serde(default = "default_volume")]
+    pub volume: f32,  // Master volume for the tones, 0-1
+    #[serde(default = "default_max_audio_sources")]
+    pub max_audio_sources: usize,  // Tones at most; more cores than this are grouped into bands
+    #[serde(default)]
+    pub waveform: cpu_audio::Waveform,
+    #[serde(default)]
+    pub audio_device: Option<String>,  // Output device by name; None for the system default
+    #[serde(default)]
+    pub muted_sources: Vec<usize>,  // Tones kept silent, by index after banding
+    #[serde(default = "default_history_length")]
+    pub history_length: usize,  // Per-core snapshots kept for the sparklines
+    #[serde(default = "default_core_alert")]
+    pub core_alert: ThresholdAlert,  // On the busiest core
+    #[serde(skip, default)]
+    pub audio: Arc<Mutex<Option<CpuAudio>>>,  // Present while tones are playing
     #[serde(skip, default)]
-    pub database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>,
+    pub audio_error: Arc<Mutex<Option<String>>>,  // Why the output device couldn't be opened
+    #[serde(skip, default)]
+    pub audio_devices: Option<Result<Vec<String>, String>>,  // Listed once, when the sound controls are first shown
+    #[serde(skip, default)]
+    pub replay: Option<CpuReplay>,
+    #[serde(skip, default = "default_cores_executor")]
+    pub cores_executor: CommandExecutor,  // /proc/stat snapshots for per-core usage; only the cpu_core_* samples are recorded
+    #[serde(skip, default)]
+    pub core_history: Arc<Mutex<CoreHistory>>,
+    #[serde(skip, default)]
+    pub core_alert_watch: Arc<Mutex<AlertWatch>>,
+}
+
+fn default_interval_seconds() -> u64 {
+    2
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+fn default_max_audio_sources() -> usize {
+    cpu_audio::DEFAULT_MAX_SOURCES
+}
+
+fn default_history_length() -> usize {
+    300
+}
+
+fn default_core_alert() -> ThresholdAlert {
+    ThresholdAlert::new(90.0, 10)
 }
 
 fn default_executor() -> CommandExecutor {
-    CommandExecutor::new()
+    CommandExecutor::new().with_sample_parser(VmstatSampleParser::default())
+}
+
+fn default_cores_executor() -> CommandExecutor {
+    // Room for a few snapshots of a big machine
+    CommandExecutor::new().with_max_lines(4096).recording_samples_only()
 }
 
 impl crate::widgets::Widget for CPUMonitorWidget {
@@ -40,12 +480,8 @@ impl crate::widgets::Widget for CPUMonitorWidget {
     fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
         let widget_id = self.id as i32;
         let widget_version = self.version;
-        self.executor.set_database(database.clone(), widget_id, widget_version);
-        self.database = database;
-    }
-    
-    fn config_changed(&self) -> bool {
-        self.config_unsaved
+        self.cores_executor.set_database(database.clone(), widget_id, widget_version);
+        self.executor.set_database(database, widget_id, widget_version);
     }
     
     fn needs_restart(&self) -> bool {
@@ -53,12 +489,20 @@ impl crate::widgets::Widget for CPUMonitorWidget {
         self.config_unsaved
     }
     
-    fn restore_widget_data(&mut self, data: Vec<String>) {
-        self.executor.load_historical_output(data);
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
     }
     
     fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
-        self.executor.set_available_hosts(hosts);
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
     }
     
     fn start(&self) {
@@ -67,6 +511,13 @@ impl crate::widgets::Widget for CPUMonitorWidget {
     
     fn stop(&self) {
         self.stop_command();
+        self.stop_audio();
+    }
+    
+    // The tones are set from the render pass, so out of view they'd hold their last volumes;
+    // they start again once the workspace is shown
+    fn leave_view(&self) {
+        self.stop_audio();
     }
     
     fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
@@ -79,28 +530,49 @@ impl crate::widgets::Widget for CPUMonitorWidget {
             .default_pos([100.0 + (idx as f32 * 50.0), 100.0 + (idx as f32 * 50.0)])
             .default_size([800.0, 600.0])
             .resizable(true)
-            .show(ctx, |ui| {
+            .show_for_widget(ctx, self.id, |ui| {
                 ui.horizontal(|ui| {
                     refresh_clicked = self.render_controls(ui);
                     
                     ui.separator();
-                    ui.label("Interval:");
-                    let old_interval = self.interval_seconds;
-                    ui.add(egui::DragValue::new(&mut self.interval_seconds).range(1..=60).suffix("s"));
-                    if old_interval != self.interval_seconds {
-                        // Handle config change immediately
-                        self.handle_config_change(self.database.clone());
-                        
-                        // Save to database if available
-                        if let Some(ref db) = self.database {
-                            let widget = crate::widgets::WidgetType::CPUMonitor(self.clone());
-                            let db_clone = db.clone();
-                            tokio::spawn(async move {
-                                if let Err(e) = db_clone.save_widget_instance(&widget).await {
-                                    eprintln!("Failed to save CPU monitor config change: {}", e);
-                                }
-                            });
+                    self.render_interval_control(ui);
+                    
+                    ui.separator();
+                    self.render_audio_toggle(ui);
+                    
+                    ui.separator();
+                    ui.menu_button("🔔 Alert", |ui| {
+                        if render_alert_settings(ui, &mut self.core_alert, "any core", "%", 100.0) {
+                            self.core_alert_watch.lock().unwrap().set_alert(self.core_alert.clone());
+                            self.save_config();
                         }
+                        ui.weak("Needs per-core usage, which only Linux hosts report");
+                    });
+                });
+                
+                ui.separator();
+                if let Some(ref mut replay) = self.replay {
+                    replay.advance();
+                    // The playhead moves with wall time, not with new output
+                    if replay.playing {
+                        ui.ctx().request_repaint();
+                    }
+                }
+                self.render_usage_bars(ui);
+                self.render_core_history(ui);
+                self.sync_audio();
+                
+                ui.collapsing("Replay", |ui| {
+                    self.render_replay_controls(ui);
+                });
+                
+                if ui.button("Export CSV…").on_hover_text("Every recorded sample as timestamp, series, value").clicked() {
+                    self.export_samples_csv();
+                }
+                
+                ui.collapsing("Color scale", |ui| {
+                    if self.render_color_scale_controls(ui) {
+                        self.save_config();
                     }
                 });
                 
@@ -132,9 +604,44 @@ impl CommandWidget for CPUMonitorWidget {
         &mut self.executor
     }
     
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
     fn execution_mode(&self) -> ExecutionMode {
         ExecutionMode::Continuous
     }
+    
+    // The per-core sampler runs alongside vmstat on the same host, so it follows host switches and Start/Stop too
+    fn start_command(&self) {
+        self.executor.run_continuous(self.wrap_for_host(self.build_command(), &ExecutionMode::Continuous));
+        self.start_core_sampling();
+    }
+    
+    fn stop_command(&self) {
+        self.executor.stop();
+        self.cores_executor.stop();
+    }
 }
 
 // Implement UI traits
@@ -146,15 +653,561 @@ impl CommandOutputRenderer for CPUMonitorWidget {
 
 impl CommandControlBar for CPUMonitorWidget {}
 
+// Clones (e.g. the one saved as config) share the tones, so only the last one tears them down.
+// Covers widgets dropped without stop(), like a workspace being cleared
+impl Drop for CPUMonitorWidget {
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.audio) == 1 {
+            self.stop_audio();
+        }
+    }
+}
+
+impl RefreshableWidget for CPUMonitorWidget {
+    fn refresh_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_seconds)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: std::time::Duration) {
+        self.interval_seconds = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
 impl CPUMonitorWidget {
     pub fn new(id: usize) -> Self {
         Self {
             id,
             version: 0,  // Starting at 0 as requested
-            interval_seconds: 2,
-            executor: CommandExecutor::new(),
+            interval_seconds: default_interval_seconds(),
+            color_scale: CpuColorScale::default(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: default_executor(),
             config_unsaved: false,
-            database: None,
+            audio_enabled: false,
+            volume: default_volume(),
+            max_audio_sources: default_max_audio_sources(),
+            waveform: cpu_audio::Waveform::default(),
+            audio_device: None,
+            muted_sources: Vec::new(),
+            history_length: default_history_length(),
+            core_alert: default_core_alert(),
+            audio: Arc::new(Mutex::new(None)),
+            audio_error: Arc::new(Mutex::new(None)),
+            audio_devices: None,
+            replay: None,
+            cores_executor: default_cores_executor(),
+            core_history: Arc::default(),
+            core_alert_watch: Arc::default(),
+        }
+    }
+    
+    // Per-core usage needs /proc/stat (Linux); elsewhere the script says so and the aggregate is all there is
+    fn start_core_sampling(&self) {
+        let script = format!(
+            "if [ -r /proc/stat ]; then while :; do grep '^cpu[0-9]' /proc/stat; echo {}; sleep {}; done; else echo {}; fi",
+            CORE_SNAPSHOT_END, self.interval_seconds, NO_PER_CORE_DATA);
+        let spec = CommandSpec::new("sh").arg("-c").arg(script);
+        *self.cores_executor.sudo.lock().unwrap() = self.executor.sudo.lock().unwrap().clone();
+        self.cores_executor.clear_output();
+        // A restart may be on another host, whose history isn't this one's
+        {
+            let mut history = self.core_history.lock().unwrap();
+            history.samples.clear();
+            history.set_capacity(self.history_length);
         }
+        self.core_alert_watch.lock().unwrap().set_alert(self.core_alert.clone());
+        let parser = CoreHistoryParser::new(self.core_history.clone())
+            .with_alert(self.core_alert_watch.clone(), self.selected_host());
+        *self.cores_executor.sample_parser.lock().unwrap() = Some(Box::new(parser));
+        self.cores_executor.run_continuous(self.wrap_for_host(spec, &ExecutionMode::Continuous));
     }
-}
\ No newline at end of file
+    
+    // Live per-core usage; Err when the host can't report it, Ok(None) until two snapshots are in
+    fn core_usages(&self) -> Result<Option<Vec<f32>>, ()> {
+        let output = self.cores_executor.output.lock().unwrap();
+        if output.iter().any(|line| line == NO_PER_CORE_DATA) {
+            return Err(());
+        }
+        Ok(per_core_usage(&output))
+    }
+    
+    // Sample under the replay playhead, or the latest live one
+    fn current_sample(&self) -> Option<CpuSample> {
+        if let Some(ref replay) = self.replay {
+            return replay.current().map(|(_, sample)| *sample);
+        }
+        let output = CommandWidget::executor(self).output.lock().unwrap();
+        latest_cpu_sample(&output)
+    }
+    
+    // Usage per sonified source: a tone per core when live per-core data is there, else one for the aggregate
+    fn sonified_usages(&self) -> Option<Vec<f32>> {
+        match self.replay {
+            Some(ref replay) => {
+                if let Some(cores) = replay.current_cores() {
+                    return Some(cores.clone());
+                }
+            }
+            None => {
+                if let Ok(Some(cores)) = self.core_usages() {
+                    return Some(cores);
+                }
+            }
+        }
+        self.current_sample().map(|sample| vec![sample.total()])
+    }
+    
+    fn render_replay_controls(&mut self, ui: &mut egui::Ui) {
+        let Some(ref mut replay) = self.replay else {
+            if ui.button("Load recording").on_hover_text("Play back recorded samples, with sound if enabled").clicked() {
+                if let Some(ref db) = self.executor.database {
+                    match crate::runtime::runtime().block_on(db.get_samples(self.id as i32)) {
+                        Ok(samples) => self.replay = Some(CpuReplay::from_samples(&samples)),
+                        Err(e) => eprintln!("Failed to load CPU samples: {}", e),
+                    }
+                }
+            }
+            return;
+        };
+        
+        let mut close = false;
+        if replay.frames.is_empty() {
+            ui.label("No samples recorded yet");
+            close = ui.button("Close").clicked();
+        } else {
+            ui.horizontal(|ui| {
+                if ui.button(if replay.playing { "⏸ Pause" } else { "▶ Play" }).clicked() {
+                    if !replay.playing && replay.position >= replay.duration() {
+                        replay.position = 0.0;
+                    }
+                    replay.playing = !replay.playing;
+                }
+                
+                egui::ComboBox::from_id_salt(format!("cpu_replay_speed_{}", self.id))
+                    .selected_text(format!("{}x", replay.speed))
+                    .width(50.0)
+                    .show_ui(ui, |ui| {
+                        for speed in CpuReplay::SPEEDS {
+                            ui.selectable_value(&mut replay.speed, *speed, format!("{}x", speed));
+                        }
+                    });
+                
+                if let Some((timestamp, _)) = replay.current() {
+                    ui.label(crate::investigation::Investigation::format_timestamp(*timestamp));
+                }
+                close = ui.button("Back to live").clicked();
+            });
+            
+            let duration = replay.duration();
+            ui.add(egui::Slider::new(&mut replay.position, 0.0..=duration)
+                .custom_formatter(|value, _| format!("{:.0}s / {:.0}s", value, duration)));
+        }
+        
+        if close {
+            self.replay = None;
+        }
+    }
+    
+    fn render_audio_toggle(&mut self, ui: &mut egui::Ui) {
+        if ui.checkbox(&mut self.audio_enabled, "🔊 Sound").on_hover_text("Play a tone that gets louder with CPU usage").changed() {
+            // Tones start on the next frame if monitoring; turning sound off silences them right away
+            if !self.audio_enabled {
+                self.stop_audio();
+            }
+            *self.audio_error.lock().unwrap() = None;
+            self.save_config();
+        }
+        
+        if self.audio_enabled {
+            // Applied on the next update, without restarting the tones
+            let response = ui.add(egui::Slider::new(&mut self.volume, 0.0..=1.0)
+                .custom_formatter(|value, _| format!("{:.0}%", value * 100.0))
+                .custom_parser(|text| text.trim_end_matches('%').parse::<f64>().ok().map(|value| value / 100.0)));
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                self.save_config();
+            }
+            
+            ui.label("Tones:");
+            if ui.add(egui::DragValue::new(&mut self.max_audio_sources).range(1..=cpu_audio::MAX_SOURCES_LIMIT))
+                .on_hover_text("With more cores than tones, neighbouring cores share a tone playing their average")
+                .changed() {
+                self.save_config();
+            }
+            
+            self.render_audio_device_selector(ui);
+            
+            egui::ComboBox::from_id_salt(format!("cpu_waveform_{}", self.id))
+                .selected_text(self.waveform.label())
+                .width(80.0)
+                .show_ui(ui, |ui| {
+                    for waveform in cpu_audio::Waveform::ALL {
+                        if ui.selectable_value(&mut self.waveform, *waveform, waveform.label()).changed() {
+                            self.save_config();
+                        }
+                    }
+                });
+            
+            if let Some(usages) = self.sonified_usages() {
+                let bands = cpu_audio::band_ranges(usages.len(), self.max_audio_sources);
+                let muted = self.muted_sources.iter().filter(|source| **source < bands.len()).count();
+                let title = if muted == 0 { "Mute".to_string() } else { format!("Mute ({})", muted) };
+                ui.menu_button(title, |ui| {
+                    for (source, cores) in bands.iter().enumerate() {
+                        let label = match cores.len() {
+                            _ if usages.len() == 1 => "All CPUs".to_string(),
+                            1 => format!("CPU {}", cores.start),
+                            _ => format!("CPUs {}-{}", cores.start, cores.end - 1),
+                        };
+                        let mut is_muted = self.muted_sources.contains(&source);
+                        if ui.checkbox(&mut is_muted, label).changed() {
+                            self.muted_sources.retain(|muted| *muted != source);
+                            if is_muted {
+                                self.muted_sources.push(source);
+                                self.muted_sources.sort_unstable();
+                            }
+                            self.save_config();
+                        }
+                    }
+                });
+            }
+        }
+        
+        if let Some(ref error) = *self.audio_error.lock().unwrap() {
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), "Audio unavailable").on_hover_text(error);
+        }
+    }
+    
+    // Switching devices drops the tones; they start again on the new one next frame
+    fn render_audio_device_selector(&mut self, ui: &mut egui::Ui) {
+        let devices = self.audio_devices.get_or_insert_with(crate::widgets::audio_output::output_device_names);
+        let devices = match devices {
+            Ok(devices) => devices.clone(),
+            Err(e) => {
+                ui.add_enabled(false, egui::Button::new("Default output"))
+                    .on_disabled_hover_text(format!("Output devices can't be listed: {}", e));
+                return;
+            }
+        };
+        
+        let mut selected = self.audio_device.clone();
+        egui::ComboBox::from_id_salt(format!("cpu_audio_device_{}", self.id))
+            .selected_text(selected.as_deref().unwrap_or("Default output"))
+            .width(140.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, "Default output");
+                for device in devices {
+                    ui.selectable_value(&mut selected, Some(device.clone()), device);
+                }
+            });
+        if selected != self.audio_device {
+            self.audio_device = selected;
+            self.stop_audio();
+            *self.audio_error.lock().unwrap() = None;
+            self.save_config();
+        }
+    }
+    
+    // Keep the tones in step with the toggle and the monitor, however it was started or stopped
+    fn sync_audio(&self) {
+        let active = self.replay.as_ref().map_or(self.executor.is_running(), |replay| replay.playing);
+        if !self.audio_enabled || !active {
+            self.stop_audio();
+            return;
+        }
+        if self.audio_error.lock().unwrap().is_some() {
+            return;
+        }
+        
+        let mut audio = self.audio.lock().unwrap();
+        if audio.is_none() {
+            match CpuAudio::new(self.audio_device.as_deref()) {
+                Ok(cpu_audio) => *audio = Some(cpu_audio),
+                Err(e) => {
+                    eprintln!("Failed to start CPU audio: {}", e);
+                    *self.audio_error.lock().unwrap() = Some(e);
+                    return;
+                }
+            }
+        }
+        
+        if let (Some(cpu_audio), Some(usages)) = (audio.as_mut(), self.sonified_usages()) {
+            let usages = cpu_audio::band_usages(&usages, self.max_audio_sources);
+            cpu_audio.set_waveform(self.waveform);
+            if let Err(e) = cpu_audio.update_cpu_audio(&usages, self.volume, &self.muted_sources) {
+                eprintln!("Failed to update CPU audio: {}", e);
+            }
+        }
+    }
+    
+    // Stop every tone, then drop the audio manager, which closes the output stream
+    fn stop_audio(&self) {
+        let audio = self.audio.lock().unwrap().take();
+        if let Some(mut cpu_audio) = audio {
+            cpu_audio.stop_all();
+        }
+    }
+    
+    fn export_samples_csv(&self) {
+        let Some(ref db) = self.executor.database else {
+            return;
+        };
+        
+        let result = crate::runtime::runtime().block_on(db.get_samples(self.id as i32))
+            .map_err(|e| e.to_string())
+            .and_then(|samples| {
+                crate::widgets::command_widget::save_samples_csv(&format!("cpu_monitor_{}.csv", self.id), &samples)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("Failed to export CPU samples: {}", e);
+        }
+    }
+    
+    fn render_usage_bars(&self, ui: &mut egui::Ui) {
+        let Some(sample) = self.current_sample() else {
+            ui.label("Waiting for CPU samples...");
+            return;
+        };
+        
+        egui::Grid::new(format!("cpu_bars_{}", self.id))
+            .num_columns(2)
+            .spacing([10.0, 4.0])
+            .show(ui, |ui| {
+                for (label, value) in [("Total", sample.total()), ("User", sample.user), ("System", sample.system), ("IO wait", sample.wait)] {
+                    ui.label(label);
+                    ui.add(egui::ProgressBar::new(value / 100.0)
+                        .fill(self.color_scale.color_for(value))
+                        .text(format!("{:.0}%", value)));
+                    ui.end_row();
+                }
+            });
+        
+        ui.add_space(4.0);
+        if let Some(ref replay) = self.replay {
+            match replay.current_cores() {
+                Some(cores) => self.render_core_bars(ui, cores),
+                None => {
+                    ui.weak("No per-core usage recorded here; the replay shows the aggregate only");
+                }
+            }
+            return;
+        }
+        match self.core_usages() {
+            Err(()) => {
+                ui.weak("Aggregate only: this host doesn't expose per-core usage (/proc/stat)");
+            }
+            Ok(None) => {
+                if self.cores_executor.is_running() {
+                    ui.weak("Measuring per-core usage...");
+                }
+            }
+            Ok(Some(cores)) => self.render_core_bars(ui, &cores),
+        }
+    }
+    
+    fn render_core_bars(&self, ui: &mut egui::Ui, cores: &[f32]) {
+        ui.collapsing(format!("Per core ({})", cores.len()), |ui| {
+            egui::Grid::new(format!("cpu_core_bars_{}", self.id))
+                .num_columns(2)
+                .spacing([10.0, 2.0])
+                .show(ui, |ui| {
+                    for (core, value) in cores.iter().enumerate() {
+                        ui.label(format!("CPU {}", core));
+                        ui.add(egui::ProgressBar::new(value / 100.0)
+                            .fill(self.color_scale.color_for(*value))
+                            .text(format!("{:.0}%", value)));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    
+    // A sparkline per core over the last `history_length` snapshots, 0-100%
+    fn render_core_history(&mut self, ui: &mut egui::Ui) {
+        if self.replay.is_some() {
+            return;
+        }
+        let history: Vec<Vec<f32>> = self.core_history.lock().unwrap().samples.iter().cloned().collect();
+        let Some(cores) = history.last().map(Vec::len) else {
+            return;
+        };
+        
+        ui.collapsing("History", |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Keep:");
+                let response = ui.add(egui::DragValue::new(&mut self.history_length).range(10..=3600).suffix(" samples"))
+                    .on_hover_text(format!("One sample every {}s", self.interval_seconds));
+                if response.changed() {
+                    self.core_history.lock().unwrap().set_capacity(self.history_length);
+                }
+                if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                    self.save_config();
+                }
+            });
+            
+            egui::Grid::new(format!("cpu_core_history_{}", self.id))
+                .num_columns(2)
+                .spacing([10.0, 2.0])
+                .show(ui, |ui| {
+                    for core in 0..cores {
+                        let values: Vec<f32> = history.iter().filter_map(|usages| usages.get(core).copied()).collect();
+                        ui.label(format!("CPU {}", core));
+                        self.render_sparkline(ui, &values);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    
+    // Newest value at the right edge; the line fills in from the right as history builds up
+    fn render_sparkline(&self, ui: &mut egui::Ui, values: &[f32]) {
+        let width = ui.available_width().max(120.0);
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(width, 20.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        
+        let slots = self.history_length.max(2) - 1;
+        let offset = slots.saturating_sub(values.len().saturating_sub(1));
+        let points: Vec<egui::Pos2> = values.iter().enumerate().map(|(i, value)| egui::pos2(
+            egui::lerp(rect.x_range(), (offset + i) as f32 / slots as f32),
+            egui::lerp(rect.bottom()..=rect.top(), value.clamp(0.0, 100.0) / 100.0),
+        )).collect();
+        let latest = values.last().copied().unwrap_or(0.0);
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.0_f32, self.color_scale.color_for(latest))));
+        
+        if !values.is_empty() {
+            let (low, high) = values.iter().fold((f32::MAX, f32::MIN), |(low, high), value| (low.min(*value), high.max(*value)));
+            response.on_hover_text(format!("Now {:.0}%, min {:.0}%, max {:.0}%", latest, low, high));
+        }
+    }
+    
+    // Returns true when the color scale changed
+    fn render_color_scale_controls(&mut self, ui: &mut egui::Ui) -> bool {
+        let old_scale = self.color_scale.clone();
+        
+        ui.horizontal(|ui| {
+            ui.label("Preset:");
+            let mut preset = self.color_scale.preset;
+            egui::ComboBox::from_id_salt(format!("cpu_color_preset_{}", self.id))
+                .selected_text(preset.label())
+                .show_ui(ui, |ui| {
+                    for option in ColorScalePreset::ALL {
+                        ui.selectable_value(&mut preset, *option, option.label());
+                    }
+                });
+            if preset != self.color_scale.preset {
+                let thresholds = (self.color_scale.medium_threshold, self.color_scale.high_threshold);
+                self.color_scale = CpuColorScale::from_preset(preset);
+                (self.color_scale.medium_threshold, self.color_scale.high_threshold) = thresholds;
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Medium from:");
+            ui.add(egui::DragValue::new(&mut self.color_scale.medium_threshold).range(0.0..=100.0).suffix("%"));
+            ui.label("High from:");
+            ui.add(egui::DragValue::new(&mut self.color_scale.high_threshold).range(0.0..=100.0).suffix("%"));
+        });
+        self.color_scale.high_threshold = self.color_scale.high_threshold.max(self.color_scale.medium_threshold);
+        
+        ui.horizontal(|ui| {
+            let mut colors_changed = false;
+            ui.label("Low:");
+            colors_changed |= ui.color_edit_button_srgb(&mut self.color_scale.low_color).changed();
+            ui.label("Medium:");
+            colors_changed |= ui.color_edit_button_srgb(&mut self.color_scale.medium_color).changed();
+            ui.label("High:");
+            colors_changed |= ui.color_edit_button_srgb(&mut self.color_scale.high_color).changed();
+            if colors_changed {
+                self.color_scale.preset = ColorScalePreset::Custom;
+            }
+        });
+        
+        old_scale != self.color_scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn per_core_usage_uses_the_last_two_snapshots() {
+        let output = "\
+cpu0 100 0 100 800 0 0 0 0 0 0
+cpu1 100 0 100 800 0 0 0 0 0 0
+--
+cpu0 150 0 150 800 0 0 0 0 0 0
+cpu1 100 0 100 850 50 0 0 0 0 0
+--
+cpu0 150";
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        
+        assert_eq!(per_core_usage(&lines), Some(vec![100.0, 0.0]));
+        assert_eq!(per_core_usage(&lines[..3]), None);
+    }
+    
+    #[test]
+    fn core_history_gets_an_entry_per_snapshot_up_to_its_capacity() {
+        let history = Arc::new(Mutex::new(CoreHistory { capacity: 2, ..Default::default() }));
+        let mut parser = CoreHistoryParser::new(history.clone());
+        let mut recorded = vec![];
+        for (busy, idle) in [(100, 100), (150, 150), (250, 150), (300, 200)] {
+            assert!(parser.parse(&format!("cpu0 {} 0 0 {} 0 0 0 0", busy, idle)).is_empty());
+            recorded.extend(parser.parse(CORE_SNAPSHOT_END));
+        }
+        
+        // Four snapshots give three diffs, and only the newest two are kept
+        let samples: Vec<Vec<f32>> = history.lock().unwrap().samples.iter().cloned().collect();
+        assert_eq!(samples, vec![vec![100.0], vec![50.0]]);
+        assert!(parser.lines.len() <= 2);
+        
+        // Each diff is also recorded
+        let series = |value| ("cpu_core_0".to_string(), value);
+        assert_eq!(recorded, vec![series(50.0), series(100.0), series(50.0)]);
+    }
+    
+    #[test]
+    fn replay_plays_back_recorded_per_core_usage() {
+        let sample = |ts, series: &str, value| (ts, series.to_string(), value);
+        let replay = CpuReplay::from_samples(&[
+            sample(1_000_000, "cpu_user", 10.0), sample(1_000_000, "cpu_system", 5.0), sample(1_000_000, "cpu_idle", 85.0),
+            sample(1_500_000, "cpu_core_0", 20.0), sample(1_500_000, "cpu_core_1", 80.0),
+            sample(3_000_000, "cpu_user", 50.0), sample(3_000_000, "cpu_system", 10.0), sample(3_000_000, "cpu_idle", 40.0),
+            sample(3_500_000, "cpu_core_0", 60.0), sample(3_500_000, "cpu_core_1", 40.0),
+        ]);
+        assert_eq!(replay.frames.len(), 2);
+        assert_eq!(replay.current_cores(), None);
+        
+        let at = |position| CpuReplay { position, ..replay.clone() };
+        assert_eq!(at(1.0).current_cores(), Some(&vec![20.0, 80.0]));
+        assert_eq!(at(2.5).current_cores(), Some(&vec![60.0, 40.0]));
+    }
+    
+    #[test]
+    fn remote_host_runs_vmstat_over_ssh() {
+        let widget = CPUMonitorWidget::new(1);
+        widget.executor.set_selected_host("web-1".to_string());
+        
+        let spec = widget.wrap_for_host(widget.build_command(), &widget.execution_mode());
+        assert_eq!(spec.program, "ssh");
+        assert_eq!(spec.args[spec.args.len() - 2..], ["web-1".to_string(), "vmstat 2".to_string()]);
+    }
+    
+    #[test]
+    fn host_connection_options_go_before_the_alias() {
+        let widget = CPUMonitorWidget::new(1);
+        widget.executor.set_available_hosts(vec![crate::database::investigation_db::Host {
+            port: Some(2222),
+            username: Some("ops".to_string()),
+            ..crate::database::investigation_db::Host::for_test("web-1", "")
+        }]);
+        widget.executor.set_selected_host("web-1".to_string());
+        
+        let spec = widget.wrap_for_host(widget.build_command(), &ExecutionMode::OneShot);
+        assert_eq!(spec.args[spec.args.len() - 6..], ["-p", "2222", "-l", "ops", "web-1", "vmstat 2"]);
+    }
+}
@@ -0,0 +1,376 @@
+use std::time::Duration;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, format_bytes, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget};
+
+// Capacity from POSIX df, then throughput from sysstat's iostat. The first iostat report is
+// averaged since boot, so it takes two one-second reports and keeps the second
+const DISK_POLL: &str = "df -Pk; echo '== iostat'; iostat -d -k -N -p ALL 1 2 2>/dev/null";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DiskMonitorWidget {
+    pub id: usize,
+    pub version: i32,
+    pub refresh_interval_secs: u64,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+// One mounted filesystem; sizes in bytes, rates in bytes per second
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskMount {
+    pub filesystem: String,
+    pub mount: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub read_rate: Option<f64>,
+    pub write_rate: Option<f64>,
+}
+
+pub fn parse_disk_output(lines: &[String]) -> Vec<DiskMount> {
+    let split = lines.iter().position(|line| line == "== iostat").unwrap_or(lines.len());
+    let rates = parse_iostat(&lines[split..]);
+    
+    lines[..split].iter()
+        .filter_map(|line| parse_df_line(line))
+        .map(|mut mount| {
+            // iostat names devices without the /dev/ (or /dev/mapper/) prefix
+            let device = mount.filesystem.rsplit('/').next().unwrap_or_default();
+            if let Some(&(read, write)) = rates.iter().find(|(name, _)| name == device).map(|(_, rate)| rate) {
+                mount.read_rate = Some(read);
+                mount.write_rate = Some(write);
+            }
+            mount
+        })
+        .collect()
+}
+
+// "Filesystem 1024-blocks Used Available Capacity Mounted on". Either end can contain spaces, so
+// anchor on the run of three numbers followed by the capacity and take the text either side of it
+fn parse_df_line(line: &str) -> Option<DiskMount> {
+    let tokens: Vec<(usize, &str)> = line.split_whitespace()
+        .map(|token| (token.as_ptr() as usize - line.as_ptr() as usize, token))
+        .collect();
+    
+    (3..tokens.len()).find_map(|i| {
+        let (capacity_start, capacity) = tokens[i];
+        capacity.strip_suffix('%')?.parse::<u32>().ok()?;
+        let numbers: Vec<u64> = tokens[i - 3..i].iter().map(|(_, token)| token.parse().ok()).collect::<Option<_>>()?;
+        let filesystem = line[..tokens[i - 3].0].trim();
+        let mount = line[capacity_start + capacity.len()..].trim();
+        if filesystem.is_empty() || mount.is_empty() {
+            return None;
+        }
+        Some(DiskMount {
+            filesystem: filesystem.to_string(),
+            mount: mount.to_string(),
+            total: numbers[0] * 1024,
+            used: numbers[1] * 1024,
+            available: numbers[2] * 1024,
+            read_rate: None,
+            write_rate: None,
+        })
+    })
+}
+
+// Read and write bytes/s per device from the last report
+fn parse_iostat(lines: &[String]) -> Vec<(String, (f64, f64))> {
+    let mut rates = Vec::new();
+    let mut columns = None;
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.first() {
+            // Older sysstat prints "Device:"
+            Some(first) if first.trim_end_matches(':') == "Device" => {
+                rates.clear();
+                columns = fields.iter().position(|f| *f == "kB_read/s")
+                    .zip(fields.iter().position(|f| *f == "kB_wrtn/s"));
+            }
+            Some(device) => {
+                let Some((read, write)) = columns else {
+                    continue;
+                };
+                let rate = |index: usize| fields.get(index).and_then(|value| value.parse::<f64>().ok()).map(|kb| kb * 1024.0);
+                if let (Some(read), Some(write)) = (rate(read), rate(write)) {
+                    rates.push((device.to_string(), (read, write)));
+                }
+            }
+            None => {}
+        }
+    }
+    rates
+}
+
+impl crate::widgets::Widget for DiskMonitorWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "disk_monitor"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    fn start(&self) {
+        self.start_command();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("Disk Monitor")
+            .id(egui::Id::new(format!("disk_monitor_{}", self.id)))
+            .open(&mut open)
+            .default_pos([200.0 + (idx as f32 * 50.0), 200.0 + (idx as f32 * 50.0)])
+            .default_size([800.0, 320.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
+                });
+                
+                ui.separator();
+                self.render_output(ui);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop();
+        self.start();
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for DiskMonitorWidget {
+    fn build_command(&self) -> CommandSpec {
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(DISK_POLL)
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
+    }
+}
+
+impl CommandOutputRenderer for DiskMonitorWidget {
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn render_output(&self, ui: &mut egui::Ui) {
+        // iostat holds the poll open for a couple of seconds, so show the last complete one until it's done
+        let lines = {
+            let output = self.executor.output.lock().unwrap();
+            let previous = self.executor.previous_output.lock().unwrap();
+            if output.iter().any(|line| line == "Command completed") || previous.is_empty() {
+                output.clone()
+            } else {
+                previous.clone()
+            }
+        };
+        
+        let mounts = parse_disk_output(&lines);
+        if mounts.is_empty() {
+            ui.label("No data available");
+            return;
+        }
+        
+        let rate = |value: Option<f64>| value.map(|value| format!("{}/s", format_bytes(value))).unwrap_or_else(|| "-".to_string());
+        
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .column(Column::auto().at_least(120.0).clip(true))
+                .column(Column::auto().at_least(120.0).clip(true))
+                .columns(Column::auto().at_least(70.0), 3)
+                .column(Column::initial(140.0).at_least(80.0))
+                .column(Column::remainder().at_least(150.0))
+                .header(20.0, |mut header| {
+                    for title in ["Mount", "Filesystem", "Size", "Used", "Avail", "Usage", "Read / Write"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|mut body| {
+                    for mount in &mounts {
+                        body.row(20.0, |mut row| {
+                            row.col(|ui| {
+                                ui.label(&mount.mount);
+                            });
+                            row.col(|ui| {
+                                ui.label(&mount.filesystem);
+                            });
+                            for value in [mount.total, mount.used, mount.available] {
+                                row.col(|ui| {
+                                    ui.monospace(format_bytes(value as f64));
+                                });
+                            }
+                            row.col(|ui| {
+                                // df's capacity counts reserved blocks as unavailable, so do the same
+                                let usable = mount.used + mount.available;
+                                let fraction = if usable > 0 { mount.used as f32 / usable as f32 } else { 0.0 };
+                                ui.add(egui::ProgressBar::new(fraction).text(format!("{:.0}%", fraction * 100.0)));
+                            });
+                            row.col(|ui| {
+                                ui.monospace(format!("{} / {}", rate(mount.read_rate), rate(mount.write_rate)));
+                            });
+                        });
+                    }
+                });
+        });
+    }
+}
+
+impl CommandControlBar for DiskMonitorWidget {}
+
+impl RefreshableWidget for DiskMonitorWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl DiskMonitorWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            refresh_interval_secs: 10,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+            config_unsaved: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn mount_paths_with_spaces_survive_and_pick_up_rates() {
+        let output = "\
+Filesystem     1024-blocks     Used Available Capacity Mounted on
+/dev/sda1         10000000  4500000   5500000      45% /
+/dev/sdb1          2000000   500000   1500000      25% /media/USB Stick
+//nas/team share  8000000  6000000   2000000      75% /mnt/team files
+== iostat
+Linux 6.1.0 (web-1) \t10/15/2026 \t_x86_64_\t(8 CPU)
+
+Device             tps    kB_read/s    kB_wrtn/s    kB_dscd/s    kB_read    kB_wrtn    kB_dscd
+sda1             50.00       999.00       999.00         0.00      99999      99999          0
+
+Device             tps    kB_read/s    kB_wrtn/s    kB_dscd/s    kB_read    kB_wrtn    kB_dscd
+sda1             12.00       100.00        50.00         0.00        100         50          0
+sdb1              0.00         0.00         0.00         0.00          0          0          0
+Command completed";
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        
+        let mounts = parse_disk_output(&lines);
+        let names: Vec<(&str, &str)> = mounts.iter().map(|m| (m.filesystem.as_str(), m.mount.as_str())).collect();
+        assert_eq!(names, vec![("/dev/sda1", "/"), ("/dev/sdb1", "/media/USB Stick"), ("//nas/team share", "/mnt/team files")]);
+        assert_eq!(mounts[0].used, 4500000 * 1024);
+        assert_eq!(mounts[0].read_rate, Some(100.0 * 1024.0));
+        assert_eq!(mounts[0].write_rate, Some(50.0 * 1024.0));
+        assert_eq!(mounts[1].read_rate, Some(0.0));
+        assert_eq!(mounts[2].read_rate, None);
+    }
+}
@@ -0,0 +1,377 @@
+use std::time::Duration;
+use eframe::egui;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget};
+
+// One line of JSON per container from each command; stats only covers running containers
+const DOCKER_POLL: &str = "docker ps --all --format '{{json .}}' && docker stats --no-stream --format '{{json .}}'";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DockerWidget {
+    pub id: usize,
+    pub version: i32,
+    pub refresh_interval_secs: u64,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default = "default_executor")]
+    pub logs_executor: CommandExecutor,  // `docker logs -f` for the selected container, display only
+    #[serde(skip, default)]
+    pub logs_container: Option<String>,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+// A row of the container table, merged from `docker ps` and `docker stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DockerContainer {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub status: String,
+    pub cpu: Option<String>,
+    pub memory: Option<String>,
+}
+
+// Containers in `docker ps` order, with stats filled in where docker reported them
+pub fn parse_docker_output(lines: &[String]) -> Vec<DockerContainer> {
+    let mut containers: Vec<DockerContainer> = Vec::new();
+    let text = |json: &Value, key: &str| json.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    
+    for line in lines {
+        let Ok(json) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        
+        if json.get("CPUPerc").is_some() {
+            let id = text(&json, "ID");
+            if let Some(container) = containers.iter_mut().find(|c| !id.is_empty() && c.id.starts_with(&id)) {
+                container.cpu = Some(text(&json, "CPUPerc"));
+                container.memory = Some(format!("{} ({})", text(&json, "MemUsage"), text(&json, "MemPerc")));
+            }
+        } else if json.get("Image").is_some() {
+            containers.push(DockerContainer {
+                id: text(&json, "ID"),
+                name: text(&json, "Names"),
+                image: text(&json, "Image"),
+                state: text(&json, "State"),
+                status: text(&json, "Status"),
+                cpu: None,
+                memory: None,
+            });
+        }
+    }
+    
+    containers
+}
+
+impl crate::widgets::Widget for DockerWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "docker"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    fn start(&self) {
+        self.start_command();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+        self.logs_executor.stop();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("Docker Containers")
+            .id(egui::Id::new(format!("docker_widget_{}", self.id)))
+            .open(&mut open)
+            .default_pos([300.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
+            .default_size([800.0, 500.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
+                });
+                
+                ui.separator();
+                
+                // `docker ps` answers well before `docker stats` does, so show the last complete poll until this one is done
+                let lines = {
+                    let output = self.executor.output.lock().unwrap();
+                    let previous = self.executor.previous_output.lock().unwrap();
+                    if output.iter().any(|line| line == "Command completed") || previous.is_empty() {
+                        output.clone()
+                    } else {
+                        previous.clone()
+                    }
+                };
+                let containers = parse_docker_output(&lines);
+                
+                if self.logs_container.is_some() {
+                    egui::TopBottomPanel::bottom(egui::Id::new(format!("docker_logs_{}", self.id)))
+                        .resizable(true)
+                        .default_height(200.0)
+                        .show_inside(ui, |ui| {
+                            self.render_logs(ui);
+                        });
+                }
+                
+                self.render_containers(ui, &containers);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop_command();
+        self.start_command();
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for DockerWidget {
+    fn build_command(&self) -> CommandSpec {
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(DOCKER_POLL)
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
+    }
+}
+
+impl CommandOutputRenderer for DockerWidget {
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+}
+
+impl CommandControlBar for DockerWidget {}
+
+impl RefreshableWidget for DockerWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl DockerWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            refresh_interval_secs: 5,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+            logs_executor: CommandExecutor::new(),
+            logs_container: None,
+            config_unsaved: false,
+        }
+    }
+    
+    fn render_containers(&mut self, ui: &mut egui::Ui, containers: &[DockerContainer]) {
+        if containers.is_empty() {
+            ui.label("No containers (is docker installed and running on this host?)");
+            return;
+        }
+        
+        let mut follow = None;
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new(format!("docker_containers_{}", self.id))
+                    .num_columns(6)
+                    .spacing([16.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["Name", "Image", "State", "CPU", "Memory", "Status"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        
+                        for container in containers {
+                            let selected = self.logs_container.as_ref() == Some(&container.name);
+                            if ui.selectable_label(selected, &container.name).on_hover_text("Follow this container's logs").clicked() {
+                                follow = Some(container.name.clone());
+                            }
+                            ui.label(&container.image);
+                            let state_color = if container.state == "running" {
+                                egui::Color32::from_rgb(80, 180, 80)
+                            } else {
+                                ui.visuals().weak_text_color()
+                            };
+                            ui.colored_label(state_color, &container.state);
+                            ui.monospace(container.cpu.as_deref().unwrap_or("-"));
+                            ui.monospace(container.memory.as_deref().unwrap_or("-"));
+                            ui.label(&container.status);
+                            ui.end_row();
+                        }
+                    });
+            });
+        
+        if let Some(name) = follow {
+            self.follow_logs(name);
+        }
+    }
+    
+    // Tail a container's logs on the same host (and prefix, sudo) as the container list
+    fn follow_logs(&mut self, name: String) {
+        self.logs_executor.stop();
+        self.logs_executor.clear_output();
+        // Share the sudo password so the logs don't prompt again
+        *self.logs_executor.sudo.lock().unwrap() = self.executor.sudo.lock().unwrap().clone();
+        let spec = CommandSpec::new("docker")
+            .arg("logs")
+            .arg("--follow")
+            .arg("--tail")
+            .arg("200")
+            .arg(&name);
+        self.logs_executor.run_continuous(self.wrap_for_host(spec, &ExecutionMode::Continuous));
+        self.logs_container = Some(name);
+    }
+    
+    fn render_logs(&mut self, ui: &mut egui::Ui) {
+        let Some(name) = self.logs_container.clone() else {
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            ui.strong(format!("Logs: {}", name));
+            if self.logs_executor.is_running() {
+                ui.spinner();
+            } else {
+                ui.label("(stopped)");
+            }
+            if ui.button("Close").clicked() {
+                self.logs_executor.stop();
+                self.logs_container = None;
+            }
+        });
+        
+        let redactor = self.executor.redactor();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in self.logs_executor.output.lock().unwrap().iter() {
+                    ui.label(egui::RichText::new(redactor.for_display(line)).monospace().size(12.0));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn stats_are_merged_into_the_matching_container() {
+        let lines = vec![
+            r#"{"ID":"3f2a1b9c8d7e","Names":"web","Image":"nginx:1.27","State":"running","Status":"Up 2 hours"}"#.to_string(),
+            r#"{"ID":"a1b2c3d4e5f6","Names":"job","Image":"alpine","State":"exited","Status":"Exited (0) 5 minutes ago"}"#.to_string(),
+            r#"{"ID":"3f2a1b9c8d7e","Name":"web","CPUPerc":"0.42%","MemUsage":"12MiB / 1.9GiB","MemPerc":"0.61%"}"#.to_string(),
+            "Cannot connect to the Docker daemon".to_string(),
+        ];
+        
+        let containers = parse_docker_output(&lines);
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].name, "web");
+        assert_eq!(containers[0].cpu.as_deref(), Some("0.42%"));
+        assert_eq!(containers[0].memory.as_deref(), Some("12MiB / 1.9GiB (0.61%)"));
+        assert_eq!(containers[1].state, "exited");
+        assert_eq!(containers[1].cpu, None);
+    }
+}
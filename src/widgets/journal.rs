@@ -0,0 +1,442 @@
+use eframe::egui;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandControlBar};
+
+// Where the log stream comes from on the selected host
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JournalSource {
+    Journald,   // Linux: journalctl -f
+    SystemLog,  // macOS: tail -F /var/log/system.log
+}
+
+impl JournalSource {
+    fn label(&self) -> &'static str {
+        match self {
+            JournalSource::Journald => "journald",
+            JournalSource::SystemLog => "system.log",
+        }
+    }
+}
+
+impl Default for JournalSource {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") { JournalSource::SystemLog } else { JournalSource::Journald }
+    }
+}
+
+// Severity, most severe first so a threshold keeps everything <= it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warning,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub const ALL: &'static [LogLevel] = &[LogLevel::Error, LogLevel::Warning, LogLevel::Info, LogLevel::Debug];
+    
+    fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warning => "Warning",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        }
+    }
+    
+    // The journalctl --priority that keeps this level and everything above
+    fn journald_priority(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "err",
+            LogLevel::Warning => "warning",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+    
+    // Syslog priorities 0-7: emerg..err, warning, notice/info, debug
+    fn from_priority(priority: u8) -> Self {
+        match priority {
+            0..=3 => LogLevel::Error,
+            4 => LogLevel::Warning,
+            5 | 6 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+    
+    fn color(&self, ui: &egui::Ui) -> egui::Color32 {
+        match self {
+            LogLevel::Error => egui::Color32::from_rgb(220, 80, 80),
+            LogLevel::Warning => egui::Color32::from_rgb(230, 160, 60),
+            LogLevel::Info => ui.visuals().text_color(),
+            LogLevel::Debug => ui.visuals().weak_text_color(),
+        }
+    }
+}
+
+// One displayed log line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub unit: String,
+    pub text: String,
+}
+
+// journalctl -o json gives one object per line; anything else (system.log, status lines) is plain text
+pub fn parse_log_line(line: &str) -> LogEntry {
+    if let Ok(json) = serde_json::from_str::<Value>(line) {
+        if json.get("MESSAGE").is_some() {
+            let field = |key: &str| json.get(key).and_then(Value::as_str).unwrap_or_default();
+            let level = field("PRIORITY").parse().map(LogLevel::from_priority).unwrap_or(LogLevel::Info);
+            let unit = field("_SYSTEMD_UNIT").to_string();
+            let identifier = if field("SYSLOG_IDENTIFIER").is_empty() { unit.as_str() } else { field("SYSLOG_IDENTIFIER") };
+            let time = field("__REALTIME_TIMESTAMP").parse::<i64>().map(format_micros).unwrap_or_default();
+            let pid = if field("_PID").is_empty() { String::new() } else { format!("[{}]", field("_PID")) };
+            // Binary messages come through as an array of bytes
+            let message = match json.get("MESSAGE") {
+                Some(Value::String(message)) => message.clone(),
+                Some(Value::Array(bytes)) => String::from_utf8_lossy(&bytes.iter().filter_map(|b| b.as_u64().map(|b| b as u8)).collect::<Vec<_>>()).into_owned(),
+                _ => String::new(),
+            };
+            return LogEntry {
+                level,
+                text: format!("{} {}{}: {}", time, identifier, pid, message),
+                unit,
+            };
+        }
+    }
+    
+    LogEntry {
+        level: guess_level(line),
+        unit: String::new(),
+        text: line.to_string(),
+    }
+}
+
+// system.log has no severity field, so go by the words in the message
+fn guess_level(line: &str) -> LogLevel {
+    let lower = line.to_lowercase();
+    if ["error", "fatal", "panic", "failed", "critical"].iter().any(|word| lower.contains(word)) {
+        LogLevel::Error
+    } else if lower.contains("warn") {
+        LogLevel::Warning
+    } else {
+        LogLevel::Info
+    }
+}
+
+// Microseconds since the epoch as "YYYY-MM-DD HH:MM:SS" UTC
+fn format_micros(micros: i64) -> String {
+    let secs = micros / 1_000_000;
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JournalWidget {
+    pub id: usize,
+    pub version: i32,
+    #[serde(default)]
+    pub source: JournalSource,
+    #[serde(default)]
+    pub unit: String,  // Empty for every unit
+    #[serde(default = "default_threshold")]
+    pub threshold: LogLevel,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+}
+
+fn default_threshold() -> LogLevel {
+    LogLevel::Info
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new().with_max_lines(5000)
+}
+
+impl crate::widgets::Widget for JournalWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "journal"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    fn start(&self) {
+        self.start_command();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new(format!("System Log ({})", self.source.label()))
+            .id(egui::Id::new(format!("journal_widget_{}", self.id)))
+            .open(&mut open)
+            .default_pos([250.0 + (idx as f32 * 50.0), 100.0 + (idx as f32 * 50.0)])
+            .default_size([800.0, 450.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                });
+                
+                ui.horizontal(|ui| {
+                    if self.render_filters(ui) {
+                        self.config_unsaved = true;
+                        self.handle_config_change();
+                        self.save_config();
+                    }
+                });
+                
+                ui.separator();
+                self.render_log(ui);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop_command();
+        self.start_command();
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for JournalWidget {
+    fn build_command(&self) -> CommandSpec {
+        match self.source {
+            JournalSource::Journald => {
+                let spec = CommandSpec::new("journalctl")
+                    .arg("--follow")
+                    .arg("--lines")
+                    .arg("200")
+                    .arg("--output")
+                    .arg("json")
+                    .arg("--output-fields")
+                    .arg("MESSAGE,PRIORITY,SYSLOG_IDENTIFIER,_PID,_SYSTEMD_UNIT")
+                    .arg("--priority")
+                    .arg(self.threshold.journald_priority());
+                if self.unit.is_empty() {
+                    spec
+                } else {
+                    spec.arg("--unit").arg(&self.unit)
+                }
+            }
+            // No server-side filtering here; the unit and threshold apply when displaying
+            JournalSource::SystemLog => CommandSpec::new("tail")
+                .arg("-F")
+                .arg("-n")
+                .arg("200")
+                .arg("/var/log/system.log"),
+        }
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Continuous
+    }
+}
+
+impl CommandControlBar for JournalWidget {}
+
+impl JournalWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            source: JournalSource::default(),
+            unit: String::new(),
+            threshold: default_threshold(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: default_executor(),
+            config_unsaved: false,
+        }
+    }
+    
+    // Source, level threshold and unit; true when any of them changed
+    fn render_filters(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        
+        ui.label("Source:");
+        egui::ComboBox::from_id_salt(format!("journal_source_{}", self.id))
+            .selected_text(self.source.label())
+            .show_ui(ui, |ui| {
+                for source in [JournalSource::Journald, JournalSource::SystemLog] {
+                    changed |= ui.selectable_value(&mut self.source, source, source.label()).changed();
+                }
+            });
+        
+        ui.label("Level:");
+        egui::ComboBox::from_id_salt(format!("journal_level_{}", self.id))
+            .selected_text(format!("{} and above", self.threshold.label()))
+            .show_ui(ui, |ui| {
+                for level in LogLevel::ALL {
+                    changed |= ui.selectable_value(&mut self.threshold, *level, level.label()).changed();
+                }
+            });
+        
+        // Applied once editing is done rather than restarting journalctl on every keystroke
+        ui.label("Unit:");
+        let id = egui::Id::new(format!("journal_unit_{}", self.id));
+        let mut unit = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| self.unit.clone());
+        let response = ui.add(egui::TextEdit::singleline(&mut unit)
+            .hint_text("all units, e.g. nginx.service")
+            .desired_width(160.0));
+        if response.lost_focus() && unit.trim() != self.unit {
+            self.unit = unit.trim().to_string();
+            changed = true;
+        }
+        ui.data_mut(|d| d.insert_temp(id, unit));
+        
+        changed
+    }
+    
+    fn render_log(&self, ui: &mut egui::Ui) {
+        let redactor = self.executor.redactor();
+        let lines = self.executor.output.lock().unwrap().clone();
+        let unit = self.unit.to_lowercase();
+        
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &lines {
+                    let entry = parse_log_line(line);
+                    if entry.level > self.threshold {
+                        continue;
+                    }
+                    // journalctl already filtered by unit; system.log lines only have the text to go on
+                    if self.source == JournalSource::SystemLog && !unit.is_empty() && !entry.text.to_lowercase().contains(&unit) {
+                        continue;
+                    }
+                    
+                    let text = egui::RichText::new(redactor.for_display(&entry.text))
+                        .monospace()
+                        .size(12.0)
+                        .color(entry.level.color(ui));
+                    ui.label(text);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn journald_json_lines_get_their_priority() {
+        let line = r#"{"__REALTIME_TIMESTAMP":"1760529600000000","PRIORITY":"3","SYSLOG_IDENTIFIER":"sshd","_PID":"812","_SYSTEMD_UNIT":"ssh.service","MESSAGE":"Connection reset by peer"}"#;
+        assert_eq!(parse_log_line(line), LogEntry {
+            level: LogLevel::Error,
+            unit: "ssh.service".to_string(),
+            text: "2025-10-15 12:00:00 sshd[812]: Connection reset by peer".to_string(),
+        });
+        
+        let warning = r#"{"PRIORITY":"4","SYSLOG_IDENTIFIER":"kernel","MESSAGE":"low memory"}"#;
+        assert_eq!(parse_log_line(warning).level, LogLevel::Warning);
+    }
+    
+    #[test]
+    fn plain_lines_are_leveled_by_their_words() {
+        assert_eq!(parse_log_line("Oct 15 12:00:00 mac kernel[0]: disk0 I/O error").level, LogLevel::Error);
+        assert_eq!(parse_log_line("Oct 15 12:00:00 mac app[12]: Warning: retrying").level, LogLevel::Warning);
+        assert_eq!(parse_log_line("Command completed").level, LogLevel::Info);
+    }
+}
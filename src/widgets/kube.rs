@@ -0,0 +1,575 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+use eframe::egui;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KubeWidget {
+    pub id: usize,
+    pub version: i32,
+    pub refresh_interval_secs: u64,
+    #[serde(default)]
+    pub namespace: String,  // Empty for all namespaces
+    #[serde(default)]
+    pub context: String,  // Empty for kubectl's current context
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default = "default_executor")]
+    pub logs_executor: CommandExecutor,  // `kubectl logs -f` for the selected pod, display only
+    #[serde(skip, default = "default_executor")]
+    pub contexts_executor: CommandExecutor,  // `kubectl config get-contexts`, run once on start
+    #[serde(skip, default)]
+    pub logs_pod: Option<PodRow>,
+    #[serde(skip, default)]
+    pub known_namespaces: BTreeSet<String>,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+// The parts of `kubectl get pods -o json` the table shows
+#[derive(Deserialize)]
+struct PodList {
+    #[serde(default)]
+    items: Vec<Pod>,
+}
+
+#[derive(Deserialize)]
+struct Pod {
+    metadata: PodMetadata,
+    #[serde(default)]
+    spec: PodSpec,
+    #[serde(default)]
+    status: PodStatus,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PodMetadata {
+    name: String,
+    #[serde(default)]
+    namespace: String,
+    deletion_timestamp: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct PodSpec {
+    node_name: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct PodStatus {
+    phase: Option<String>,
+    reason: Option<String>,
+    #[serde(default)]
+    container_statuses: Vec<ContainerStatus>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContainerStatus {
+    #[serde(default)]
+    ready: bool,
+    #[serde(default)]
+    restart_count: u32,
+    #[serde(default)]
+    state: ContainerState,
+}
+
+#[derive(Deserialize, Default)]
+struct ContainerState {
+    waiting: Option<StateReason>,
+    terminated: Option<StateReason>,
+}
+
+#[derive(Deserialize)]
+struct StateReason {
+    reason: Option<String>,
+}
+
+// A row of the pod table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PodRow {
+    pub namespace: String,
+    pub name: String,
+    pub status: String,
+    pub ready: String,
+    pub restarts: u32,
+    pub node: String,
+}
+
+impl From<Pod> for PodRow {
+    fn from(pod: Pod) -> Self {
+        let containers = &pod.status.container_statuses;
+        // Like kubectl's STATUS column: a container's waiting/terminated reason beats the pod phase
+        let container_reason = containers.iter()
+            .filter_map(|c| c.state.waiting.as_ref().or(c.state.terminated.as_ref()))
+            .find_map(|state| state.reason.clone());
+        let status = if pod.metadata.deletion_timestamp.is_some() {
+            "Terminating".to_string()
+        } else {
+            container_reason
+                .or(pod.status.reason)
+                .or(pod.status.phase)
+                .unwrap_or_else(|| "Unknown".to_string())
+        };
+        
+        Self {
+            ready: format!("{}/{}", containers.iter().filter(|c| c.ready).count(), containers.len()),
+            restarts: containers.iter().map(|c| c.restart_count).sum(),
+            node: pod.spec.node_name.unwrap_or_default(),
+            namespace: pod.metadata.namespace,
+            name: pod.metadata.name,
+            status,
+        }
+    }
+}
+
+// kubectl prints the pod list as one pretty-printed JSON document across many lines
+pub fn parse_pod_list(lines: &[String]) -> Result<Vec<PodRow>, String> {
+    let Some(start) = lines.iter().position(|line| line.trim_start().starts_with('{')) else {
+        // No document at all: whatever kubectl said instead, e.g. no cluster access
+        return match lines.iter().find(|line| !line.trim().is_empty()) {
+            Some(line) => Err(line.clone()),
+            None => Ok(Vec::new()),
+        };
+    };
+    
+    // The executor's own status lines follow the document, so stop at its end
+    let json = lines[start..].join("\n");
+    let list = serde_json::Deserializer::from_str(&json)
+        .into_iter::<PodList>()
+        .next()
+        .unwrap_or_else(|| Ok(PodList { items: Vec::new() }))
+        .map_err(|e| e.to_string())?;
+    Ok(list.items.into_iter().map(PodRow::from).collect())
+}
+
+impl crate::widgets::Widget for KubeWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "kube"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    fn start(&self) {
+        self.start_command();
+        self.load_contexts();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+        self.logs_executor.stop();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("Kubernetes Pods")
+            .id(egui::Id::new(format!("kube_widget_{}", self.id)))
+            .open(&mut open)
+            .default_pos([300.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
+            .default_size([850.0, 500.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
+                });
+                
+                // Only a complete document parses, so use the last finished poll while one is in flight
+                let output = self.executor.output.lock().unwrap().clone();
+                let pods = parse_pod_list(&output).or_else(|e| {
+                    let previous = self.executor.previous_output.lock().unwrap().clone();
+                    if previous.is_empty() { Err(e) } else { parse_pod_list(&previous) }
+                });
+                if self.namespace.is_empty() {
+                    if let Ok(ref pods) = pods {
+                        self.known_namespaces.extend(pods.iter().map(|pod| pod.namespace.clone()));
+                    }
+                }
+                
+                ui.horizontal(|ui| {
+                    if self.render_selectors(ui) {
+                        self.config_unsaved = true;
+                        self.handle_config_change();
+                        self.save_config();
+                    }
+                });
+                
+                ui.separator();
+                
+                if self.logs_pod.is_some() {
+                    egui::TopBottomPanel::bottom(egui::Id::new(format!("kube_logs_{}", self.id)))
+                        .resizable(true)
+                        .default_height(200.0)
+                        .show_inside(ui, |ui| {
+                            self.render_logs(ui);
+                        });
+                }
+                
+                match pods {
+                    Ok(pods) => self.render_pods(ui, &pods),
+                    Err(e) => {
+                        // Not JSON: kubectl's own error message, e.g. no cluster access
+                        ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("Couldn't read the pod list: {}", e));
+                        self.render_output(ui);
+                    }
+                }
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop_command();
+        self.start_command();
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for KubeWidget {
+    fn build_command(&self) -> CommandSpec {
+        let spec = self.kubectl().arg("get").arg("pods");
+        let spec = if self.namespace.is_empty() {
+            spec.arg("--all-namespaces")
+        } else {
+            spec.arg("--namespace").arg(&self.namespace)
+        };
+        spec.arg("--output").arg("json")
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
+    }
+}
+
+impl CommandOutputRenderer for KubeWidget {
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+}
+
+impl CommandControlBar for KubeWidget {}
+
+impl RefreshableWidget for KubeWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl KubeWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            refresh_interval_secs: 5,
+            namespace: String::new(),
+            context: String::new(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+            logs_executor: CommandExecutor::new(),
+            contexts_executor: CommandExecutor::new(),
+            logs_pod: None,
+            known_namespaces: BTreeSet::new(),
+            config_unsaved: false,
+        }
+    }
+    
+    // kubectl pinned to the chosen context
+    fn kubectl(&self) -> CommandSpec {
+        let spec = CommandSpec::new("kubectl");
+        if self.context.is_empty() {
+            spec
+        } else {
+            spec.arg("--context").arg(&self.context)
+        }
+    }
+    
+    // List the contexts on the selected host for the context dropdown
+    fn load_contexts(&self) {
+        let spec = CommandSpec::new("kubectl")
+            .arg("config")
+            .arg("get-contexts")
+            .arg("--output")
+            .arg("name");
+        *self.contexts_executor.sudo.lock().unwrap() = self.executor.sudo.lock().unwrap().clone();
+        self.contexts_executor.clear_output();
+        self.contexts_executor.run_once(self.wrap_for_host(spec, &ExecutionMode::OneShot));
+    }
+    
+    // Context and namespace dropdowns; true when either changed
+    fn render_selectors(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        
+        ui.label("Context:");
+        let contexts = self.contexts_executor.output.lock().unwrap().clone();
+        let context_label = if self.context.is_empty() { "(current)" } else { self.context.as_str() };
+        egui::ComboBox::from_id_salt(format!("kube_context_{}", self.id))
+            .selected_text(context_label)
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut self.context, String::new(), "(current)").changed();
+                for context in contexts.iter().filter(|c| !c.trim().is_empty()) {
+                    changed |= ui.selectable_value(&mut self.context, context.trim().to_string(), context.trim()).changed();
+                }
+            });
+        
+        ui.label("Namespace:");
+        let mut namespaces = self.known_namespaces.clone();
+        if !self.namespace.is_empty() {
+            namespaces.insert(self.namespace.clone());
+        }
+        let namespace_label = if self.namespace.is_empty() { "All namespaces" } else { self.namespace.as_str() };
+        egui::ComboBox::from_id_salt(format!("kube_namespace_{}", self.id))
+            .selected_text(namespace_label)
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut self.namespace, String::new(), "All namespaces").changed();
+                for namespace in &namespaces {
+                    changed |= ui.selectable_value(&mut self.namespace, namespace.clone(), namespace).changed();
+                }
+            });
+        
+        // Namespaces seen so far come from the all-namespaces listing; a new context has its own
+        if changed {
+            self.known_namespaces.clear();
+        }
+        changed
+    }
+    
+    fn render_pods(&mut self, ui: &mut egui::Ui, pods: &[PodRow]) {
+        if pods.is_empty() {
+            ui.label("No pods");
+            return;
+        }
+        
+        let mut follow = None;
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::Grid::new(format!("kube_pods_{}", self.id))
+                    .num_columns(6)
+                    .spacing([16.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["Namespace", "Name", "Status", "Ready", "Restarts", "Node"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        
+                        for pod in pods {
+                            ui.label(&pod.namespace);
+                            let selected = self.logs_pod.as_ref().is_some_and(|p| p.namespace == pod.namespace && p.name == pod.name);
+                            if ui.selectable_label(selected, &pod.name).on_hover_text("Follow this pod's logs").clicked() {
+                                follow = Some(pod.clone());
+                            }
+                            let status_color = match pod.status.as_str() {
+                                "Running" | "Succeeded" | "Completed" => egui::Color32::from_rgb(80, 180, 80),
+                                "Pending" | "ContainerCreating" | "Terminating" => egui::Color32::from_rgb(230, 160, 60),
+                                _ => egui::Color32::from_rgb(220, 80, 80),
+                            };
+                            ui.colored_label(status_color, &pod.status);
+                            ui.monospace(&pod.ready);
+                            if pod.restarts > 0 {
+                                ui.colored_label(egui::Color32::from_rgb(230, 160, 60), pod.restarts.to_string());
+                            } else {
+                                ui.monospace("0");
+                            }
+                            ui.label(&pod.node);
+                            ui.end_row();
+                        }
+                    });
+            });
+        
+        if let Some(pod) = follow {
+            self.follow_logs(pod);
+        }
+    }
+    
+    // Stream a pod's logs (all containers) through the same host, prefix and context as the list
+    fn follow_logs(&mut self, pod: PodRow) {
+        self.logs_executor.stop();
+        self.logs_executor.clear_output();
+        *self.logs_executor.sudo.lock().unwrap() = self.executor.sudo.lock().unwrap().clone();
+        let spec = self.kubectl()
+            .arg("logs")
+            .arg("--follow")
+            .arg("--all-containers")
+            .arg("--tail")
+            .arg("200")
+            .arg("--namespace")
+            .arg(&pod.namespace)
+            .arg(&pod.name);
+        self.logs_executor.run_continuous(self.wrap_for_host(spec, &ExecutionMode::Continuous));
+        self.logs_pod = Some(pod);
+    }
+    
+    fn render_logs(&mut self, ui: &mut egui::Ui) {
+        let Some(pod) = self.logs_pod.clone() else {
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            ui.strong(format!("Logs: {}/{}", pod.namespace, pod.name));
+            if self.logs_executor.is_running() {
+                ui.spinner();
+            } else {
+                ui.label("(stopped)");
+            }
+            if ui.button("Close").clicked() {
+                self.logs_executor.stop();
+                self.logs_pod = None;
+            }
+        });
+        
+        let redactor = self.executor.redactor();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in self.logs_executor.output.lock().unwrap().iter() {
+                    ui.label(egui::RichText::new(redactor.for_display(line)).monospace().size(12.0));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn pod_list_json_becomes_rows() {
+        let json = r#"{
+            "apiVersion": "v1",
+            "items": [
+                {
+                    "metadata": {"name": "api-7d9f", "namespace": "prod"},
+                    "spec": {"nodeName": "node-a"},
+                    "status": {
+                        "phase": "Running",
+                        "containerStatuses": [
+                            {"ready": true, "restartCount": 0, "state": {"running": {}}},
+                            {"ready": false, "restartCount": 4, "state": {"waiting": {"reason": "CrashLoopBackOff"}}}
+                        ]
+                    }
+                },
+                {
+                    "metadata": {"name": "worker-1", "namespace": "jobs"},
+                    "spec": {},
+                    "status": {"phase": "Pending"}
+                }
+            ]
+        }"#;
+        let lines: Vec<String> = json.lines().map(str::to_string).collect();
+        
+        let pods = parse_pod_list(&lines).unwrap();
+        assert_eq!(pods.len(), 2);
+        assert_eq!(pods[0], PodRow {
+            namespace: "prod".to_string(),
+            name: "api-7d9f".to_string(),
+            status: "CrashLoopBackOff".to_string(),
+            ready: "1/2".to_string(),
+            restarts: 4,
+            node: "node-a".to_string(),
+        });
+        assert_eq!(pods[1].status, "Pending");
+        assert_eq!(pods[1].ready, "0/0");
+        
+        let mut finished = lines.clone();
+        finished.push("Command completed".to_string());
+        assert_eq!(parse_pod_list(&finished).unwrap().len(), 2);
+        assert!(parse_pod_list(&lines[..5]).is_err());
+        assert!(parse_pod_list(&["error: You must be logged in to the server".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use std::time::Duration;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, format_bytes, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget};
+
+// /proc/meminfo on Linux; macOS has vm_stat for pages and sysctl for the totals.
+// Both are plain "key: value" lists, so they're parsed here rather than shipped off to jc
+const MEMORY_POLL: &str = "if [ -r /proc/meminfo ]; then cat /proc/meminfo; else vm_stat; sysctl hw.memsize vm.swapusage; fi";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MemoryMonitorWidget {
+    pub id: usize,
+    pub version: i32,
+    pub refresh_interval_secs: u64,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+    #[serde(skip, default)]
+    pub config_unsaved: bool,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+// Byte counts for RAM and swap
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryStats {
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+    pub cached: u64,  // Page cache and buffers, reclaimable on demand
+    pub swap_total: u64,
+    pub swap_used: u64,
+}
+
+pub fn parse_memory_output(lines: &[String]) -> Option<MemoryStats> {
+    if lines.iter().any(|line| line.starts_with("MemTotal:")) {
+        parse_meminfo(lines)
+    } else {
+        parse_vm_stat(lines)
+    }
+}
+
+// "MemTotal:       16315208 kB"
+fn parse_meminfo(lines: &[String]) -> Option<MemoryStats> {
+    let field = |key: &str| -> u64 {
+        lines.iter()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|value| value.split_whitespace().next()?.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .unwrap_or(0)
+    };
+    
+    let total = field("MemTotal");
+    if total == 0 {
+        return None;
+    }
+    let free = field("MemFree");
+    let cached = field("Buffers") + field("Cached") + field("SReclaimable");
+    let swap_total = field("SwapTotal");
+    Some(MemoryStats {
+        total,
+        // Same accounting as `free`: whatever isn't free or cache
+        used: total.saturating_sub(free + cached),
+        free,
+        cached,
+        swap_total,
+        swap_used: swap_total.saturating_sub(field("SwapFree")),
+    })
+}
+
+// vm_stat counts pages ("Pages free:   12345."); sysctl gives "hw.memsize: 17179869184"
+// and "vm.swapusage: total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)"
+fn parse_vm_stat(lines: &[String]) -> Option<MemoryStats> {
+    let page_size: u64 = lines.iter()
+        .find_map(|line| line.split_once("page size of ")?.1.split_whitespace().next()?.parse().ok())
+        .unwrap_or(4096);
+    let pages = |key: &str| -> u64 {
+        lines.iter()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|value| value.trim().trim_end_matches('.').parse::<u64>().ok())
+            .map(|count| count * page_size)
+            .unwrap_or(0)
+    };
+    
+    let total: u64 = lines.iter()
+        .find_map(|line| line.strip_prefix("hw.memsize:")?.trim().parse().ok())?;
+    let free = pages("Pages free") + pages("Pages speculative");
+    let cached = pages("File-backed pages");
+    let swap = |key: &str| -> u64 {
+        lines.iter()
+            .find_map(|line| line.strip_prefix("vm.swapusage:"))
+            .and_then(|line| line.split_once(&format!("{} = ", key)))
+            .and_then(|(_, rest)| parse_sysctl_size(rest.split_whitespace().next()?))
+            .unwrap_or(0)
+    };
+    Some(MemoryStats {
+        total,
+        used: total.saturating_sub(free + cached),
+        free,
+        cached,
+        swap_total: swap("total"),
+        swap_used: swap("used"),
+    })
+}
+
+// "1024.50M" -> bytes
+fn parse_sysctl_size(text: &str) -> Option<u64> {
+    let (number, multiplier) = match text.chars().last()? {
+        'K' => (&text[..text.len() - 1], 1024.0),
+        'M' => (&text[..text.len() - 1], 1024.0 * 1024.0),
+        'G' => (&text[..text.len() - 1], 1024.0 * 1024.0 * 1024.0),
+        _ => (text, 1.0),
+    };
+    number.parse::<f64>().ok().map(|value| (value * multiplier) as u64)
+}
+
+impl crate::widgets::Widget for MemoryMonitorWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "memory_monitor"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+        self.config_unsaved = false;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn needs_restart(&self) -> bool {
+        self.config_unsaved
+    }
+    
+    fn start(&self) {
+        self.start_command();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("Memory Monitor")
+            .id(egui::Id::new(format!("memory_monitor_{}", self.id)))
+            .open(&mut open)
+            .default_pos([200.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
+            .default_size([560.0, 180.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
+                });
+                
+                ui.separator();
+                self.render_output(ui);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop();
+        self.start();
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+}
+
+impl CommandWidget for MemoryMonitorWidget {
+    fn build_command(&self) -> CommandSpec {
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(MEMORY_POLL)
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
+    }
+}
+
+impl CommandOutputRenderer for MemoryMonitorWidget {
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn render_output(&self, ui: &mut egui::Ui) {
+        // The buffer is cleared at the start of every poll, so fall back to the last complete one
+        let lines = {
+            let output = self.executor.output.lock().unwrap();
+            if output.is_empty() {
+                self.executor.previous_output.lock().unwrap().clone()
+            } else {
+                output.clone()
+            }
+        };
+        
+        let Some(stats) = parse_memory_output(&lines) else {
+            ui.label("No data available");
+            return;
+        };
+        
+        let rows = [
+            ("Memory", stats.total, Some(stats.used), Some(stats.free), Some(stats.cached)),
+            ("Swap", stats.swap_total, Some(stats.swap_used), Some(stats.swap_total.saturating_sub(stats.swap_used)), None),
+        ];
+        let bytes = |value: Option<u64>| value.map(|value| format_bytes(value as f64)).unwrap_or_else(|| "-".to_string());
+        
+        TableBuilder::new(ui)
+            .striped(true)
+            .column(Column::auto().at_least(70.0))
+            .columns(Column::auto().at_least(80.0), 4)
+            .column(Column::remainder().at_least(120.0))
+            .header(20.0, |mut header| {
+                for title in ["", "Total", "Used", "Free", "Cached", "Usage"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|mut body| {
+                for (label, total, used, free, cached) in rows {
+                    body.row(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.strong(label);
+                        });
+                        row.col(|ui| {
+                            ui.monospace(format_bytes(total as f64));
+                        });
+                        for value in [used, free, cached] {
+                            row.col(|ui| {
+                                ui.monospace(bytes(value));
+                            });
+                        }
+                        row.col(|ui| {
+                            if total > 0 {
+                                let fraction = used.unwrap_or(0) as f32 / total as f32;
+                                ui.add(egui::ProgressBar::new(fraction).text(format!("{:.0}%", fraction * 100.0)));
+                            } else {
+                                ui.weak("none");
+                            }
+                        });
+                    });
+                }
+            });
+    }
+}
+
+impl CommandControlBar for MemoryMonitorWidget {}
+
+impl RefreshableWidget for MemoryMonitorWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl MemoryMonitorWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            refresh_interval_secs: 5,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+            config_unsaved: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+    
+    #[test]
+    fn meminfo_is_parsed_like_free() {
+        let output = lines("\
+MemTotal:        1000000 kB
+MemFree:          200000 kB
+MemAvailable:     600000 kB
+Buffers:           50000 kB
+Cached:           300000 kB
+SReclaimable:      50000 kB
+SwapTotal:        500000 kB
+SwapFree:         400000 kB
+Command completed");
+
+        let stats = parse_memory_output(&output).unwrap();
+        assert_eq!(stats.total, 1000000 * 1024);
+        assert_eq!(stats.free, 200000 * 1024);
+        assert_eq!(stats.cached, 400000 * 1024);
+        assert_eq!(stats.used, 400000 * 1024);
+        assert_eq!(stats.swap_used, 100000 * 1024);
+    }
+    
+    #[test]
+    fn vm_stat_pages_and_sysctl_totals_are_parsed() {
+        let output = lines("\
+Mach Virtual Memory Statistics: (page size of 16384 bytes)
+Pages free:                               10000.
+Pages active:                            200000.
+Pages speculative:                         2000.
+File-backed pages:                        50000.
+hw.memsize: 17179869184
+vm.swapusage: total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)");
+
+        let stats = parse_memory_output(&output).unwrap();
+        assert_eq!(stats.total, 17179869184);
+        assert_eq!(stats.free, 12000 * 16384);
+        assert_eq!(stats.cached, 50000 * 16384);
+        assert_eq!(stats.used, 17179869184 - 62000 * 16384);
+        assert_eq!(stats.swap_total, 2048 * 1024 * 1024);
+        assert_eq!(stats.swap_used, (1024.5 * 1024.0 * 1024.0) as u64);
+    }
+}
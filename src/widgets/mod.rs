@@ -1,10 +1,24 @@
 pub mod command_widget;
+pub mod command_runner;
 pub mod raw_command;
 pub mod cpu_monitor;
+pub mod cpu_audio;
+pub mod audio_output;
+pub mod reverse_dns;
 pub mod system_info;
 pub mod process_monitor;
 pub mod network_monitor;
 pub mod about;
+pub mod sql_query;
+pub mod docker;
+pub mod kube;
+pub mod journal;
+pub mod summary;
+pub mod memory_monitor;
+pub mod disk_monitor;
+pub mod chart;
+pub mod alerts;
+pub mod ansi;
 
 pub use raw_command::RawCommandWidget;
 pub use cpu_monitor::CPUMonitorWidget;
@@ -12,6 +26,14 @@ pub use system_info::SystemInfoWidget;
 pub use process_monitor::ProcessMonitorWidget;
 pub use network_monitor::NetworkMonitorWidget;
 pub use about::AboutWidget;
+pub use sql_query::SqlQueryWidget;
+pub use docker::DockerWidget;
+pub use kube::KubeWidget;
+pub use journal::JournalWidget;
+pub use summary::SummaryWidget;
+pub use memory_monitor::MemoryMonitorWidget;
+pub use disk_monitor::DiskMonitorWidget;
+pub use chart::ChartWidget;
 
 use serde::{Serialize, Deserialize};
 use enum_dispatch::enum_dispatch;
@@ -28,14 +50,21 @@ pub trait Widget {
     fn stop(&self) {} 
     fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool);
     fn refresh(&self) { self.stop(); self.start(); }
-    fn config_changed(&self) -> bool { false }
+    // The workspace went out of view with the widget left running - default no-op
+    fn leave_view(&self) {}
     fn needs_restart(&self) -> bool { false }
     
-    // Restore historical data to widget - default no-op for widgets without data
-    fn restore_widget_data(&mut self, _data: Vec<String>); 
+    // Restore recorded (timestamp, line, stream) triples to the widget - no-op for widgets without data
+    fn restore_widget_data(&mut self, _data: Vec<command_widget::TimedLine>); 
     
     // Set available hosts for command widgets - default no-op
-    fn set_available_hosts(&mut self, _hosts: Vec<crate::database::investigation_db::Host>); 
+    fn set_available_hosts(&mut self, _hosts: Vec<crate::database::investigation_db::Host>) {}
+    
+    // A host was edited: widgets on it follow it to its new alias and prefix, then take the new host list
+    fn host_edited(&mut self, _from: &crate::database::investigation_db::Host, _to: &crate::database::investigation_db::Host, _hosts: Vec<crate::database::investigation_db::Host>) {}
+    
+    // Cost of the widget's command so far - None for widgets that don't run one
+    fn resource_usage(&self) -> Option<command_widget::WidgetUsage> { None }
 }
 
 // TODO: Figure out how to not repeat this. 
@@ -64,8 +93,42 @@ impl WidgetType {
     pub fn new_about(id: usize) -> Self {
         WidgetType::About(AboutWidget::new(id))
     }
+    
+    pub fn new_sql_query(id: usize) -> Self {
+        WidgetType::SqlQuery(SqlQueryWidget::new(id))
+    }
+    
+    pub fn new_docker(id: usize) -> Self {
+        WidgetType::Docker(DockerWidget::new(id))
+    }
+    
+    pub fn new_kube(id: usize) -> Self {
+        WidgetType::Kube(KubeWidget::new(id))
+    }
+    
+    pub fn new_journal(id: usize) -> Self {
+        WidgetType::Journal(JournalWidget::new(id))
+    }
+    
+    pub fn new_summary(id: usize) -> Self {
+        WidgetType::Summary(SummaryWidget::new(id))
+    }
+    
+    pub fn new_memory_monitor(id: usize) -> Self {
+        WidgetType::MemoryMonitor(MemoryMonitorWidget::new(id))
+    }
+    
+    pub fn new_disk_monitor(id: usize) -> Self {
+        WidgetType::DiskMonitor(DiskMonitorWidget::new(id))
+    }
+    
+    pub fn new_chart(id: usize) -> Self {
+        WidgetType::Chart(ChartWidget::new(id))
+    }
 }
 
+// A workspace holds a handful of these, so a big variant costs nothing worth boxing for
+#[allow(clippy::large_enum_variant)]
 #[enum_dispatch(Widget)]
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -76,5 +139,79 @@ pub enum WidgetType {
     ProcessMonitor(ProcessMonitorWidget),
     NetworkMonitor(NetworkMonitorWidget),
     About(AboutWidget),
+    SqlQuery(SqlQueryWidget),
+    Docker(DockerWidget),
+    Kube(KubeWidget),
+    Journal(JournalWidget),
+    Summary(SummaryWidget),
+    MemoryMonitor(MemoryMonitorWidget),
+    DiskMonitor(DiskMonitorWidget),
+    Chart(ChartWidget),
 }
 
+
+// Window position and content size (x, y, width, height), as saved with the widget
+pub type WidgetLayout = [f32; 4];
+
+// Where egui keeps a widget's current layout, and a saved one to apply on its next frame
+fn layout_id(widget_id: usize) -> egui::Id {
+    egui::Id::new(("widget_layout", widget_id))
+}
+
+fn layout_restore_id(widget_id: usize) -> egui::Id {
+    egui::Id::new(("widget_layout_restore", widget_id))
+}
+
+// The layer a widget's window was last shown on, to tell which window is in front
+fn window_layer_id(widget_id: usize) -> egui::Id {
+    egui::Id::new(("widget_window_layer", widget_id))
+}
+
+// Of the given widgets, the one whose window is in front of every other window
+pub fn front_widget(ctx: &egui::Context, widget_ids: impl IntoIterator<Item = usize>) -> Option<usize> {
+    let top = ctx.top_layer_id()?;
+    widget_ids.into_iter().find(|widget_id| ctx.data(|d| d.get_temp::<egui::LayerId>(window_layer_id(*widget_id))) == Some(top))
+}
+
+pub fn current_layout(ctx: &egui::Context, widget_id: usize) -> Option<WidgetLayout> {
+    ctx.data(|d| d.get_temp(layout_id(widget_id)))
+}
+
+pub fn restore_layout(ctx: &egui::Context, widget_id: usize, layout: WidgetLayout) {
+    ctx.data_mut(|d| d.insert_temp(layout_restore_id(widget_id), layout));
+}
+
+// Widgets show their main window through this so its layout can be saved and restored
+pub trait WidgetWindow {
+    fn show_for_widget<R>(self, ctx: &egui::Context, widget_id: usize, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> Option<egui::InnerResponse<Option<R>>>;
+}
+
+impl WidgetWindow for egui::Window<'_> {
+    fn show_for_widget<R>(self, ctx: &egui::Context, widget_id: usize, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> Option<egui::InnerResponse<Option<R>>> {
+        // Forced for one frame, since egui may remember this window id from another investigation;
+        // a fixed size is stored as the size the user can then resize from
+        let restore = ctx.data_mut(|d| d.remove_temp::<WidgetLayout>(layout_restore_id(widget_id)));
+        let window = match restore {
+            Some([x, y, width, height]) => self.current_pos([x, y]).fixed_size([width, height]),
+            None => self,
+        };
+        
+        let mut content_size = None;
+        let response = window.show(ctx, |ui| {
+            content_size = Some(ui.max_rect().size());
+            add_contents(ui)
+        });
+        
+        // Collapsed windows keep their last content size
+        if let Some(ref response) = response {
+            ctx.data_mut(|d| d.insert_temp(window_layer_id(widget_id), response.response.layer_id));
+            let previous = current_layout(ctx, widget_id);
+            let size = content_size.or(previous.map(|[_, _, width, height]| egui::vec2(width, height)));
+            if let Some(size) = size {
+                let min = response.response.rect.min;
+                ctx.data_mut(|d| d.insert_temp(layout_id(widget_id), [min.x, min.y, size.x, size.y]));
+            }
+        }
+        response
+    }
+}
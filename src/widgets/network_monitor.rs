@@ -1,25 +1,74 @@
+use std::collections::HashMap;
 use std::time::Duration;
 use eframe::egui;
+use crate::widgets::WidgetWindow;
+use crate::widgets::reverse_dns;
 use serde::{Serialize, Deserialize};
-use crate::widgets::command_widget::{CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::widgets::alerts::{self, AlertWatch, ThresholdAlert, render_alert_settings};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, format_bytes, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, FilterableOutput, FilterMode, FilterCache, RefreshableWidget, SampleParser, render_filter_box};
+
+// Interface byte counters and socket owners follow the connections in the same poll, so recorded
+// polls keep them too. lsof only sees the user's own processes without root, and Linux boxes may
+// only have ss; whatever comes back is used, and sockets nobody claimed are shown without an owner
+const INTERFACES_MARKER: &str = "--interfaces--";
+const PROCESSES_MARKER: &str = "--processes--";
+const NETWORK_POLL: &str = "netstat -an; echo --interfaces--; \
+if [ -r /proc/net/dev ]; then cat /proc/net/dev; else netstat -ib; fi; echo --processes--; \
+if command -v lsof >/dev/null 2>&1; then lsof -nP -i 2>/dev/null; \
+elif command -v ss >/dev/null 2>&1; then ss -tunap 2>/dev/null; fi; exit 0";
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct NetworkMonitorWidget {
     pub id: usize,
     pub version: i32,
+    #[serde(default = "default_refresh_interval_secs")]
     pub refresh_interval_secs: u64,
+    #[serde(default)]
     pub filter_text: String,
+    #[serde(default)]
+    pub filter_mode: FilterMode,
+    #[serde(default)]
     pub show_established_only: bool,
+    #[serde(default)]
+    pub resolve_names: bool,  // Show foreign addresses by their reverse DNS name once looked up
+    #[serde(default = "default_established_alert")]
+    pub established_alert: ThresholdAlert,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
     #[serde(skip, default = "default_executor")]
     pub executor: CommandExecutor,
     #[serde(skip, default)]
     pub config_unsaved: bool,
+    #[serde(skip, default)]
+    pub filter_cache: FilterCache,
+    #[serde(skip, default)]
+    pub interface_rates: Arc<Mutex<Vec<InterfaceRate>>>,  // Between the last two live polls
+    #[serde(skip, default)]
+    pub established_alert_watch: Arc<Mutex<AlertWatch>>,
+    #[serde(skip, default)]
+    pub history: Vec<(i64, Vec<String>)>,  // Recorded polls with their timestamps
+    #[serde(skip, default)]
+    pub history_index: Option<usize>,  // Poll being reviewed; None shows live output
 }
 
 fn default_executor() -> CommandExecutor {
     CommandExecutor::new()
 }
 
+fn default_refresh_interval_secs() -> u64 {
+    5
+}
+
+fn default_established_alert() -> ThresholdAlert {
+    ThresholdAlert::new(500.0, 0)
+}
+
 impl crate::widgets::Widget for NetworkMonitorWidget {
     fn widget_type_name(&self) -> &'static str {
         "network_monitor"
@@ -44,10 +93,6 @@ impl crate::widgets::Widget for NetworkMonitorWidget {
         self.executor.set_database(database, widget_id, widget_version);
     }
     
-    fn config_changed(&self) -> bool {
-        self.config_unsaved
-    }
-    
     
     fn needs_restart(&self) -> bool {
         // Only restart if refresh interval changed, not for filter changes
@@ -56,7 +101,13 @@ impl crate::widgets::Widget for NetworkMonitorWidget {
         self.config_unsaved
     }
     
+    // Rates start over, since a restart may be on another host
     fn start(&self) {
+        self.interface_rates.lock().unwrap().clear();
+        self.established_alert_watch.lock().unwrap().set_alert(self.established_alert.clone());
+        let parser = InterfaceRateParser::new(self.interface_rates.clone())
+            .with_alert(self.established_alert_watch.clone(), self.selected_host());
+        *self.executor.sample_parser.lock().unwrap() = Some(Box::new(parser));
         self.start_command();
     }
     
@@ -74,7 +125,7 @@ impl crate::widgets::Widget for NetworkMonitorWidget {
             .default_pos([300.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
             .default_size([700.0, 400.0])
             .resizable(true)
-            .show(ctx, |ui| {
+            .show_for_widget(ctx, self.id, |ui| {
                 // Control bar
                 ui.horizontal(|ui| {
                     refresh_clicked = self.render_controls(ui);
@@ -85,34 +136,42 @@ impl crate::widgets::Widget for NetworkMonitorWidget {
                     ui.checkbox(&mut self.show_established_only, "Established Only");
                     if old_established != self.show_established_only {
                         self.config_unsaved = true;
+                        self.save_config();
+                    }
+                    
+                    if ui.checkbox(&mut self.resolve_names, "Resolve names")
+                        .on_hover_text("Look up foreign addresses in DNS from this machine; ones that don't resolve stay as IPs")
+                        .changed() {
+                        self.save_config();
                     }
                     
                     ui.separator();
                     
-                    ui.label("Filter:");
-                    let old_filter = self.filter_text.clone();
-                    ui.text_edit_singleline(&mut self.filter_text);
-                    if old_filter != self.filter_text {
+                    let error = self.filter_error();
+                    if render_filter_box(ui, &mut self.filter_text, &mut self.filter_mode, error) {
                         self.config_unsaved = true;
+                        self.save_config();
                     }
                     
                     ui.separator();
-                    ui.label("Interval:");
-                    let old_interval = self.refresh_interval_secs;
-                    ui.add(egui::DragValue::new(&mut self.refresh_interval_secs).range(1..=60).suffix("s"));
-                    if old_interval != self.refresh_interval_secs {
-                        self.config_unsaved = true;
-                        if self.executor.is_running() {
-                            self.stop();
-                            self.start();
+                    self.render_interval_control(ui);
+                    
+                    ui.separator();
+                    ui.menu_button("🔔 Alert", |ui| {
+                        if render_alert_settings(ui, &mut self.established_alert, "established connections", "", 1_000_000.0) {
+                            self.established_alert_watch.lock().unwrap().set_alert(self.established_alert.clone());
+                            self.save_config();
                         }
-                    }
+                    });
                 });
                 
+                self.render_history_controls(ui);
+                self.render_interface_rates(ui);
+                
                 ui.separator();
                 
-                // Output from netstat command
-                self.render_output(ui);
+                let lines = self.displayed_lines();
+                self.render_connections(ui, &lines);
             });
         
         (open, refresh_clicked)
@@ -123,20 +182,29 @@ impl crate::widgets::Widget for NetworkMonitorWidget {
         self.start();
     }
     
-    fn restore_widget_data(&mut self, data: Vec<String>) {
-        self.executor.load_historical_output(data);
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
     }
     
     fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
-        self.executor.set_available_hosts(hosts);
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
     }
 }
 
 // Implement the CommandWidget trait
 impl CommandWidget for NetworkMonitorWidget {
     fn build_command(&self) -> CommandSpec {
-        CommandSpec::new("netstat")
-            .arg("-an")
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(NETWORK_POLL)
     }
     
     fn executor(&self) -> &CommandExecutor {
@@ -147,6 +215,30 @@ impl CommandWidget for NetworkMonitorWidget {
         &mut self.executor
     }
     
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
     fn execution_mode(&self) -> ExecutionMode {
         ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
     }
@@ -159,18 +251,451 @@ impl CommandOutputRenderer for NetworkMonitorWidget {
     }
 }
 
-impl CommandControlBar for NetworkMonitorWidget {}
+impl CommandControlBar for NetworkMonitorWidget {
+    fn table_export(&self) -> Option<(Vec<&'static str>, Vec<Vec<String>>)> {
+        // Exported like recorded output, so with the investigation's redaction applied
+        let redactor = self.executor.redactor();
+        let lines = self.displayed_lines();
+        let (connections, owners) = split_poll(&lines);
+        let rows = connections.iter()
+            .filter_map(|line| {
+                let owner = socket_owner(line, &owners);
+                let fields = parse_connection(line)?;
+                self.shows_line(&with_owner(line, owner)).then_some((fields, owner))
+            })
+            .map(|(fields, owner)| fields.iter()
+                .map(String::as_str)
+                .chain([owner.unwrap_or_default()])
+                .map(|field| redactor.redact(field).into_owned())
+                .collect())
+            .collect();
+        Some((vec!["Proto", "Recv-Q", "Send-Q", "Local Address", "Foreign Address", "State", "Process (PID)"], rows))
+    }
+}
+
+// netstat's lines, and socket owners by local port from their section
+pub fn split_poll(lines: &[String]) -> (&[String], HashMap<String, String>) {
+    let end = lines.iter().position(|line| line == INTERFACES_MARKER || line == PROCESSES_MARKER).unwrap_or(lines.len());
+    (&lines[..end], parse_socket_owners(section(lines, PROCESSES_MARKER)))
+}
+
+// The lines after `marker`, up to the next one
+fn section<'a>(lines: &'a [String], marker: &str) -> &'a [String] {
+    let Some(start) = lines.iter().position(|line| line == marker) else {
+        return &[];
+    };
+    let rest = &lines[start + 1..];
+    let end = rest.iter().position(|line| line == INTERFACES_MARKER || line == PROCESSES_MARKER).unwrap_or(rest.len());
+    &rest[..end]
+}
+
+// (received, sent) byte totals by interface name
+pub type InterfaceCounters = HashMap<String, (u64, u64)>;
+
+// From /proc/net/dev or macOS's `netstat -ib`
+pub fn parse_interface_counters(lines: &[String]) -> InterfaceCounters {
+    let mut counters = HashMap::new();
+    let header = lines.iter().find(|line| line.contains("Ibytes") && line.contains("Obytes"));
+    if let Some(header) = header {
+        // Columns are right-aligned and Address can be blank, so they're counted from the end.
+        // The <Link#n> row of each interface has its totals
+        let columns: Vec<&str> = header.split_whitespace().collect();
+        let from_end = |name: &str| columns.iter().position(|column| *column == name).map(|i| columns.len() - i);
+        let (Some(received), Some(sent)) = (from_end("Ibytes"), from_end("Obytes")) else {
+            return counters;
+        };
+        for line in lines.iter().filter(|line| line.contains("<Link#")) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let field = |from_end: usize| fields.len().checked_sub(from_end).and_then(|i| fields[i].parse::<u64>().ok());
+            if let (Some(name), Some(received), Some(sent)) = (fields.first(), field(received), field(sent)) {
+                counters.entry(name.to_string()).or_insert((received, sent));
+            }
+        }
+    } else {
+        // "  eth0: 1234 56 0 0 0 0 0 0 7890 12 0 0 0 0 0 0", bytes first in each half
+        for line in lines {
+            let Some((name, values)) = line.split_once(':') else { continue };
+            let values: Vec<u64> = values.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            if values.len() >= 9 {
+                counters.insert(name.trim().to_string(), (values[0], values[8]));
+            }
+        }
+    }
+    counters
+}
+
+// Bytes per second over one interval
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceRate {
+    pub name: String,
+    pub received: f64,
+    pub sent: f64,
+}
+
+// Counters that went down (a reset or a new interface) have no rate until the next poll
+pub fn interface_rates(before: &InterfaceCounters, after: &InterfaceCounters, elapsed_secs: f64) -> Vec<InterfaceRate> {
+    let mut rates: Vec<InterfaceRate> = after.iter()
+        .filter_map(|(name, &(received, sent))| {
+            let &(received_before, sent_before) = before.get(name)?;
+            (received >= received_before && sent >= sent_before && elapsed_secs > 0.0).then(|| InterfaceRate {
+                name: name.clone(),
+                received: (received - received_before) as f64 / elapsed_secs,
+                sent: (sent - sent_before) as f64 / elapsed_secs,
+            })
+        })
+        .collect();
+    rates.sort_by(|a, b| a.name.cmp(&b.name));
+    rates
+}
+
+// Diffs each poll's interface counters against the previous poll's as the lines arrive
+struct InterfaceRateParser {
+    rates: Arc<Mutex<Vec<InterfaceRate>>>,
+    section: Option<Vec<String>>,  // The interfaces section being read
+    previous: Option<(Instant, InterfaceCounters)>,
+    // ESTABLISHED lines in the poll's netstat section, checked against the alert when the section ends;
+    // ss's lines in the processes section say ESTAB, so they're never counted
+    established: usize,
+    alert: Option<(Arc<Mutex<AlertWatch>>, String)>,  // With the host it's named by
+}
+
+impl InterfaceRateParser {
+    fn new(rates: Arc<Mutex<Vec<InterfaceRate>>>) -> Self {
+        Self { rates, section: None, previous: None, established: 0, alert: None }
+    }
+    
+    fn with_alert(mut self, watch: Arc<Mutex<AlertWatch>>, host: String) -> Self {
+        self.alert = Some((watch, host));
+        self
+    }
+}
+
+impl SampleParser for InterfaceRateParser {
+    fn parse(&mut self, line: &str) -> Vec<(String, f64)> {
+        if line == INTERFACES_MARKER {
+            self.section = Some(Vec::new());
+            if let Some((ref watch, ref host)) = self.alert {
+                let mut watch = watch.lock().unwrap();
+                if watch.observe(self.established as f64, Instant::now()) {
+                    alerts::notify("Network Monitor", &format!("{} established connections on {} for over {}s", self.established, host, watch.alert.for_secs));
+                }
+            }
+            self.established = 0;
+        } else if parse_connection(line).is_some_and(|fields| fields[5] == "ESTABLISHED") {
+            self.established += 1;
+        } else if line == PROCESSES_MARKER {
+            if let Some(section) = self.section.take() {
+                let now = Instant::now();
+                let counters = parse_interface_counters(&section);
+                if let Some((then, ref before)) = self.previous {
+                    *self.rates.lock().unwrap() = interface_rates(before, &counters, now.duration_since(then).as_secs_f64());
+                }
+                self.previous = Some((now, counters));
+            }
+        } else if let Some(ref mut section) = self.section {
+            section.push(line.to_string());
+        }
+        vec![]
+    }
+}
+
+// The port of "127.0.0.1:631", "[::1]:631" or macOS's "127.0.0.1.631"
+fn port_of(address: &str) -> &str {
+    address.rsplit([':', '.']).next().unwrap_or_default()
+}
+
+// "name (pid)" by local port, from `lsof -nP -i` or `ss -tunap`; the first owner of a port wins
+pub fn parse_socket_owners(lines: &[String]) -> HashMap<String, String> {
+    let mut owners = HashMap::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let owner = if let Some(process) = line.split_once("users:((\"").map(|(_, rest)| rest) {
+            // ss: tcp ESTAB 0 0 10.0.0.5:22 10.0.0.9:51234 users:(("sshd",pid=812,fd=4))
+            let name = process.split('"').next().unwrap_or_default();
+            let pid = process.split_once("pid=").and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next());
+            fields.get(4).zip(pid).map(|(local, pid)| (port_of(local), format!("{} ({})", name, pid)))
+        } else if fields.len() >= 9 && fields[1].parse::<u32>().is_ok() {
+            // lsof: sshd 812 root 4u IPv4 0x1234 0t0 TCP 10.0.0.5:22->10.0.0.9:51234 (ESTABLISHED)
+            let local = fields[8].split("->").next().unwrap_or_default();
+            Some((port_of(local), format!("{} ({})", fields[0], fields[1])))
+        } else {
+            None
+        };
+        if let Some((port, owner)) = owner.filter(|(port, _)| !port.is_empty() && *port != "*") {
+            owners.entry(port.to_string()).or_insert(owner);
+        }
+    }
+    owners
+}
+
+fn socket_owner<'a>(line: &str, owners: &'a HashMap<String, String>) -> Option<&'a str> {
+    let local = parse_connection(line)?[3].clone();
+    owners.get(port_of(&local)).map(String::as_str)
+}
+
+// The foreign address as "name:port" once its lookup is done; it stays as is until then
+fn with_resolved_name(line: &str) -> String {
+    let Some(foreign) = parse_connection(line).map(|fields| fields[4].clone()) else {
+        return line.to_string();
+    };
+    let (Some(ip), Some((_, port))) = (reverse_dns::address_ip(&foreign), foreign.rsplit_once([':', '.'])) else {
+        return line.to_string();
+    };
+    match (reverse_dns::hostname(ip), line.rfind(&foreign)) {
+        (Some(name), Some(start)) => format!("{}{}:{}{}", &line[..start], name, port, &line[start + foreign.len()..]),
+        _ => line.to_string(),
+    }
+}
+
+// A netstat line with its owner on the end, as shown and filtered
+fn with_owner(line: &str, owner: Option<&str>) -> String {
+    match owner {
+        Some(owner) => format!("{}  {}", line, owner),
+        None if line.starts_with("Proto") => format!("{}  Process (PID)", line),
+        None => line.to_string(),
+    }
+}
+
+// The columns of a tcp/udp line from `netstat -an`; UDP sockets have no state. Unix sockets and headers give None
+pub fn parse_connection(line: &str) -> Option<[String; 6]> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if !fields.first().is_some_and(|proto| proto.starts_with("tcp") || proto.starts_with("udp")) || fields.len() < 5 {
+        return None;
+    }
+    let field = |i: usize| fields.get(i).map(|f| f.to_string()).unwrap_or_default();
+    Some([field(0), field(1), field(2), field(3), field(4), field(5)])
+}
+
+impl RefreshableWidget for NetworkMonitorWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
+
+impl FilterableOutput for NetworkMonitorWidget {
+    fn filter_pattern(&self) -> &str {
+        &self.filter_text
+    }
+    
+    fn filter_mode(&self) -> FilterMode {
+        self.filter_mode
+    }
+    
+    fn filter_cache(&self) -> &FilterCache {
+        &self.filter_cache
+    }
+}
 
 impl NetworkMonitorWidget {
     pub fn new(id: usize) -> Self {
         Self {
             id,
             version: 0,
-            refresh_interval_secs: 5, // 5 second refresh
+            refresh_interval_secs: default_refresh_interval_secs(),
             filter_text: String::new(),
+            filter_mode: FilterMode::default(),
             show_established_only: false,
+            resolve_names: false,
+            established_alert: default_established_alert(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
+            filter_cache: FilterCache::default(),
+            interface_rates: Arc::default(),
+            established_alert_watch: Arc::default(),
+            history: vec![],
+            history_index: None,
+        }
+    }
+    
+    // Output from the netstat command, or a recorded poll under review
+    fn displayed_lines(&self) -> Vec<String> {
+        match self.history_index.and_then(|i| self.history.get(i)) {
+            Some((_, lines)) => lines.clone(),
+            None => self.executor.output.lock().unwrap().clone(),
         }
     }
-}
\ No newline at end of file
+    
+    fn shows_line(&self, line: &str) -> bool {
+        (!self.show_established_only || line.contains("ESTABLISHED")) && self.matches_filter(line)
+    }
+    
+    // Live only; recorded polls under review don't change it
+    fn render_interface_rates(&self, ui: &mut egui::Ui) {
+        let rates = self.interface_rates.lock().unwrap().clone();
+        if rates.is_empty() {
+            return;
+        }
+        let rate = |bytes: f64| format!("{}/s", format_bytes(bytes));
+        
+        egui::CollapsingHeader::new(format!("Interfaces ({})", rates.len()))
+            .id_salt(format!("network_interfaces_{}", self.id))
+            .show(ui, |ui| {
+                egui::Grid::new(format!("network_rates_{}", self.id))
+                    .num_columns(3)
+                    .striped(true)
+                    .spacing([20.0, 2.0])
+                    .show(ui, |ui| {
+                        ui.strong("Interface");
+                        ui.strong("Receive");
+                        ui.strong("Send");
+                        ui.end_row();
+                        for interface in &rates {
+                            ui.monospace(&interface.name);
+                            ui.monospace(rate(interface.received));
+                            ui.monospace(rate(interface.sent));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+    
+    // Step through past polls recorded in the investigation
+    fn render_history_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            match self.history_index {
+                None => {
+                    if ui.button("Review history").clicked() {
+                        if let Some(ref db) = self.executor.database {
+                            match crate::runtime::runtime().block_on(db.get_widget_runs(self.id as i32)) {
+                                Ok(runs) => {
+                                    self.history_index = runs.len().checked_sub(1);
+                                    self.history = runs;
+                                }
+                                Err(e) => eprintln!("Failed to load network history: {}", e),
+                            }
+                        }
+                    }
+                }
+                Some(mut index) => {
+                    let last = self.history.len().saturating_sub(1);
+                    ui.label("Poll:");
+                    ui.add(egui::Slider::new(&mut index, 0..=last).show_value(false));
+                    if ui.small_button("◀").clicked() {
+                        index = index.saturating_sub(1);
+                    }
+                    if ui.small_button("▶").clicked() {
+                        index = (index + 1).min(last);
+                    }
+                    self.history_index = Some(index);
+                    
+                    if let Some((timestamp, _)) = self.history.get(index) {
+                        ui.label(format!("{} of {}, {}", index + 1, self.history.len(), crate::investigation::Investigation::format_timestamp(*timestamp)));
+                    }
+                    if ui.button("Back to live").clicked() {
+                        self.history_index = None;
+                        self.history.clear();
+                    }
+                }
+            }
+        });
+    }
+    
+    // Filtering sees the owner too, so a process name finds its sockets
+    fn render_connections(&self, ui: &mut egui::Ui, lines: &[String]) {
+        let redactor = self.executor.redactor();
+        let (connections, owners) = split_poll(lines);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(self.history_index.is_none())
+            .show(ui, |ui| {
+                for line in connections {
+                    let owner = socket_owner(line, &owners);
+                    let line = match self.resolve_names {
+                        true => with_resolved_name(line),
+                        false => line.to_string(),
+                    };
+                    let line = with_owner(&line, owner);
+                    if self.shows_line(&line) {
+                        ui.label(egui::RichText::new(redactor.for_display(&line)).monospace().size(12.0));
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn connections_split_into_columns_on_linux_and_macos() {
+        let row = |line: &str| parse_connection(line).map(|fields| fields.to_vec());
+        assert_eq!(row("tcp        0      0 127.0.0.1:631           0.0.0.0:*               LISTEN"),
+            Some(vec!["tcp", "0", "0", "127.0.0.1:631", "0.0.0.0:*", "LISTEN"].into_iter().map(String::from).collect()));
+        assert_eq!(row("udp4       0      0  *.5353                 *.*").unwrap()[5], "");
+        assert_eq!(row("tcp6       0      0  ::1.631  *.*  LISTEN").unwrap()[0], "tcp6");
+        assert_eq!(row("Proto Recv-Q Send-Q Local Address           Foreign Address         State"), None);
+        assert_eq!(row("unix  2      [ ACC ]     STREAM     LISTENING     12345    /run/systemd/private"), None);
+    }
+    
+    #[test]
+    fn sockets_are_joined_to_their_owners_by_local_port() {
+        let poll: Vec<String> = "\
+tcp        0      0 10.0.0.5:22             10.0.0.9:51234          ESTABLISHED
+tcp4       0      0  127.0.0.1.631          *.*                    LISTEN
+udp        0      0 0.0.0.0:5353            0.0.0.0:*
+--processes--
+COMMAND  PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+sshd     812 root    4u  IPv4  0x1234      0t0  TCP 10.0.0.5:22->10.0.0.9:51234 (ESTABLISHED)
+cupsd    90  root    5u  IPv6  0x5678      0t0  TCP [::1]:631 (LISTEN)
+lsof: WARNING: can't stat() fuse.gvfsd-fuse file system /run/user/1000/gvfs"
+            .lines().map(str::to_string).collect();
+        
+        let (connections, owners) = split_poll(&poll);
+        assert_eq!(connections.len(), 3);
+        let owner_of = |i: usize| socket_owner(&connections[i], &owners);
+        assert_eq!(owner_of(0), Some("sshd (812)"));
+        assert_eq!(owner_of(1), Some("cupsd (90)"));
+        // Nothing we could see owns it, e.g. lsof without root
+        assert_eq!(owner_of(2), None);
+        
+        // Sections after the connections don't count as connections
+        let poll: Vec<String> = vec!["tcp 0 0 10.0.0.5:22 10.0.0.9:51234 ESTABLISHED".to_string(), INTERFACES_MARKER.to_string(), "eth0: 1 2 3 4 5 6 7 8 9".to_string()];
+        assert_eq!(split_poll(&poll).0.len(), 1);
+        
+        let ss: Vec<String> = vec![
+            "Netid State  Recv-Q Send-Q Local Address:Port Peer Address:Port Process".to_string(),
+            "udp   UNCONN 0      0      0.0.0.0:5353       0.0.0.0:*         users:((\"avahi-daemon\",pid=640,fd=12))".to_string(),
+            "tcp   LISTEN 0      128    0.0.0.0:8080       0.0.0.0:*".to_string(),
+        ];
+        assert_eq!(parse_socket_owners(&ss), HashMap::from([("5353".to_string(), "avahi-daemon (640)".to_string())]));
+    }
+    
+    #[test]
+    fn interface_rates_come_from_counter_deltas_on_linux_and_macos() {
+        let lines = |text: &str| -> Vec<String> { text.lines().map(str::to_string).collect() };
+        let proc_net_dev = |received: u64, sent: u64| lines(&format!("\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    5000      50    0    0    0     0          0         0     5000      50    0    0    0     0       0          0
+  eth0: {}    100    0    0    0     0          0         0 {}    80    0    0    0     0       0          0", received, sent));
+        
+        let before = parse_interface_counters(&proc_net_dev(1_000_000, 200_000));
+        let after = parse_interface_counters(&proc_net_dev(1_500_000, 200_000));
+        assert_eq!(after["eth0"], (1_500_000, 200_000));
+        assert_eq!(interface_rates(&before, &after, 2.0), vec![
+            InterfaceRate { name: "eth0".to_string(), received: 250_000.0, sent: 0.0 },
+            InterfaceRate { name: "lo".to_string(), received: 0.0, sent: 0.0 },
+        ]);
+        // A counter reset gives no rate rather than a huge one
+        assert!(interface_rates(&after, &before, 2.0).iter().all(|rate| rate.name != "eth0"));
+        
+        let netstat_ib = lines("\
+Name       Mtu   Network       Address            Ipkts Ierrs     Ibytes    Opkts Oerrs     Obytes  Coll
+lo0        16384 <Link#1>                         1234     0     567890     1234     0     567890     0
+lo0        16384 127           localhost          1234     -     567890     1234     -     567890     -
+en0        1500  <Link#6>    a4:83:e7:12:34:56  987654     0 1234567890   456789     0   98765432     0
+en0        1500  192.168.1     192.168.1.20     987000     - 1234000000   456000     -   98700000     -");
+        let counters = parse_interface_counters(&netstat_ib);
+        assert_eq!(counters["lo0"], (567890, 567890));
+        assert_eq!(counters["en0"], (1234567890, 98765432));
+    }
+}
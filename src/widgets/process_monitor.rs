@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use eframe::egui;
+use crate::widgets::WidgetWindow;
 use serde::{Serialize, Deserialize};
-use crate::widgets::command_widget::{CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, format_bytes, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, RefreshableWidget};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub enum ProcessSortBy {
     CPU,
@@ -10,6 +14,75 @@ pub enum ProcessSortBy {
     PID,
 }
 
+// A field of jc's `ps aux` records that the table can show
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ProcessColumn {
+    Pid,
+    User,
+    Command,
+    Cpu,
+    Memory,
+    Vsz,
+    Rss,
+    Tty,
+    Stat,
+    Start,
+    Time,
+}
+
+impl ProcessColumn {
+    // Table order, whatever order they were picked in
+    pub const ALL: [ProcessColumn; 11] = [
+        ProcessColumn::Pid,
+        ProcessColumn::User,
+        ProcessColumn::Command,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::Vsz,
+        ProcessColumn::Rss,
+        ProcessColumn::Tty,
+        ProcessColumn::Stat,
+        ProcessColumn::Start,
+        ProcessColumn::Time,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::User => "USER",
+            ProcessColumn::Command => "COMMAND",
+            ProcessColumn::Cpu => "CPU%",
+            ProcessColumn::Memory => "MEMORY",
+            ProcessColumn::Vsz => "VSZ",
+            ProcessColumn::Rss => "RSS",
+            ProcessColumn::Tty => "TTY",
+            ProcessColumn::Stat => "STAT",
+            ProcessColumn::Start => "START",
+            ProcessColumn::Time => "TIME",
+        }
+    }
+    
+    fn jc_key(&self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "pid",
+            ProcessColumn::User => "user",
+            ProcessColumn::Command => "command",
+            ProcessColumn::Cpu => "cpu_percent",
+            ProcessColumn::Memory => "mem_percent",
+            ProcessColumn::Vsz => "vsz",
+            ProcessColumn::Rss => "rss",
+            ProcessColumn::Tty => "tty",
+            ProcessColumn::Stat => "stat",
+            ProcessColumn::Start => "start",
+            ProcessColumn::Time => "time",
+        }
+    }
+}
+
+fn default_columns() -> Vec<ProcessColumn> {
+    vec![ProcessColumn::Pid, ProcessColumn::Command, ProcessColumn::Cpu, ProcessColumn::Memory]
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ProcessMonitorWidget {
     pub id: usize,
@@ -17,12 +90,60 @@ pub struct ProcessMonitorWidget {
     pub refresh_interval_secs: u64,
     pub max_processes: usize,
     pub sort_by: ProcessSortBy,
+    #[serde(default)]
+    pub filter_text: String,  // Only processes whose command contains this, ignoring case
+    #[serde(default)]
+    pub full_command: bool,  // The whole command line instead of just the program
+    #[serde(default = "default_columns")]
+    pub columns: Vec<ProcessColumn>,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
     #[serde(skip, default = "default_executor")]
     pub executor: CommandExecutor,
     #[serde(skip, default)]
     pub config_unsaved: bool,
+    #[serde(skip, default)]
+    pub table_cache: Arc<Mutex<Option<(ProcessTableKey, ProcessTable)>>>,  // Parsed output, display only
+    #[serde(skip, default = "default_executor")]
+    pub kill_executor: CommandExecutor,  // Runs kill on the selected host
+    #[serde(skip, default)]
+    pub kill: Arc<Mutex<KillState>>,
 }
 
+// A signal for one process, named in the confirmation and the result
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillRequest {
+    pub pid: String,
+    pub command: String,
+    pub force: bool,
+}
+
+impl KillRequest {
+    fn signal(&self) -> &'static str {
+        if self.force { "SIGKILL" } else { "SIGTERM" }
+    }
+}
+
+// The row menu sets `pending` from render_output, which only gets &self; confirming moves it to `sent`
+#[derive(Debug, Clone, Default)]
+pub struct KillState {
+    pub pending: Option<KillRequest>,
+    pub sent: Option<KillRequest>,
+}
+
+pub fn kill_command(request: &KillRequest) -> CommandSpec {
+    CommandSpec::new("kill")
+        .arg(if request.force { "-KILL" } else { "-TERM" })
+        .arg(&request.pid)
+}
+
+// Output revision and the display settings a cached table was built with
+type ProcessTableKey = (u64, ProcessSortBy, usize, String);
+
 fn default_executor() -> CommandExecutor {
     CommandExecutor::new()
 }
@@ -48,13 +169,10 @@ impl crate::widgets::Widget for ProcessMonitorWidget {
     fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
         let widget_id = self.id as i32;
         let widget_version = self.version;
+        self.kill_executor.set_database(database.clone(), widget_id, widget_version);
         self.executor.set_database(database, widget_id, widget_version);
     }
     
-    fn config_changed(&self) -> bool {
-        self.config_unsaved
-    }
-    
     
     fn needs_restart(&self) -> bool {
         // Process monitor needs restart for execution-affecting changes (sort, max processes, interval)
@@ -79,7 +197,7 @@ impl crate::widgets::Widget for ProcessMonitorWidget {
             .default_pos([150.0 + (idx as f32 * 50.0), 150.0 + (idx as f32 * 50.0)])
             .default_size([900.0, 600.0])
             .resizable(true)
-            .show(ctx, |ui| {
+            .show_for_widget(ctx, self.id, |ui| {
                 ui.horizontal(|ui| {
                     refresh_clicked = self.render_controls(ui);
                     
@@ -89,43 +207,57 @@ impl crate::widgets::Widget for ProcessMonitorWidget {
                     ui.selectable_value(&mut self.sort_by, ProcessSortBy::CPU, "CPU");
                     ui.selectable_value(&mut self.sort_by, ProcessSortBy::Memory, "Memory");
                     ui.selectable_value(&mut self.sort_by, ProcessSortBy::PID, "PID");
-                    if old_sort != self.sort_by {
-                        self.config_unsaved = true;
-                        // Restart with new sort if running
-                        if self.executor.is_running() {
-                            self.stop();
-                            self.start();
-                        }
-                    }
                     
                     ui.separator();
                     ui.label("Max:");
                     let old_max = self.max_processes;
                     ui.add(egui::DragValue::new(&mut self.max_processes).range(5..=100));
-                    if old_max != self.max_processes {
+                    
+                    let changed = old_sort != self.sort_by || old_max != self.max_processes;
+                    if changed {
                         self.config_unsaved = true;
-                        if self.executor.is_running() {
-                            self.stop();
-                            self.start();
-                        }
+                    }
+                    if self.config_settled(ui, changed) {
+                        self.handle_config_change();
+                        self.save_config();
                     }
                     
                     ui.separator();
-                    ui.label("Interval:");
-                    let old_interval = self.refresh_interval_secs;
-                    ui.add(egui::DragValue::new(&mut self.refresh_interval_secs).range(1..=60).suffix("s"));
-                    if old_interval != self.refresh_interval_secs {
+                    let filter = ui.add(egui::TextEdit::singleline(&mut self.filter_text)
+                        .hint_text("Filter commands")
+                        .desired_width(140.0));
+                    if filter.changed() {
                         self.config_unsaved = true;
-                        if self.executor.is_running() {
-                            self.stop();
-                            self.start();
+                        self.save_config();
+                    }
+                    ui.menu_button("Columns", |ui| {
+                        for column in ProcessColumn::ALL {
+                            let mut shown = self.columns.contains(&column);
+                            if ui.checkbox(&mut shown, column.label()).changed() {
+                                self.columns = ProcessColumn::ALL.into_iter()
+                                    .filter(|c| if *c == column { shown } else { self.columns.contains(c) })
+                                    .collect();
+                                self.config_unsaved = true;
+                                self.save_config();
+                            }
                         }
+                    });
+                    if ui.checkbox(&mut self.full_command, "Full command")
+                        .on_hover_text("Show each process's whole command line instead of just its program")
+                        .changed() {
+                        self.config_unsaved = true;
+                        self.save_config();
                     }
+                    
+                    ui.separator();
+                    self.render_interval_control(ui);
                 });
                 
                 ui.separator();
+                self.render_kill_result(ui);
                 self.render_output(ui);
             });
+        self.render_kill_confirmation(ctx);
         
         (open, refresh_clicked)
     }
@@ -135,12 +267,20 @@ impl crate::widgets::Widget for ProcessMonitorWidget {
         self.start();
     }
     
-    fn restore_widget_data(&mut self, data: Vec<String>) {
-        self.executor.load_historical_output(data);
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
     }
     
     fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
-        self.executor.set_available_hosts(hosts);
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
     }
 }
 
@@ -159,6 +299,30 @@ impl CommandWidget for ProcessMonitorWidget {
         &mut self.executor
     }
     
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
     fn execution_mode(&self) -> ExecutionMode {
         ExecutionMode::Periodic(Duration::from_secs(self.refresh_interval_secs))
     }
@@ -171,153 +335,281 @@ impl CommandOutputRenderer for ProcessMonitorWidget {
     
     fn render_output(&self, ui: &mut eframe::egui::Ui) {
         use eframe::egui;
-        use serde_json::Value;
         
-        let raw_output = CommandWidget::executor(self).output.lock().unwrap();
-        
-        if raw_output.is_empty() {
-            ui.label("No data available");
-            return;
+        let mut cache = self.table_cache.lock().unwrap();
+        let key = (self.executor.output_revision(), self.sort_by.clone(), self.max_processes, self.filter_text.clone());
+        if cache.as_ref().is_none_or(|(cached_key, _)| *cached_key != key) {
+            // Parse finished runs only; mid-run, keep the table unless the sort changed, then redo the last run
+            let output = self.executor.output.lock().unwrap();
+            let complete = !self.executor.is_running() || output.last().is_some_and(|line| line == "Command completed");
+            let settings_changed = cache.as_ref().is_none_or(|((_, sort_by, max, filter), _)| (sort_by, *max, filter) != (&key.1, key.2, &key.3));
+            if complete {
+                *cache = Some((key, parse_process_table(&output.join("\n"), &self.sort_by, self.max_processes, &self.filter_text)));
+            } else if settings_changed {
+                let previous = self.executor.previous_output.lock().unwrap();
+                *cache = Some((key, parse_process_table(&previous.join("\n"), &self.sort_by, self.max_processes, &self.filter_text)));
+            }
         }
+        let Some((_, table)) = cache.as_ref() else {
+            return;
+        };
         
-        let raw_text = raw_output.join("\n");
-        
-        // Process through jc --ps to get JSON
-        match std::process::Command::new("sh")
-            .arg("-c")
-            .arg(format!("echo '{}' | jc --ps -q 2>/dev/null", raw_text.replace("'", "'\\''")))
-            .output()
-        {
-            Ok(output) if !output.stdout.is_empty() => {
-                // Parse JSON and render as egui table
-                match serde_json::from_slice::<Value>(&output.stdout) {
-                    Ok(json) => {
-                        if let Some(processes) = json.as_array() {
-                            // Render as egui table
-                            egui::ScrollArea::vertical()
-                                .auto_shrink([false, false])
-                                .stick_to_bottom(true)
-                                .show(ui, |ui| {
-                                    egui::Grid::new("process_table")
-                                        .num_columns(4)
-                                        .spacing([20.0, 4.0])
-                                        .striped(true)
-                                        .show(ui, |ui| {
-                                            // Header
-                                            ui.strong("PID");
-                                            ui.strong("COMMAND"); 
-                                            ui.strong("CPU%");
-                                            ui.strong("MEMORY");
-                                            ui.end_row();
-                                            
-                                            // Process rows - sort by the selected field
-                                            let mut process_list: Vec<&Value> = processes.iter().collect();
-                                            match self.sort_by {
-                                                ProcessSortBy::CPU => {
-                                                    process_list.sort_by(|a, b| {
-                                                        let a_cpu = a.get("cpu_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                                                        let b_cpu = b.get("cpu_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                                                        b_cpu.partial_cmp(&a_cpu).unwrap_or(std::cmp::Ordering::Equal)
-                                                    });
-                                                }
-                                                ProcessSortBy::Memory => {
-                                                    process_list.sort_by(|a, b| {
-                                                        let a_mem = a.get("mem_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                                                        let b_mem = b.get("mem_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                                                        b_mem.partial_cmp(&a_mem).unwrap_or(std::cmp::Ordering::Equal)
-                                                    });
-                                                }
-                                                ProcessSortBy::PID => {
-                                                    process_list.sort_by(|a, b| {
-                                                        let a_pid = a.get("pid").and_then(|v| v.as_i64()).unwrap_or(0);
-                                                        let b_pid = b.get("pid").and_then(|v| v.as_i64()).unwrap_or(0);
-                                                        a_pid.cmp(&b_pid)
-                                                    });
-                                                }
-                                            }
-                                            
-                                            for process in process_list.iter().take(self.max_processes) {
-                                                let pid = process.get("pid")
-                                                    .and_then(|v| v.as_i64())
-                                                    .map(|v| v.to_string())
-                                                    .unwrap_or_else(|| "N/A".to_string());
-                                                
-                                                let command = process.get("command")
-                                                    .and_then(|v| v.as_str())
-                                                    .unwrap_or("N/A")
-                                                    .split_whitespace()
-                                                    .next()
-                                                    .unwrap_or("N/A");
-                                                
-                                                let cpu = process.get("cpu_percent")
-                                                    .and_then(|v| v.as_f64())
-                                                    .map(|v| format!("{:.1}%", v))
-                                                    .unwrap_or_else(|| "N/A".to_string());
-                                                
-                                                let memory = process.get("mem_percent")
-                                                    .and_then(|v| v.as_f64())
-                                                    .map(|v| format!("{:.1}%", v))
-                                                    .unwrap_or_else(|| "N/A".to_string());
-                                                
-                                                ui.monospace(&pid);
-                                                ui.monospace(command);
-                                                ui.monospace(&cpu);
-                                                ui.monospace(memory);
-                                                ui.end_row();
-                                            }
-                                        });
-                                });
-                        } else {
-                            // JSON doesn't have expected structure - fail
-                            ui.label("❌ jc failed to parse ps output - invalid JSON structure");
-                            ui.separator();
-                            ui.label("Raw ps output for debugging:");
-                            ui.separator();
-                            egui::ScrollArea::vertical()
-                                .max_height(200.0)
-                                .show(ui, |ui| {
-                                    for line in raw_text.lines() {
-                                        ui.label(egui::RichText::new(line).monospace().size(10.0));
-                                    }
-                                });
-                        }
+        let raw_lines = |ui: &mut egui::Ui, label: &str, text: &str| {
+            ui.separator();
+            ui.label(label);
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for line in text.lines() {
+                        ui.label(egui::RichText::new(line).monospace().size(10.0));
                     }
-                    Err(e) => {
-                        // JSON parsing failed - fail
-                        ui.label(format!("❌ JSON parsing failed: {}", e));
-                        ui.separator();
-                        ui.label("Raw jc output for debugging:");
-                        ui.separator();
-                        egui::ScrollArea::vertical()
-                            .max_height(200.0)
+                });
+        };
+        
+        match table {
+            ProcessTable::Empty => {
+                ui.label("No data available");
+            }
+            ProcessTable::Rows(rows) => {
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("process_table")
+                            .num_columns(self.columns.len() + 1)
+                            .spacing([20.0, 4.0])
+                            .striped(true)
                             .show(ui, |ui| {
-                                let jc_output = String::from_utf8_lossy(&output.stdout);
-                                for line in jc_output.lines() {
-                                    ui.label(egui::RichText::new(line).monospace().size(10.0));
+                                // Header
+                                for column in &self.columns {
+                                    ui.strong(column.label());
+                                }
+                                ui.label("");
+                                ui.end_row();
+                                
+                                for row in rows {
+                                    for column in &self.columns {
+                                        if *column == ProcessColumn::Command && self.full_command {
+                                            ui.scope(|ui| {
+                                                ui.set_max_width(FULL_COMMAND_WIDTH);
+                                                ui.add(egui::Label::new(egui::RichText::new(&row.command_line).monospace()).truncate())
+                                                    .on_hover_text(&row.command_line);
+                                            });
+                                        } else {
+                                            ui.monospace(row.cell(*column));
+                                        }
+                                    }
+                                    if self.can_kill(&row.pid) {
+                                        self.render_kill_menu(ui, row);
+                                    } else {
+                                        ui.label("");
+                                    }
+                                    ui.end_row();
                                 }
                             });
-                    }
-                }
+                    });
             }
-            _ => {
+            ProcessTable::UnexpectedJson(raw_text) => {
+                // JSON doesn't have expected structure - fail
+                ui.label("❌ jc failed to parse ps output - invalid JSON structure");
+                raw_lines(ui, "Raw ps output for debugging:", raw_text);
+            }
+            ProcessTable::InvalidJson(error, jc_output) => {
+                // JSON parsing failed - fail
+                ui.label(format!("❌ JSON parsing failed: {}", error));
+                raw_lines(ui, "Raw jc output for debugging:", jc_output);
+            }
+            ProcessTable::JcFailed(raw_text) => {
                 // jc command failed - fail
                 ui.label("❌ jc --ps command failed or not supported on this platform");
-                ui.separator();
-                ui.label("Raw ps output for debugging:");
-                ui.separator();
-                egui::ScrollArea::vertical()
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        for line in raw_text.lines() {
-                            ui.label(egui::RichText::new(line).monospace().size(10.0));
-                        }
-                    });
+                raw_lines(ui, "Raw ps output for debugging:", raw_text);
             }
         }
     }
 }
 
-impl CommandControlBar for ProcessMonitorWidget {}
+// Full command lines are cut off with an ellipsis past this, so the other columns stay in view
+const FULL_COMMAND_WIDTH: f32 = 420.0;
+
+// One displayed row, already formatted
+#[derive(Debug, Clone)]
+pub struct ProcessRow {
+    pub pid: String,
+    pub command: String,  // Just the program
+    pub command_line: String,  // With its arguments
+    pub fields: HashMap<ProcessColumn, String>,  // Every other column
+}
+
+impl ProcessRow {
+    pub fn cell(&self, column: ProcessColumn) -> &str {
+        match column {
+            ProcessColumn::Pid => &self.pid,
+            ProcessColumn::Command => &self.command,
+            _ => self.fields.get(&column).map(String::as_str).unwrap_or("N/A"),
+        }
+    }
+}
+
+// How jc's value for a column is shown; a field ps didn't report, or left as null, is "N/A"
+fn format_field(column: ProcessColumn, value: Option<&serde_json::Value>) -> String {
+    use serde_json::Value;
+    
+    match (column, value) {
+        (ProcessColumn::Cpu | ProcessColumn::Memory, Some(value)) => value.as_f64()
+            .map(|v| format!("{:.1}%", v))
+            .unwrap_or_else(|| "N/A".to_string()),
+        // ps reports sizes in KiB
+        (ProcessColumn::Vsz | ProcessColumn::Rss, Some(value)) => value.as_f64()
+            .map(|v| format_bytes(v * 1024.0))
+            .unwrap_or_else(|| "N/A".to_string()),
+        (_, Some(Value::String(text))) => text.clone(),
+        (_, Some(Value::Number(number))) => number.to_string(),
+        (_, Some(Value::Bool(flag))) => flag.to_string(),
+        _ => "N/A".to_string(),
+    }
+}
+
+// What a run of `ps aux` turned into, kept until the output or the sort changes
+#[derive(Debug, Clone)]
+pub enum ProcessTable {
+    Empty,
+    Rows(Vec<ProcessRow>),
+    UnexpectedJson(String),  // Raw ps output
+    InvalidJson(String, String),  // Parse error and jc's output
+    JcFailed(String),  // Raw ps output
+}
+
+// Parse ps output through jc --ps, then filter, sort and trim it for display
+fn parse_process_table(raw_text: &str, sort_by: &ProcessSortBy, max_processes: usize, filter: &str) -> ProcessTable {
+    use serde_json::Value;
+    
+    if raw_text.is_empty() {
+        return ProcessTable::Empty;
+    }
+    
+    let output = match run_jc("--ps", raw_text) {
+        Ok(output) if !output.stdout.is_empty() => output,
+        _ => return ProcessTable::JcFailed(raw_text.to_string()),
+    };
+    let json = match serde_json::from_slice::<Value>(&output.stdout) {
+        Ok(json) => json,
+        Err(e) => return ProcessTable::InvalidJson(e.to_string(), String::from_utf8_lossy(&output.stdout).into_owned()),
+    };
+    let Some(processes) = json.as_array() else {
+        return ProcessTable::UnexpectedJson(raw_text.to_string());
+    };
+    
+    // Filter before trimming, so the table shows the top matches
+    let mut process_list: Vec<&Value> = processes.iter()
+        .filter(|process| command_matches(process.get("command").and_then(|v| v.as_str()).unwrap_or(""), filter))
+        .collect();
+    
+    // Sort by the selected field
+    match sort_by {
+        ProcessSortBy::CPU => {
+            process_list.sort_by(|a, b| {
+                let a_cpu = a.get("cpu_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                let b_cpu = b.get("cpu_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                b_cpu.partial_cmp(&a_cpu).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        ProcessSortBy::Memory => {
+            process_list.sort_by(|a, b| {
+                let a_mem = a.get("mem_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                let b_mem = b.get("mem_percent").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                b_mem.partial_cmp(&a_mem).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        ProcessSortBy::PID => {
+            process_list.sort_by(|a, b| {
+                let a_pid = a.get("pid").and_then(|v| v.as_i64()).unwrap_or(0);
+                let b_pid = b.get("pid").and_then(|v| v.as_i64()).unwrap_or(0);
+                a_pid.cmp(&b_pid)
+            });
+        }
+    }
+    
+    let rows = process_list.iter().take(max_processes).map(|process| {
+        let command_line = process.get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or("N/A");
+        ProcessRow {
+            pid: process.get("pid")
+                .and_then(|v| v.as_i64())
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "N/A".to_string()),
+            command: command_line
+                .split_whitespace()
+                .next()
+                .unwrap_or("N/A")
+                .to_string(),
+            command_line: command_line.to_string(),
+            fields: ProcessColumn::ALL.into_iter()
+                .filter(|column| !matches!(column, ProcessColumn::Pid | ProcessColumn::Command))
+                .map(|column| (column, format_field(column, process.get(column.jc_key()))))
+                .collect(),
+        }
+    }).collect();
+    ProcessTable::Rows(rows)
+}
+
+fn command_matches(command: &str, filter: &str) -> bool {
+    let filter = filter.trim();
+    filter.is_empty() || command.to_lowercase().contains(&filter.to_lowercase())
+}
+
+// Feed text to a jc parser over its stdin, so the text never passes through a shell
+fn run_jc(parser: &str, input: &str) -> std::io::Result<std::process::Output> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    
+    let mut child = Command::new("jc")
+        .arg(parser)
+        .arg("-q")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    
+    // Write from another thread so a full stdout pipe can't deadlock against a full stdin pipe
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    writer.join().unwrap_or(Ok(()))?;
+    Ok(output)
+}
+
+impl CommandControlBar for ProcessMonitorWidget {
+    fn table_export(&self) -> Option<(Vec<&'static str>, Vec<Vec<String>>)> {
+        let redactor = self.executor.redactor();
+        let rows = match self.table_cache.lock().unwrap().as_ref() {
+            Some((_, ProcessTable::Rows(rows))) => rows.iter()
+                .map(|row| self.columns.iter()
+                    .map(|column| match column {
+                        ProcessColumn::Command if self.full_command => redactor.redact(&row.command_line).into_owned(),
+                        ProcessColumn::Command | ProcessColumn::User => redactor.redact(row.cell(*column)).into_owned(),
+                        _ => row.cell(*column).to_string(),
+                    })
+                    .collect())
+                .collect(),
+            _ => vec![],
+        };
+        Some((self.columns.iter().map(ProcessColumn::label).collect(), rows))
+    }
+}
+
+impl RefreshableWidget for ProcessMonitorWidget {
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_interval_secs)
+    }
+    
+    fn set_refresh_interval(&mut self, interval: Duration) {
+        self.refresh_interval_secs = interval.as_secs();
+        self.config_unsaved = true;
+    }
+}
 
 
 impl ProcessMonitorWidget {
@@ -328,8 +620,176 @@ impl ProcessMonitorWidget {
             refresh_interval_secs: 5,
             max_processes: 20,
             sort_by: ProcessSortBy::CPU,
+            filter_text: String::new(),
+            full_command: false,
+            columns: default_columns(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
+            table_cache: Arc::new(Mutex::new(None)),
+            kill_executor: CommandExecutor::new(),
+            kill: Arc::new(Mutex::new(KillState::default())),
+        }
+    }
+    
+    fn is_local(&self) -> bool {
+        crate::database::investigation_db::Host::is_plain_localhost(&self.executor.get_selected_host(), &self.executor.command_prefix())
+            || self.executor.selected_host_entry().is_some_and(|host| host.is_localhost)
+    }
+    
+    // Only rows from a running monitor, whose PIDs are current; never the local init process
+    fn can_kill(&self, pid: &str) -> bool {
+        let Ok(pid) = pid.parse::<u32>() else {
+            return false;
+        };
+        self.executor.is_running() && pid > 0 && !(pid == 1 && self.is_local())
+    }
+    
+    fn render_kill_menu(&self, ui: &mut egui::Ui, row: &ProcessRow) {
+        ui.menu_button("⋯", |ui| {
+            for (label, force) in [("Terminate (kill)", false), ("Force kill (kill -9)", true)] {
+                if ui.button(label).clicked() {
+                    self.kill.lock().unwrap().pending = Some(KillRequest { pid: row.pid.clone(), command: row.command.clone(), force });
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+    
+    fn render_kill_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(request) = self.kill.lock().unwrap().pending.clone() else {
+            return;
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        
+        egui::Window::new("Kill process?")
+            .id(egui::Id::new(format!("process_monitor_kill_{}", self.id)))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Send {} to {} (PID {}) on {}?", request.signal(), request.command, request.pid, self.executor.get_selected_host()));
+                if request.force {
+                    ui.weak("The process can't clean up before it exits.");
+                }
+                ui.horizontal(|ui| {
+                    confirmed = ui.button(if request.force { "Force kill" } else { "Terminate" }).clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        
+        if confirmed {
+            self.send_kill(request);
+        } else if cancelled {
+            self.kill.lock().unwrap().pending = None;
+        }
+    }
+    
+    fn send_kill(&self, request: KillRequest) {
+        *self.kill_executor.sudo.lock().unwrap() = self.executor.sudo.lock().unwrap().clone();
+        self.kill_executor.clear_output();
+        self.kill_executor.run_once(self.wrap_for_host(kill_command(&request), &ExecutionMode::OneShot));
+        let mut kill = self.kill.lock().unwrap();
+        kill.pending = None;
+        kill.sent = Some(request);
+    }
+    
+    // kill prints nothing when it works; anything else, like "Operation not permitted", is shown as is
+    fn render_kill_result(&self, ui: &mut egui::Ui) {
+        let Some(request) = self.kill.lock().unwrap().sent.clone() else {
+            return;
+        };
+        if self.kill_executor.is_running() {
+            ui.weak(format!("Sending {} to {} ({})…", request.signal(), request.command, request.pid));
+            return;
         }
+        let output = self.kill_executor.output.lock().unwrap();
+        let errors: Vec<&String> = output.iter().filter(|line| *line != "Command completed").collect();
+        ui.horizontal(|ui| {
+            if errors.is_empty() {
+                ui.label(format!("Sent {} to {} ({})", request.signal(), request.command, request.pid));
+            } else {
+                for line in errors {
+                    ui.colored_label(egui::Color32::RED, line);
+                }
+            }
+            if ui.small_button("✖").clicked() {
+                self.kill.lock().unwrap().sent = None;
+            }
+        });
+        ui.separator();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn kill_sends_the_chosen_signal_and_spares_local_init() {
+        let terminate = KillRequest { pid: "4242".to_string(), command: "sleep".to_string(), force: false };
+        assert_eq!(kill_command(&terminate).args, ["-TERM", "4242"]);
+        assert_eq!(kill_command(&KillRequest { force: true, ..terminate }).args, ["-KILL", "4242"]);
+        
+        let widget = ProcessMonitorWidget::new(1);
+        *widget.executor.is_running.lock().unwrap() = true;
+        assert!(widget.can_kill("4242"));
+        assert!(!widget.can_kill("1"));
+        assert!(!widget.can_kill("N/A"));
+        
+        widget.executor.set_selected_host("web-1".to_string());
+        assert!(widget.can_kill("1"));
+    }
+    
+    #[test]
+    fn kills_are_recorded_with_the_widget_output() {
+        use crate::widgets::Widget;
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::runtime::runtime().block_on(async {
+            let db = crate::database::investigation_db::InvestigationDB::create(&dir.path().join("test.skop"), "Test", "", &[0.1, 0.2, 0.3]).await.unwrap();
+            db.save_widget_instance(&crate::widgets::WidgetType::new_process_monitor(1)).await.unwrap();
+            std::sync::Arc::new(db)
+        });
+        let mut widget = ProcessMonitorWidget::new(1);
+        widget.kill_executor = CommandExecutor::new()
+            .with_runner(crate::widgets::command_runner::FakeRunner::new(&[]).with_stderr(&["kill: (4242) - Operation not permitted"]).with_exit_code(Some(1)));
+        widget.set_database(Some(db.clone()));
+        
+        widget.send_kill(KillRequest { pid: "4242".to_string(), command: "sleep".to_string(), force: false });
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let recorded = loop {
+            let recorded = crate::runtime::runtime().block_on(db.get_widget_data(1, 0)).unwrap();
+            if recorded.len() >= 2 || std::time::Instant::now() > deadline {
+                break recorded;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert!(recorded.iter().any(|line| line == "kill: (4242) - Operation not permitted"), "{:?}", recorded);
+    }
+    
+    #[test]
+    fn filter_matches_commands_ignoring_case() {
+        assert!(command_matches("/usr/bin/Postgres -D /var/lib/pgsql", "postgres"));
+        assert!(command_matches("nginx: worker process", " NGINX "));
+        assert!(command_matches("anything", ""));
+        assert!(!command_matches("sshd: root@pts/0", "nginx"));
+    }
+    
+    #[test]
+    fn columns_format_what_jc_reports_and_fall_back_to_na() {
+        let process = serde_json::json!({"pid": 7, "user": "postgres", "cpu_percent": 12.25, "rss": 2048, "tty": null});
+        let field = |column: ProcessColumn| format_field(column, process.get(column.jc_key()));
+        assert_eq!(field(ProcessColumn::User), "postgres");
+        assert_eq!(field(ProcessColumn::Cpu), "12.2%");
+        assert_eq!(field(ProcessColumn::Rss), "2.0 MB");
+        assert_eq!(field(ProcessColumn::Tty), "N/A");
+        assert_eq!(field(ProcessColumn::Stat), "N/A");
+        
+        let row = ProcessRow { pid: "7".to_string(), command: "postgres".to_string(), command_line: "postgres -D /data".to_string(), fields: HashMap::new() };
+        assert_eq!(row.cell(ProcessColumn::Pid), "7");
+        assert_eq!(row.cell(ProcessColumn::Vsz), "N/A");
     }
 }
\ No newline at end of file
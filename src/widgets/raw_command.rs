@@ -1,6 +1,8 @@
+use std::time::Duration;
 use eframe::egui;
+use crate::widgets::WidgetWindow;
 use serde::{Serialize, Deserialize};
-use crate::widgets::command_widget::{CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar, FilterableOutput, FilterMode, FilterCache, render_filter_box};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RawCommandWidget {
@@ -8,16 +10,87 @@ pub struct RawCommandWidget {
     pub version: i32,
     pub command: String,
     pub needs_config: bool,
+    #[serde(default)]
+    pub mode: RawCommandMode,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_shell")]
+    pub shell: String,
+    #[serde(default)]
+    pub login_shell: bool,
+    #[serde(default)]
+    pub timeout_secs: u64,  // 0 for no timeout; not applied to continuous runs
+    #[serde(default)]
+    pub filter_text: String,
+    #[serde(default)]
+    pub filter_mode: FilterMode,
+    #[serde(default = "default_auto_scroll")]
+    pub auto_scroll: bool,  // Follow new output; off to read back while a command keeps printing
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
     #[serde(skip, default = "default_executor")]
     pub executor: CommandExecutor,
     #[serde(skip, default)]
     pub config_unsaved: bool,
+    #[serde(skip, default)]
+    pub config_error: Option<String>,
+    #[serde(skip, default)]
+    pub filter_cache: FilterCache,
+}
+
+// How the raw command is run
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum RawCommandMode {
+    #[default]
+    Once,
+    Continuous,
+    Periodic,
+    Watch,
+}
+
+impl RawCommandMode {
+    pub const ALL: &'static [RawCommandMode] = &[
+        RawCommandMode::Once,
+        RawCommandMode::Continuous,
+        RawCommandMode::Periodic,
+        RawCommandMode::Watch,
+    ];
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            RawCommandMode::Once => "Once",
+            RawCommandMode::Continuous => "Continuous",
+            RawCommandMode::Periodic => "Every N seconds",
+            RawCommandMode::Watch => "Watch (highlight changes)",
+        }
+    }
 }
 
+// Runs the command from the editor
+const RUN_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+
 fn default_executor() -> CommandExecutor {
     CommandExecutor::new()
 }
 
+fn default_auto_scroll() -> bool {
+    true
+}
+
+fn default_interval_secs() -> u64 {
+    5
+}
+
+fn default_shell() -> String {
+    "sh".to_string()
+}
+
+const SHELLS: &[&str] = &["sh", "bash", "zsh"];
+
 impl crate::widgets::Widget for RawCommandWidget {
     fn widget_type_name(&self) -> &'static str {
         "raw_command"
@@ -42,20 +115,25 @@ impl crate::widgets::Widget for RawCommandWidget {
         self.executor.set_database(database, widget_id, widget_version);
     }
     
-    fn restore_widget_data(&mut self, data: Vec<String>) {
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.apply_saved_settings();
         if !data.is_empty() {
-            self.executor.load_historical_output(data);
+            self.restore_output(data);
             // If we have historical data, the widget was previously configured
             self.needs_config = false;
         }
     }
     
     fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
-        self.executor.set_available_hosts(hosts);
+        self.update_available_hosts(hosts);
     }
     
-    fn config_changed(&self) -> bool {
-        self.config_unsaved
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
     }
     
     
@@ -65,7 +143,10 @@ impl crate::widgets::Widget for RawCommandWidget {
     }
     
     fn start(&self) {
-        self.start_command();
+        // Never hand an empty command to the shell
+        if validate_command(&self.command).is_ok() {
+            self.start_command();
+        }
     }
     
     fn stop(&self) {
@@ -89,50 +170,108 @@ impl crate::widgets::Widget for RawCommandWidget {
                     ui.label("Configure Command:");
                     ui.separator();
                     
+                    ui.label("Command:");
+                    let (changed, run_requested) = Self::command_editor(ui, &mut self.command);
+                    if changed {
+                        self.config_unsaved = true;
+                        self.config_error = None;
+                    }
+                    
                     ui.horizontal(|ui| {
-                        ui.label("Command:");
-                        let old_command = self.command.clone();
-                        ui.text_edit_singleline(&mut self.command);
-                        if old_command != self.command {
+                        if self.render_mode_selector(ui) | self.render_shell_selector(ui) {
                             self.config_unsaved = true;
                         }
                     });
                     
                     ui.separator();
                     
-                    if ui.button("Execute Command").clicked() && !self.command.is_empty() {
-                        self.needs_config = false;
-                        self.start();
+                    if let Some(ref error) = self.config_error {
+                        ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                    }
+                    
+                    let execute_clicked = ui.button("Execute Command")
+                        .on_hover_text(format!("Or press {} in the editor", ui.ctx().format_shortcut(&RUN_SHORTCUT)))
+                        .clicked();
+                    if execute_clicked || run_requested {
+                        match validate_command(&self.command) {
+                            Ok(_) => {
+                                self.needs_config = false;
+                                self.config_error = None;
+                                self.save_config();
+                                self.start();
+                            }
+                            Err(e) => self.config_error = Some(e),
+                        }
                     }
                 });
         } else {
             // Execution mode  
-            egui::Window::new(format!("Raw Command: {}", self.command))
+            egui::Window::new(format!("Raw Command: {}", crate::redaction::mask_secrets(self.command.lines().next().unwrap_or(""))))
                 .id(egui::Id::new(format!("raw_widget_{}", self.id)))
                 .open(&mut open)
                 .default_pos([250.0 + (idx as f32 * 50.0), 100.0 + (idx as f32 * 50.0)])
                 .default_size([600.0, 400.0])
                 .resizable(true)
-                .show(ctx, |ui| {
+                .show_for_widget(ctx, self.id, |ui| {
                     ui.horizontal(|ui| {
                         refresh_clicked = self.render_controls(ui);
                         
                         ui.separator();
-                        ui.label("Command:");
-                        let old_command = self.command.clone();
-                        ui.text_edit_singleline(&mut self.command);
-                        if old_command != self.command {
+                        if ui.checkbox(&mut self.auto_scroll, "Auto-scroll")
+                            .on_hover_text("Keep the newest output in view; turn off to read back while lines keep coming")
+                            .changed() {
+                            self.save_config();
+                        }
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        if self.render_mode_selector(ui) | self.render_shell_selector(ui) {
                             self.config_unsaved = true;
-                            // Restart with new command if running
+                            // Restart under the new mode/shell if running
                             if self.executor.is_running() {
                                 self.stop();
                                 self.start();
                             }
+                            self.save_config();
                         }
                     });
                     
+                    ui.label("Command:");
+                    let (changed, run_requested) = Self::command_editor(ui, &mut self.command);
+                    if changed {
+                        self.config_unsaved = true;
+                        self.config_error = None;
+                        // Restart with new command if running
+                        if self.executor.is_running() {
+                            self.stop();
+                            self.start();
+                        }
+                    }
+                    if let Some(ref error) = self.config_error {
+                        ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                    }
+                    if run_requested {
+                        self.config_error = validate_command(&self.command).err();
+                        self.refresh();
+                    }
+                    
+                    // Watch mode shows whole screens, which a line filter would tear apart
+                    if self.mode != RawCommandMode::Watch {
+                        ui.horizontal(|ui| {
+                            let error = self.filter_error();
+                            if render_filter_box(ui, &mut self.filter_text, &mut self.filter_mode, error) {
+                                self.config_unsaved = true;
+                                self.save_config();
+                            }
+                        });
+                    }
+                    
                     ui.separator();
-                    self.render_output(ui);
+                    if self.mode == RawCommandMode::Watch {
+                        self.render_watch_output(ui);
+                    } else {
+                        self.render_output(ui);
+                    }
                 });
         }
         
@@ -148,10 +287,15 @@ impl crate::widgets::Widget for RawCommandWidget {
 // Implement the CommandWidget trait
 impl CommandWidget for RawCommandWidget {
     fn build_command(&self) -> CommandSpec {
-        // Use shell to execute the raw command
-        CommandSpec::new("sh")
-            .arg("-c")
-            .arg(&self.command)
+        // Use shell to execute the raw command, as a login shell if asked so profile PATH is set
+        let spec = CommandSpec::new(&self.shell)
+            .arg(if self.login_shell { "-lc" } else { "-c" })
+            .arg(self.command.trim());
+        if self.timeout_secs > 0 {
+            spec.timeout(Duration::from_secs(self.timeout_secs))
+        } else {
+            spec
+        }
     }
     
     fn executor(&self) -> &CommandExecutor {
@@ -162,8 +306,41 @@ impl CommandWidget for RawCommandWidget {
         &mut self.executor
     }
     
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    // Plain text output, so lines from several hosts read fine side by side
+    fn supports_host_groups(&self) -> bool {
+        true
+    }
+    
     fn execution_mode(&self) -> ExecutionMode {
-        ExecutionMode::OneShot
+        match self.mode {
+            RawCommandMode::Once => ExecutionMode::OneShot,
+            RawCommandMode::Continuous => ExecutionMode::Continuous,
+            RawCommandMode::Periodic | RawCommandMode::Watch => ExecutionMode::Periodic(Duration::from_secs(self.interval_secs)),
+        }
     }
 }
 
@@ -172,6 +349,32 @@ impl CommandOutputRenderer for RawCommandWidget {
     fn executor(&self) -> &CommandExecutor {
         &self.executor
     }
+    
+    fn shows_line(&self, line: &str) -> bool {
+        self.matches_filter(line)
+    }
+    
+    fn highlights(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        self.filter_matches(text)
+    }
+    
+    fn follows_tail(&self) -> bool {
+        self.auto_scroll
+    }
+}
+
+impl FilterableOutput for RawCommandWidget {
+    fn filter_pattern(&self) -> &str {
+        &self.filter_text
+    }
+    
+    fn filter_mode(&self) -> FilterMode {
+        self.filter_mode
+    }
+    
+    fn filter_cache(&self) -> &FilterCache {
+        &self.filter_cache
+    }
 }
 
 impl CommandControlBar for RawCommandWidget {}
@@ -183,19 +386,266 @@ impl RawCommandWidget {
             version: 0,
             command,
             needs_config: false,
+            mode: RawCommandMode::default(),
+            interval_secs: default_interval_secs(),
+            shell: default_shell(),
+            login_shell: false,
+            timeout_secs: 0,
+            filter_text: String::new(),
+            filter_mode: FilterMode::default(),
+            auto_scroll: default_auto_scroll(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
+            config_error: None,
+            filter_cache: FilterCache::default(),
         }
     }
     
+    // Mode combo box plus interval for periodic runs; returns true if changed
+    fn render_mode_selector(&mut self, ui: &mut egui::Ui) -> bool {
+        let old_mode = self.mode;
+        let old_interval = self.interval_secs;
+        let old_timeout = self.timeout_secs;
+        
+        ui.label("Mode:");
+        egui::ComboBox::from_id_salt(format!("raw_mode_{}", self.id))
+            .selected_text(self.mode.label())
+            .show_ui(ui, |ui| {
+                for mode in RawCommandMode::ALL {
+                    ui.selectable_value(&mut self.mode, *mode, mode.label());
+                }
+            });
+        
+        if matches!(self.mode, RawCommandMode::Periodic | RawCommandMode::Watch) {
+            ui.add(egui::DragValue::new(&mut self.interval_secs).range(1..=3600).suffix("s"));
+        }
+        
+        if self.mode != RawCommandMode::Continuous {
+            ui.label("Timeout:");
+            ui.add(egui::DragValue::new(&mut self.timeout_secs).range(0..=86400).suffix("s"))
+                .on_hover_text("Kill runs that take longer than this; 0 for no timeout");
+        }
+        
+        old_mode != self.mode || old_interval != self.interval_secs || old_timeout != self.timeout_secs
+    }
+    
+    // Latest run's output with changes from the previous run highlighted, like `watch -d`
+    fn render_watch_output(&self, ui: &mut egui::Ui) {
+        let redactor = self.executor.redactor();
+        let redact = |lines: &Vec<String>| -> Vec<String> {
+            lines.iter().map(|line| redactor.for_display(line).into_owned()).collect()
+        };
+        let output = redact(&self.executor.output.lock().unwrap());
+        let previous = redact(&self.executor.previous_output.lock().unwrap());
+        
+        ui.label(format!("Every {}s, changes since the previous run are highlighted", self.interval_secs));
+        
+        let font = egui::FontId::monospace(12.0);
+        let text_color = ui.visuals().text_color();
+        let highlight = ui.visuals().selection.bg_fill;
+        
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (i, line) in output.iter().enumerate() {
+                    let changed = match previous.get(i) {
+                        // Nothing to compare against on the first run
+                        _ if previous.is_empty() => None,
+                        Some(old) => changed_char_range(old, line),
+                        None => Some((0, line.chars().count())),
+                    };
+                    
+                    let mut job = egui::text::LayoutJob::default();
+                    match changed {
+                        Some((start, end)) => {
+                            let start_byte = char_to_byte(line, start);
+                            let end_byte = char_to_byte(line, end);
+                            let plain = egui::TextFormat::simple(font.clone(), text_color);
+                            let marked = egui::TextFormat { background: highlight, ..plain.clone() };
+                            job.append(&line[..start_byte], 0.0, plain.clone());
+                            job.append(&line[start_byte..end_byte], 0.0, marked);
+                            job.append(&line[end_byte..], 0.0, plain);
+                        }
+                        None => {
+                            job.append(line, 0.0, egui::TextFormat::simple(font.clone(), text_color));
+                        }
+                    }
+                    ui.label(job);
+                }
+            });
+    }
+    
+    // Shell combo box and login shell toggle; returns true if changed
+    fn render_shell_selector(&mut self, ui: &mut egui::Ui) -> bool {
+        let old_shell = self.shell.clone();
+        let old_login = self.login_shell;
+        
+        ui.separator();
+        ui.label("Shell:");
+        egui::ComboBox::from_id_salt(format!("raw_shell_{}", self.id))
+            .selected_text(&self.shell)
+            .width(60.0)
+            .show_ui(ui, |ui| {
+                for shell in SHELLS {
+                    ui.selectable_value(&mut self.shell, shell.to_string(), *shell);
+                }
+            });
+        ui.checkbox(&mut self.login_shell, "Login shell")
+            .on_hover_text("Run via a login shell so PATH from .profile/.zprofile is picked up");
+        
+        old_shell != self.shell || old_login != self.login_shell
+    }
+    
+    // Multi-line monospace command editor; returns (changed, Cmd+Enter pressed)
+    fn command_editor(ui: &mut egui::Ui, command: &mut String) -> (bool, bool) {
+        let response = ui.add(egui::TextEdit::multiline(command)
+            .font(egui::TextStyle::Monospace)
+            .desired_rows(3)
+            .desired_width(f32::INFINITY));
+        
+        let run_requested = response.has_focus() && ui.input_mut(|i| i.consume_shortcut(&RUN_SHORTCUT));
+        
+        if crate::redaction::contains_secret(command) {
+            ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "⚠ This command appears to contain a secret")
+                .on_hover_text("It is masked in the window title and in recorded output, but kept as typed so the command can run");
+        }
+        
+        (response.changed(), run_requested)
+    }
+    
     pub fn new_with_config(id: usize) -> Self {
         Self {
             id,
             version: 0,
             command: String::new(),
             needs_config: true,
+            mode: RawCommandMode::default(),
+            interval_secs: default_interval_secs(),
+            shell: default_shell(),
+            login_shell: false,
+            timeout_secs: 0,
+            filter_text: String::new(),
+            filter_mode: FilterMode::default(),
+            auto_scroll: default_auto_scroll(),
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
+            config_error: None,
+            filter_cache: FilterCache::default(),
         }
     }
-}
\ No newline at end of file
+}
+
+// Trimmed command, or an error to show inline if there is nothing to run
+fn validate_command(command: &str) -> Result<&str, String> {
+    let command = command.trim();
+    if command.is_empty() {
+        Err("Enter a command to run".to_string())
+    } else {
+        Ok(command)
+    }
+}
+
+// Char range of `new` that differs from `old`, found by trimming the common prefix and suffix
+fn changed_char_range(old: &str, new: &str) -> Option<(usize, usize)> {
+    if old == new {
+        return None;
+    }
+    
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    
+    let prefix = old_chars.iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_chars[prefix..].iter().rev()
+        .zip(new_chars[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    
+    Some((prefix, new_chars.len() - suffix))
+}
+
+fn char_to_byte(s: &str, char_idx: usize) -> usize {
+    s.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(s.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn empty_command_is_rejected() {
+        assert!(validate_command("").is_err());
+    }
+    
+    #[test]
+    fn whitespace_only_command_is_rejected() {
+        assert!(validate_command("  \t\n  ").is_err());
+    }
+    
+    #[test]
+    fn command_is_trimmed_before_running() {
+        let mut widget = RawCommandWidget::new(0, "  ls -la\n".to_string());
+        assert_eq!(validate_command(&widget.command), Ok("ls -la"));
+        assert_eq!(widget.build_command().args, vec!["-c", "ls -la"]);
+        
+        widget.login_shell = true;
+        assert_eq!(widget.build_command().args, vec!["-lc", "ls -la"]);
+    }
+    
+    #[test]
+    fn quoted_arguments_are_passed_to_the_shell_intact() {
+        let widget = RawCommandWidget::new(0, "printf '%s|' \"a b\" 'c d'".to_string());
+        let spec = widget.build_command();
+        let output = std::process::Command::new(&spec.program).args(&spec.args).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a b|c d|");
+    }
+    
+    #[test]
+    fn host_group_fans_out_to_each_member() {
+        let widget = RawCommandWidget::new(0, "uptime".to_string());
+        let member = |name: &str, alias: &str, group: &str| crate::database::investigation_db::Host {
+            name: name.to_string(),
+            group: group.to_string(),
+            ..crate::database::investigation_db::Host::for_test(alias, "")
+        };
+        widget.executor.set_available_hosts(vec![member("Web 1", "web-1", "web"), member("DB", "db-1", "db"), member("Web 2", "web-2", "web")]);
+        widget.executor.set_selected_group(Some("web".to_string()));
+        
+        let spec = widget.wrap_for_host(widget.build_command(), &widget.execution_mode());
+        let targets: Vec<(&str, &str)> = spec.fan_out.iter().map(|(name, spec)| (name.as_str(), spec.args[spec.args.len() - 2].as_str())).collect();
+        assert_eq!(targets, vec![("Web 1", "web-1"), ("Web 2", "web-2")]);
+        assert!(spec.fan_out.iter().all(|(_, spec)| spec.program == "ssh" && spec.args.last().unwrap() == "sh -c uptime"));
+    }
+    
+    #[test]
+    fn stream_choices_survive_a_reload() {
+        use crate::widgets::{Widget, command_widget::StreamSelection};
+        let mut widget = RawCommandWidget::new(0, "make".to_string());
+        let streams = StreamSettings { display: StreamSelection::Both, record: StreamSelection::StderrOnly };
+        widget.set_saved_stream_settings(streams);
+        
+        let mut reloaded: RawCommandWidget = serde_json::from_str(&serde_json::to_string(&widget).unwrap()).unwrap();
+        assert_eq!(reloaded.executor.stream_settings(), StreamSettings::default());
+        reloaded.restore_widget_data(Vec::new());
+        assert_eq!(reloaded.executor.stream_settings(), streams);
+    }
+    
+    #[test]
+    fn timestamp_column_survives_a_reload() {
+        use crate::widgets::Widget;
+        let mut widget = RawCommandWidget::new(0, "make".to_string());
+        widget.set_saved_timestamp_display(TimestampDisplay::Absolute);
+        
+        let mut reloaded: RawCommandWidget = serde_json::from_str(&serde_json::to_string(&widget).unwrap()).unwrap();
+        reloaded.restore_widget_data(Vec::new());
+        assert_eq!(reloaded.executor.timestamp_display(), TimestampDisplay::Absolute);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// A lookup that takes longer than this leaves the address as it is
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+enum Lookup {
+    Pending,
+    Resolved(String),
+    Unresolved,
+}
+
+// Shared by every widget for the rest of the session, failures included, so nothing is looked up twice
+fn cache() -> &'static Mutex<HashMap<IpAddr, Lookup>> {
+    static CACHE: OnceLock<Mutex<HashMap<IpAddr, Lookup>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+// The cached name for `ip`, starting a lookup in the background the first time it's asked for.
+// None until the lookup finishes, or for good if it fails; a repaint is requested when it's done
+pub fn hostname(ip: IpAddr) -> Option<String> {
+    let mut entries = cache().lock().unwrap();
+    match entries.get(&ip) {
+        Some(Lookup::Resolved(name)) => return Some(name.clone()),
+        Some(Lookup::Pending | Lookup::Unresolved) => return None,
+        None => {}
+    }
+    entries.insert(ip, Lookup::Pending);
+    drop(entries);
+    
+    crate::runtime::runtime().spawn(async move {
+        let lookup = tokio::time::timeout(LOOKUP_TIMEOUT, tokio::task::spawn_blocking(move || lookup_name(ip))).await;
+        let result = match lookup {
+            Ok(Ok(Some(name))) => Lookup::Resolved(name),
+            _ => Lookup::Unresolved,
+        };
+        cache().lock().unwrap().insert(ip, result);
+        crate::runtime::request_repaint();
+    });
+    None
+}
+
+// The address part of "93.184.216.34:443", "[::1]:631" or macOS's "93.184.216.34.443";
+// wildcards like "0.0.0.0:*" and "*.*" have nothing to look up
+pub fn address_ip(address: &str) -> Option<IpAddr> {
+    let (host, _port) = address.rsplit_once([':', '.'])?;
+    let ip: IpAddr = host.trim_start_matches('[').trim_end_matches(']').parse().ok()?;
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+// Reverse DNS through getnameinfo; blocks, so it runs on tokio's blocking pool
+#[cfg(unix)]
+fn lookup_name(ip: IpAddr) -> Option<String> {
+    let mut host = [0 as libc::c_char; libc::NI_MAXHOST as usize];
+    let mut lookup = |address: *const libc::sockaddr, length: usize| unsafe {
+        libc::getnameinfo(address, length as libc::socklen_t, host.as_mut_ptr(), host.len() as libc::socklen_t, std::ptr::null_mut(), 0, libc::NI_NAMEREQD)
+    };
+    
+    let status = match ip {
+        IpAddr::V4(ip) => {
+            let mut address: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+            address.sin_family = libc::AF_INET as libc::sa_family_t;
+            address.sin_addr.s_addr = u32::from_ne_bytes(ip.octets());
+            #[cfg(target_os = "macos")]
+            {
+                address.sin_len = std::mem::size_of::<libc::sockaddr_in>() as u8;
+            }
+            lookup(&address as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_in>())
+        }
+        IpAddr::V6(ip) => {
+            let mut address: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+            address.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            address.sin6_addr.s6_addr = ip.octets();
+            #[cfg(target_os = "macos")]
+            {
+                address.sin6_len = std::mem::size_of::<libc::sockaddr_in6>() as u8;
+            }
+            lookup(&address as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_in6>())
+        }
+    };
+    if status != 0 {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(host.as_ptr()) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+// No libc elsewhere; addresses stay as they are
+#[cfg(not(unix))]
+fn lookup_name(_ip: IpAddr) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn addresses_are_split_from_ports_in_either_netstat_format() {
+        assert_eq!(address_ip("93.184.216.34:443"), Some("93.184.216.34".parse().unwrap()));
+        assert_eq!(address_ip("93.184.216.34.443"), Some("93.184.216.34".parse().unwrap()));
+        assert_eq!(address_ip("2606:2800:220:1::248:443"), Some("2606:2800:220:1::248".parse().unwrap()));
+        assert_eq!(address_ip("[::1]:631"), Some("::1".parse().unwrap()));
+        assert_eq!(address_ip("0.0.0.0:*"), None);
+        assert_eq!(address_ip("*.*"), None);
+    }
+}
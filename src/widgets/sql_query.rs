@@ -0,0 +1,163 @@
+use std::sync::{Arc, Mutex};
+use eframe::egui;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::database::investigation_db::{InvestigationDB, QueryResult};
+
+const MAX_ROWS: usize = 1000;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SqlQueryWidget {
+    pub id: usize,
+    pub version: i32,
+    pub query: String,
+    #[serde(skip, default)]
+    pub database: Option<Arc<InvestigationDB>>,
+    #[serde(skip, default = "default_result")]
+    pub result: Arc<Mutex<Result<QueryResult, String>>>,
+}
+
+fn default_result() -> Arc<Mutex<Result<QueryResult, String>>> {
+    Arc::new(Mutex::new(Ok(QueryResult::default())))
+}
+
+impl crate::widgets::Widget for SqlQueryWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "sql_query"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+    }
+    
+    fn set_database(&mut self, database: Option<Arc<InvestigationDB>>) {
+        self.database = database;
+    }
+    
+    fn restore_widget_data(&mut self, _data: Vec<crate::widgets::command_widget::TimedLine>) {
+        // SQL query widget reads the investigation directly, nothing to restore
+    }
+    
+    fn start(&self) {
+        self.run_query();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        
+        egui::Window::new("SQL Query")
+            .id(egui::Id::new(format!("sql_query_{}", self.id)))
+            .open(&mut open)
+            .default_pos([200.0 + (idx as f32 * 50.0), 120.0 + (idx as f32 * 50.0)])
+            .default_size([700.0, 450.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.label("Read-only SELECT against this investigation (tables: widgets, raw_data, hosts, metadata)");
+                
+                ui.add(egui::TextEdit::multiline(&mut self.query)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY));
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Run").clicked() {
+                        self.run_query();
+                        self.save_config();
+                    }
+                    
+                    if let Ok(ref result) = *self.result.lock().unwrap() {
+                        let suffix = if result.truncated { format!(" (first {} shown)", MAX_ROWS) } else { String::new() };
+                        ui.label(format!("{} rows{}", result.rows.len(), suffix));
+                    }
+                });
+                
+                ui.separator();
+                self.render_result(ui);
+            });
+        
+        (open, false)
+    }
+    
+    fn refresh(&self) {
+        self.run_query();
+    }
+}
+
+impl SqlQueryWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            query: "SELECT widget_id, COUNT(*) AS lines, MIN(timestamp) AS first_seen, MAX(timestamp) AS last_seen\nFROM raw_data GROUP BY widget_id".to_string(),
+            database: None,
+            result: default_result(),
+        }
+    }
+    
+    fn run_query(&self) {
+        let Some(ref db) = self.database else {
+            *self.result.lock().unwrap() = Err("No investigation database available".to_string());
+            return;
+        };
+        
+        let rt = crate::runtime::runtime();
+        let result = rt.block_on(db.run_read_only_query(&self.query, MAX_ROWS))
+            .map_err(|e| e.to_string());
+        *self.result.lock().unwrap() = result;
+    }
+    
+    // Save the widget config to the investigation database
+    fn save_config(&self) {
+        if let Some(ref db) = self.database {
+            let widget = crate::widgets::WidgetType::SqlQuery(self.clone());
+            let rt = crate::runtime::runtime();
+            if let Err(e) = rt.block_on(db.save_widget_instance(&widget)) {
+                eprintln!("Failed to save SQL query config change: {}", e);
+            }
+        }
+    }
+    
+    fn render_result(&self, ui: &mut egui::Ui) {
+        let result = self.result.lock().unwrap();
+        
+        match *result {
+            Err(ref e) => {
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("❌ {}", e));
+            }
+            Ok(ref result) if result.columns.is_empty() => {
+                ui.label("No results");
+            }
+            Ok(ref result) => {
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        egui::Grid::new(format!("sql_result_{}", self.id))
+                            .num_columns(result.columns.len())
+                            .spacing([20.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for column in &result.columns {
+                                    ui.strong(column);
+                                }
+                                ui.end_row();
+                                
+                                for row in &result.rows {
+                                    for value in row {
+                                        ui.monospace(value);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            }
+        }
+    }
+}
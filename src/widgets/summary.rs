@@ -0,0 +1,354 @@
+use eframe::egui;
+use crate::widgets::WidgetWindow;
+use serde::{Serialize, Deserialize};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, format_bytes, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandControlBar};
+use crate::widgets::cpu_monitor::{latest_cpu_sample, CpuSample};
+
+// One run gathers everything; each section starts with a "== name" marker line
+const SUMMARY_SCRIPT: &str = "\
+echo '== hostname'; hostname; \
+echo '== uptime'; uptime; \
+echo '== vmstat'; vmstat 1 2; \
+echo '== memory'; free -b; \
+echo '== disk'; df -Pk /; \
+echo '== processes'; ps -Ao pid=,pcpu=,comm=";
+
+const TOP_PROCESSES: usize = 3;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SummaryWidget {
+    pub id: usize,
+    pub version: i32,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
+    #[serde(skip, default = "default_executor")]
+    pub executor: CommandExecutor,
+}
+
+fn default_executor() -> CommandExecutor {
+    CommandExecutor::new()
+}
+
+// A condensed snapshot of one box
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemSummary {
+    pub hostname: Option<String>,
+    pub uptime: Option<String>,
+    pub load: Option<String>,
+    pub cpu: Option<CpuSample>,
+    pub memory: Option<(u64, u64)>,  // Used and total bytes
+    pub disk: Option<(u64, u64, String)>,  // Used and total bytes of /, and df's capacity
+    pub top_processes: Vec<(String, f32, String)>,  // PID, CPU% and command, busiest first
+}
+
+pub fn parse_summary(lines: &[String]) -> SystemSummary {
+    let mut summary = SystemSummary::default();
+    let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+    for line in lines {
+        if let Some(name) = line.strip_prefix("== ") {
+            sections.push((name.trim(), Vec::new()));
+        } else if let Some((_, section)) = sections.last_mut() {
+            section.push(line.clone());
+        }
+    }
+    
+    for (name, section) in &sections {
+        match *name {
+            "hostname" => summary.hostname = section.first().map(|line| line.trim().to_string()),
+            "uptime" => {
+                if let Some(line) = section.first() {
+                    (summary.uptime, summary.load) = parse_uptime(line);
+                }
+            }
+            // The first vmstat sample is averaged since boot, so the cpu monitor's "latest" is the one we want
+            "vmstat" => summary.cpu = latest_cpu_sample(section),
+            "memory" => {
+                summary.memory = section.iter()
+                    .find(|line| line.starts_with("Mem:"))
+                    .and_then(|line| {
+                        let fields: Vec<u64> = line.split_whitespace().skip(1).filter_map(|field| field.parse().ok()).collect();
+                        Some((*fields.get(1)?, *fields.first()?))
+                    });
+            }
+            "disk" => {
+                summary.disk = section.get(1).and_then(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    let total: u64 = fields.get(1)?.parse().ok()?;
+                    let used: u64 = fields.get(2)?.parse().ok()?;
+                    Some((used * 1024, total * 1024, fields.get(4)?.to_string()))
+                });
+            }
+            "processes" => {
+                let mut processes: Vec<(String, f32, String)> = section.iter()
+                    .filter_map(|line| {
+                        let mut fields = line.split_whitespace();
+                        let pid = fields.next()?.to_string();
+                        let cpu = fields.next()?.parse().ok()?;
+                        let command = fields.collect::<Vec<_>>().join(" ");
+                        Some((pid, cpu, command))
+                    })
+                    .collect();
+                processes.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+                processes.truncate(TOP_PROCESSES);
+                summary.top_processes = processes;
+            }
+            _ => {}
+        }
+    }
+    
+    summary
+}
+
+// " 12:00:00 up 3 days,  4:05,  2 users,  load average: 0.52, 0.58, 0.59" (macOS says "load averages:")
+fn parse_uptime(line: &str) -> (Option<String>, Option<String>) {
+    let load = line.split_once("load average")
+        .and_then(|(_, rest)| rest.split_once(':'))
+        .map(|(_, values)| values.trim().replace(", ", " ").replace(',', " "));
+    let uptime = line.split_once(" up ").map(|(_, rest)| {
+        let rest = rest.split(" load average").next().unwrap_or(rest);
+        // Drop the user count and the trailing separators
+        let parts: Vec<&str> = rest.split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty() && !part.ends_with("user") && !part.ends_with("users"))
+            .collect();
+        parts.join(", ")
+    });
+    (uptime, load)
+}
+
+impl crate::widgets::Widget for SummaryWidget {
+    fn widget_type_name(&self) -> &'static str {
+        "summary"
+    }
+    
+    fn widget_id(&self) -> usize {
+        self.id
+    }
+    
+    fn widget_version(&self) -> i32 {
+        self.version
+    }
+    
+    fn increment_version(&mut self) {
+        self.version += 1;
+    }
+    
+    fn set_database(&mut self, database: Option<std::sync::Arc<crate::database::investigation_db::InvestigationDB>>) {
+        let widget_id = self.id as i32;
+        let widget_version = self.version;
+        self.executor.set_database(database, widget_id, widget_version);
+    }
+    
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
+    }
+    
+    fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
+    }
+    
+    fn start(&self) {
+        self.start_command();
+    }
+    
+    fn stop(&self) {
+        self.stop_command();
+    }
+    
+    fn render(&mut self, ctx: &egui::Context, idx: usize) -> (bool, bool) {
+        let mut open = true;
+        let mut refresh_clicked = false;
+        
+        egui::Window::new("System Summary")
+            .id(egui::Id::new(format!("summary_widget_{}", self.id)))
+            .open(&mut open)
+            .default_pos([250.0 + (idx as f32 * 50.0), 100.0 + (idx as f32 * 50.0)])
+            .default_size([360.0, 260.0])
+            .resizable(true)
+            .show_for_widget(ctx, self.id, |ui| {
+                ui.horizontal(|ui| {
+                    refresh_clicked = self.render_controls(ui);
+                });
+                
+                ui.separator();
+                let summary = parse_summary(&self.executor.output.lock().unwrap());
+                self.render_summary(ui, &summary);
+            });
+        
+        (open, refresh_clicked)
+    }
+    
+    fn refresh(&self) {
+        self.stop();
+        self.start();
+    }
+}
+
+impl CommandWidget for SummaryWidget {
+    fn build_command(&self) -> CommandSpec {
+        CommandSpec::new("sh")
+            .arg("-c")
+            .arg(SUMMARY_SCRIPT)
+    }
+    
+    fn executor(&self) -> &CommandExecutor {
+        &self.executor
+    }
+    
+    fn executor_mut(&mut self) -> &mut CommandExecutor {
+        &mut self.executor
+    }
+    
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
+    fn execution_mode(&self) -> ExecutionMode {
+        ExecutionMode::OneShot
+    }
+}
+
+impl CommandControlBar for SummaryWidget {}
+
+impl SummaryWidget {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            version: 0,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
+            executor: CommandExecutor::new(),
+        }
+    }
+    
+    fn render_summary(&self, ui: &mut egui::Ui, summary: &SystemSummary) {
+        let missing = "-".to_string();
+        
+        egui::Grid::new(format!("summary_{}", self.id))
+            .num_columns(2)
+            .spacing([16.0, 4.0])
+            .show(ui, |ui| {
+                ui.strong("Host");
+                ui.label(summary.hostname.as_ref().unwrap_or(&missing));
+                ui.end_row();
+                
+                ui.strong("Uptime");
+                ui.label(summary.uptime.as_ref().unwrap_or(&missing));
+                ui.end_row();
+                
+                ui.strong("Load");
+                ui.monospace(summary.load.as_ref().unwrap_or(&missing));
+                ui.end_row();
+                
+                ui.strong("CPU");
+                match summary.cpu {
+                    Some(cpu) => ui.monospace(format!("{:.0}%  (user {:.0}%, system {:.0}%, wait {:.0}%)", cpu.total(), cpu.user, cpu.system, cpu.wait)),
+                    None => ui.monospace(&missing),
+                };
+                ui.end_row();
+                
+                ui.strong("Memory");
+                match summary.memory {
+                    Some((used, total)) if total > 0 => ui.monospace(format!("{} / {}  ({:.0}%)",
+                        format_bytes(used as f64), format_bytes(total as f64), used as f64 * 100.0 / total as f64)),
+                    _ => ui.monospace(&missing),
+                };
+                ui.end_row();
+                
+                ui.strong("Disk /");
+                match summary.disk {
+                    Some((used, total, ref capacity)) => ui.monospace(format!("{} / {}  ({})",
+                        format_bytes(used as f64), format_bytes(total as f64), capacity)),
+                    None => ui.monospace(&missing),
+                };
+                ui.end_row();
+                
+                ui.strong("Top CPU");
+                ui.vertical(|ui| {
+                    if summary.top_processes.is_empty() {
+                        ui.monospace(&missing);
+                    }
+                    for (pid, cpu, command) in &summary.top_processes {
+                        ui.monospace(format!("{:>5.1}%  {:>7}  {}", cpu, pid, command));
+                    }
+                });
+                ui.end_row();
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sections_are_parsed_into_a_summary() {
+        let output = "\
+== hostname
+web-1
+== uptime
+ 12:00:00 up 3 days,  4:05,  2 users,  load average: 0.52, 0.58, 0.59
+== vmstat
+procs -----------memory---------- ---swap-- -----io---- -system-- ------cpu-----
+ r  b   swpd   free   buff  cache   si   so    bi    bo   in   cs us sy id wa st
+ 1  0      0 812344  10240 204800    0    0     5     9   50   80  3  1 95  1  0
+ 2  0      0 812000  10240 204800    0    0     0     0  400  900 20 10 68  2  0
+== memory
+               total        used        free      shared  buff/cache   available
+Mem:      2000000000   500000000  1000000000     1000000   500000000  1400000000
+Swap:              0           0           0
+== disk
+Filesystem     1024-blocks     Used Available Capacity Mounted on
+/dev/root         10000000  4500000   5500000      45% /
+== processes
+    1  0.0 systemd
+  812 12.5 postgres
+  913 40.0 java
+ 1001  3.2 nginx
+Command completed";
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        
+        let summary = parse_summary(&lines);
+        assert_eq!(summary.hostname.as_deref(), Some("web-1"));
+        assert_eq!(summary.uptime.as_deref(), Some("3 days, 4:05"));
+        assert_eq!(summary.load.as_deref(), Some("0.52 0.58 0.59"));
+        assert_eq!(summary.cpu.map(|cpu| cpu.total()), Some(32.0));
+        assert_eq!(summary.memory, Some((500000000, 2000000000)));
+        assert_eq!(summary.disk, Some((4500000 * 1024, 10000000 * 1024, "45%".to_string())));
+        let top: Vec<&str> = summary.top_processes.iter().map(|(_, _, command)| command.as_str()).collect();
+        assert_eq!(top, vec!["java", "postgres", "nginx"]);
+    }
+}
@@ -1,6 +1,7 @@
 use eframe::egui;
+use crate::widgets::WidgetWindow;
 use serde::{Serialize, Deserialize};
-use crate::widgets::command_widget::{CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
+use crate::widgets::command_widget::{TimedLine, StreamSettings, TimestampDisplay, CommandExecutor, CommandSpec, CommandWidget, ExecutionMode, CommandOutputRenderer, CommandControlBar};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SystemInfoWidget {
@@ -8,6 +9,12 @@ pub struct SystemInfoWidget {
     pub version: i32,
     pub info_type: String,
     pub needs_config: bool,
+    #[serde(default)]
+    pub max_lines: Option<usize>,  // Output lines kept in memory; None for the executor's default
+    #[serde(default)]
+    pub streams: Option<StreamSettings>,  // Which of stdout/stderr to show and record; None for both
+    #[serde(default)]
+    pub timestamps: Option<TimestampDisplay>,  // Arrival time in front of each line; None for off
     #[serde(skip, default = "default_executor")]
     pub executor: CommandExecutor,
     #[serde(skip, default)]
@@ -42,22 +49,26 @@ impl crate::widgets::Widget for SystemInfoWidget {
         self.executor.set_database(database, widget_id, widget_version);
     }
     
-    fn config_changed(&self) -> bool {
-        self.config_unsaved
-    }
-    
     
     fn needs_restart(&self) -> bool {
         // System info needs restart when info type changes (different commands)
         self.config_unsaved
     }
     
-    fn restore_widget_data(&mut self, data: Vec<String>) {
-        self.executor.load_historical_output(data);
+    fn restore_widget_data(&mut self, data: Vec<TimedLine>) {
+        self.restore_output(data);
     }
     
     fn set_available_hosts(&mut self, hosts: Vec<crate::database::investigation_db::Host>) {
-        self.executor.set_available_hosts(hosts);
+        self.update_available_hosts(hosts);
+    }
+    
+    fn host_edited(&mut self, from: &crate::database::investigation_db::Host, to: &crate::database::investigation_db::Host, hosts: Vec<crate::database::investigation_db::Host>) {
+        self.follow_host_edit(from, to, hosts);
+    }
+    
+    fn resource_usage(&self) -> Option<crate::widgets::command_widget::WidgetUsage> {
+        Some(self.executor.usage())
     }
     
     fn start(&self) {
@@ -114,7 +125,7 @@ impl crate::widgets::Widget for SystemInfoWidget {
                 .default_pos([250.0 + (idx as f32 * 50.0), 100.0 + (idx as f32 * 50.0)])
                 .default_size([600.0, 400.0])
                 .resizable(true)
-                .show(ctx, |ui| {
+                .show_for_widget(ctx, self.id, |ui| {
                     ui.horizontal(|ui| {
                         refresh_clicked = self.render_controls(ui);
                         
@@ -170,6 +181,30 @@ impl CommandWidget for SystemInfoWidget {
         &mut self.executor
     }
     
+    fn saved_max_lines(&self) -> Option<usize> {
+        self.max_lines
+    }
+    
+    fn set_saved_max_lines(&mut self, max: usize) {
+        self.max_lines = Some(max);
+    }
+    
+    fn saved_stream_settings(&self) -> Option<StreamSettings> {
+        self.streams
+    }
+    
+    fn set_saved_stream_settings(&mut self, settings: StreamSettings) {
+        self.streams = Some(settings);
+    }
+    
+    fn saved_timestamp_display(&self) -> Option<TimestampDisplay> {
+        self.timestamps
+    }
+    
+    fn set_saved_timestamp_display(&mut self, display: TimestampDisplay) {
+        self.timestamps = Some(display);
+    }
+    
     fn execution_mode(&self) -> ExecutionMode {
         ExecutionMode::OneShot
     }
@@ -191,6 +226,9 @@ impl SystemInfoWidget {
             version: 0,
             info_type: "overview".to_string(),
             needs_config: false,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
         }
@@ -202,6 +240,9 @@ impl SystemInfoWidget {
             version: 0,
             info_type: String::new(),
             needs_config: true,
+            max_lines: None,
+            streams: None,
+            timestamps: None,
             executor: CommandExecutor::new(),
             config_unsaved: false,
         }